@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use crate::structs::{Diagnostic, FileData, Severity};
+
+// Renders diagnostics in the style of rustc, quoting the offending source line:
+//
+// error: expected a class name after `class`, found `(`
+//   --> src/models.py:12:7
+//    |
+// 12 | class (Base):
+//    |       ^
+pub struct Reporter<'a> {
+    files: HashMap<&'a str, &'a FileData>,
+}
+
+impl<'a> Reporter<'a> {
+    pub fn new(files: &'a [FileData]) -> Reporter<'a> {
+        let mut hash = HashMap::new();
+        for file in files {
+            hash.insert(file.filepath.as_str(), file);
+        }
+
+        Reporter {
+            files: hash,
+        }
+    }
+
+    pub fn report(&self, diagnostics: &[Diagnostic]) -> String {
+        let mut sorted: Vec<&Diagnostic> = diagnostics.iter().collect();
+        sorted.sort_by(|a, b| {
+            (&a.filepath, a.line, a.column).cmp(&(&b.filepath, b.line, b.column))
+        });
+
+        let mut report = String::new();
+        for diagnostic in sorted {
+            report.push_str(&self.render(diagnostic));
+            report.push('\n');
+        }
+
+        let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+        let warnings = diagnostics.iter().filter(|d| d.severity == Severity::Warning).count();
+
+        if errors > 0 {
+            report.push_str(&format!("error: {} {} emitted\n", errors, plural(errors, "error")));
+        }
+        if warnings > 0 {
+            report.push_str(&format!("warning: {} {} emitted\n", warnings, plural(warnings, "warning")));
+        }
+
+        report
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut rendered = format!("{}: {}\n", diagnostic.severity, diagnostic.message);
+
+        let mut location = diagnostic.filepath.clone();
+        if diagnostic.line > 0 {
+            location.push_str(&format!(":{}", diagnostic.line));
+        }
+        if diagnostic.column > 0 {
+            location.push_str(&format!(":{}", diagnostic.column));
+        }
+
+        let source = self.files
+            .get(diagnostic.filepath.as_str())
+            .and_then(|file| file.lines.get(&diagnostic.line));
+
+        let source = match source {
+            Some(source) => source,
+            None => {
+                rendered.push_str(&format!(" --> {}\n", location));
+                return rendered
+            },
+        };
+
+        let gutter = " ".repeat(diagnostic.line.to_string().len());
+        let (start, width) = underline(source, diagnostic.column, diagnostic.length);
+
+        rendered.push_str(&format!("{}--> {}\n", gutter, location));
+        rendered.push_str(&format!("{} |\n", gutter));
+        rendered.push_str(&format!("{} | {}\n", diagnostic.line, source.replace('\t', "    ")));
+        rendered.push_str(&format!("{} | {}{}\n", gutter, " ".repeat(start), "^".repeat(width)));
+
+        rendered
+    }
+}
+
+// Offset and width of the underline, in display columns. Without a column the whole line is underlined
+fn underline(source: &str, column: usize, length: usize) -> (usize, usize) {
+    let widths: Vec<usize> = source
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .collect();
+
+    if column > 0 {
+        let start = widths.iter().take(column - 1).sum();
+        let width = widths.iter().skip(column - 1).take(length).sum::<usize>().max(1);
+        return (start, width)
+    }
+
+    let indent = source.chars().take_while(|c| c.is_whitespace()).count();
+    let end = source.trim_end().chars().count().max(indent);
+    let start = widths[..indent].iter().sum();
+    let width = widths[indent..end].iter().sum::<usize>().max(1);
+
+    (start, width)
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Reporter;
+    use crate::structs::{Diagnostic, FileData, Severity};
+
+    fn file(lines: &[&str]) -> FileData {
+        FileData {
+            file_no: 1,
+            file_type: String::from("python"),
+            filepath: String::from("src/models.py"),
+            lines: lines.iter().enumerate().map(|(index, line)| (index as i64 + 1, line.to_string())).collect::<HashMap<i64, String>>(),
+        }
+    }
+
+    fn diagnostic(line: i64, column: usize, length: usize) -> Diagnostic {
+        Diagnostic {
+            filepath: String::from("src/models.py"),
+            line,
+            column,
+            length,
+            severity: Severity::Error,
+            message: String::from("expected a class name after `class`, found `(`"),
+        }
+    }
+
+    #[test]
+    fn the_column_is_underlined_below_the_source_line() {
+        let files = [file(&["class (Base):"])];
+        let rendered = Reporter::new(&files).render(&diagnostic(1, 7, 1));
+
+        assert_eq!(rendered, "error: expected a class name after `class`, found `(`\n --> src/models.py:1:7\n  |\n1 | class (Base):\n  |       ^\n");
+    }
+
+    #[test]
+    fn tabs_are_underlined_as_four_columns() {
+        let files = [file(&["\tclass (Base):"])];
+        let rendered = Reporter::new(&files).render(&diagnostic(1, 8, 6));
+
+        assert!(rendered.ends_with("1 |     class (Base):\n  |           ^^^^^^\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn without_a_column_the_line_is_underlined_past_its_indent() {
+        let files = [file(&["    x = 1  "])];
+        let rendered = Reporter::new(&files).render(&diagnostic(1, 0, 0));
+
+        assert!(rendered.contains(" --> src/models.py:1\n"), "{}", rendered);
+        assert!(rendered.ends_with("  |     ^^^^^\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn reports_are_sorted_and_counted() {
+        let files = [file(&["class (Base):", "class (Other):"])];
+        let mut warning = diagnostic(1, 7, 1);
+        warning.severity = Severity::Warning;
+        let report = Reporter::new(&files).report(&[diagnostic(2, 7, 1), warning]);
+
+        assert!(report.find(":1:7").unwrap() < report.find(":2:7").unwrap());
+        assert!(report.ends_with("error: 1 error emitted\nwarning: 1 warning emitted\n"), "{}", report);
+    }
+}
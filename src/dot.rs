@@ -0,0 +1,220 @@
+use crate::structs::{visibility, Function, Hierarchy, Variable};
+
+// Renders part of a hierarchy as a Graphviz digraph, one record node per object with
+// its fields and methods, clustered by source file. Edges point from child to parent,
+// dashed where a class implements an interface or trait, dotted with a label where
+// a ruby module is mixed in, and with a diamond at the child where a go struct embeds a type:
+//
+// digraph classtree {
+//     rankdir=BT;
+//     subgraph cluster_0 {
+//         label="models/base.py";
+//         n0 [label="{Base|+ name: str\l|+ greet(other: Base): str\l}"];
+//         n1 [label="{Child||}"];
+//     }
+//     n1 -> n0;
+// }
+pub struct Dot<'a> {
+    hierarchy: &'a Hierarchy,
+    rankdir: String,
+    hide_private: bool,
+}
+
+impl<'a> Dot<'a> {
+    pub fn new(hierarchy: &'a Hierarchy, rankdir: &str, hide_private: bool) -> Dot<'a> {
+        Dot {
+            hierarchy,
+            rankdir: rankdir.to_string(),
+            hide_private,
+        }
+    }
+
+    pub fn render(&self, nodes: &[usize]) -> String {
+        let nodes: Vec<usize> = nodes
+            .iter()
+            .copied()
+            .filter(|index| !self.hide_private || self.hierarchy.nodes[*index].object.public)
+            .collect();
+
+        let mut dot = String::from("digraph classtree {\n");
+        dot.push_str(&format!("    rankdir={};\n", self.rankdir));
+        dot.push_str("    node [shape=record, fontname=\"Helvetica\", fontsize=10];\n");
+        dot.push_str("    edge [arrowhead=empty];\n");
+
+        // clusters in the order their files first appear
+        let mut files: Vec<&str> = Vec::new();
+        for index in &nodes {
+            let filepath = self.hierarchy.nodes[*index].object.filepath.as_str();
+            if !files.contains(&filepath) {
+                files.push(filepath);
+            }
+        }
+
+        for (cluster, filepath) in files.iter().enumerate() {
+            dot.push_str(&format!("\n    subgraph cluster_{} {{\n", cluster));
+            dot.push_str(&format!("        label=\"{}\";\n", escape(filepath)));
+            for index in nodes.iter().filter(|index| self.hierarchy.nodes[**index].object.filepath == *filepath) {
+                dot.push_str(&format!("        n{} [label=\"{}\"];\n", index, self.label(*index)));
+            }
+            dot.push_str("    }\n");
+        }
+
+        let mut external: Vec<&str> = Vec::new();
+        let mut edges = String::new();
+        for index in &nodes {
+            let node = &self.hierarchy.nodes[*index];
+            for parent in node.parents.iter().filter(|parent| nodes.contains(parent)) {
+                let identifier = &self.hierarchy.nodes[*parent].object.identifier;
+                if let Some(mixin) = self.hierarchy.mixin(*index, identifier) {
+                    edges.push_str(&format!("    n{} -> n{} [style=dotted, label=\"{}\"];\n", index, parent, mixin));
+                } else if self.hierarchy.embeds(*index, identifier) {
+                    edges.push_str(&format!("    n{} -> n{} [dir=back, arrowtail=diamond];\n", index, parent));
+                } else if self.hierarchy.implements(*index, *parent) {
+                    edges.push_str(&format!("    n{} -> n{} [style=dashed];\n", index, parent));
+                } else {
+                    edges.push_str(&format!("    n{} -> n{};\n", index, parent));
+                }
+            }
+
+            // parents that aren't declared anywhere we read, eg object or library classes
+            for parent in &node.unresolved_parents {
+                let position = match external.iter().position(|name| name == parent) {
+                    Some(position) => position,
+                    None => {
+                        external.push(parent);
+                        external.len() - 1
+                    },
+                };
+                match self.hierarchy.mixin(*index, parent) {
+                    Some(mixin) => edges.push_str(&format!("    n{} -> x{} [style=dotted, label=\"{}\"];\n", index, position, mixin)),
+                    None if self.hierarchy.embeds(*index, parent) => edges.push_str(&format!("    n{} -> x{} [dir=back, arrowtail=diamond];\n", index, position)),
+                    None if self.hierarchy.implements_unresolved(*index, parent) => edges.push_str(&format!("    n{} -> x{} [style=dashed];\n", index, position)),
+                    None => edges.push_str(&format!("    n{} -> x{};\n", index, position)),
+                }
+            }
+        }
+
+        if !external.is_empty() {
+            dot.push('\n');
+        }
+        for (position, name) in external.iter().enumerate() {
+            dot.push_str(&format!("    x{} [label=\"{}\", shape=box, style=dashed];\n", position, escape(name)));
+        }
+
+        if !edges.is_empty() {
+            dot.push('\n');
+            dot.push_str(&edges);
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    // {Name|fields|methods}, each member on its own left aligned line
+    fn label(&self, index: usize) -> String {
+        let object = &self.hierarchy.nodes[index].object;
+
+        let fields: String = object.variables
+            .iter()
+            .filter(|variable| !self.hide_private || variable.access.is_public())
+            .map(|variable| format!("{}\\l", escape(&field(variable))))
+            .collect();
+
+        let methods: String = object.functions
+            .iter()
+            .filter(|function| !self.hide_private || function.access.is_public())
+            .map(|function| format!("{}\\l", escape(&method(function))))
+            .collect();
+
+        format!("{{{}|{}|{}}}", escape(&object.identifier), fields, methods)
+    }
+}
+
+// + name: str
+fn field(variable: &Variable) -> String {
+    match variable.type_string() {
+        Some(vtype) if !vtype.is_empty() => format!("{} {}: {}", visibility(variable.access), variable.identifier, vtype),
+        _ => format!("{} {}", visibility(variable.access), variable.identifier),
+    }
+}
+
+// + greet(other: Base): str
+fn method(function: &Function) -> String {
+    let arguments: Vec<String> = function.arguments()
+        .iter()
+        .map(|parameter| match &parameter.ptype {
+            Some(ptype) => format!("{}: {}", parameter.identifier, ptype),
+            None => parameter.identifier.clone(),
+        })
+        .collect();
+
+    let mut method = format!("{} {}({})", visibility(function.access), function.identifier, arguments.join(", "));
+    if !function.return_type.is_empty() {
+        method.push_str(&format!(": {}", function.return_type));
+    }
+
+    method
+}
+
+// Characters with a meaning inside record labels and quoted strings
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if matches!(c, '\\' | '"' | '{' | '}' | '|' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dot;
+    use crate::testing::resolve;
+
+    #[test]
+    fn external_interfaces_are_drawn_as_realisations() {
+        let hierarchy = resolve(&[("com/example/shapes/Shape.java", "public class Shape extends Base implements Serializable {\n}\n")]);
+        let dot = Dot::new(&hierarchy, "BT", false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        assert!(dot.contains("n0 -> x0;\n"), "{}", dot);
+        assert!(dot.contains("n0 -> x1 [style=dashed];\n"), "{}", dot);
+    }
+
+    #[test]
+    fn embedded_go_structs_are_composition() {
+        let hierarchy = resolve(&[("shapes/square.go", "package shapes\n\ntype Base struct{}\n\ntype Square struct {\n    Base\n}\n")]);
+        let base = hierarchy.find("Base").unwrap();
+        let square = hierarchy.find("Square").unwrap();
+        let dot = Dot::new(&hierarchy, "BT", false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        assert!(dot.contains(&format!("    n{} -> n{} [dir=back, arrowtail=diamond];\n", square, base)), "{}", dot);
+    }
+
+    const SHAPES: &str = "class Shape:\n    def area(self):\n        pass\n\n    def _cache(self):\n        pass\n\nclass Square(Shape):\n    pass\n\nclass Cube(Square):\n    pass\n\nclass _Scratch(Shape):\n    pass\n";
+
+    #[test]
+    fn only_the_root_and_the_levels_below_it_are_drawn() {
+        let hierarchy = resolve(&[("shapes.py", SHAPES)]);
+        let square = hierarchy.find("Square").unwrap();
+        let dot = Dot::new(&hierarchy, "LR", false).render(&hierarchy.descendants(&[square], Some(0)));
+
+        assert!(dot.contains("    rankdir=LR;\n"));
+        assert!(dot.contains(&format!("n{} [label=\"{{Square||}}\"]", square)), "{}", dot);
+        assert!(!dot.contains("Shape") && !dot.contains("Cube"), "{}", dot);
+    }
+
+    #[test]
+    fn private_objects_and_members_can_be_hidden() {
+        let hierarchy = resolve(&[("shapes.py", SHAPES)]);
+        let nodes = hierarchy.descendants(&hierarchy.roots(), None);
+        let shown = Dot::new(&hierarchy, "BT", false).render(&nodes);
+        let hidden = Dot::new(&hierarchy, "BT", true).render(&nodes);
+
+        assert!(shown.contains("_Scratch") && shown.contains("_cache"));
+        assert!(!hidden.contains("_Scratch") && !hidden.contains("_cache"), "{}", hidden);
+        assert!(hidden.contains("+ area()"), "{}", hidden);
+    }
+}
@@ -1,13 +1,23 @@
-use core::panic;
 use std::collections::HashMap;
-use crate::structs::{FileData, Token, TokenisedFileData, Type};
+use crate::structs::{Diagnostic, FileData, Severity, Token, TokenPosition, TokenisedFileData, Type};
 
 
 pub struct Lexer {
     pos: usize,
     current_char: char,
     current_line: String,
+    current_line_no: i64,
     files: Vec<FileData>,
+
+    // delimiter, whether a \ escapes it, and line and column of a string still open at the end of a line, eg """
+    open_string: Option<(String, bool, i64, usize)>,
+    // line and column of a /* comment still open at the end of a line
+    open_comment: Option<(i64, usize)>,
+    // the last token other than indents and comments, which says whether a / starts a regex
+    previous: Option<Token>,
+    diagnostics: Vec<Diagnostic>,
+    filepath: String,
+    file_type: String,
 }
 
 
@@ -17,14 +27,22 @@ impl Lexer {
             pos: 0,
             current_char: '\0',
             current_line: String::new(),
+            current_line_no: 0,
             files,
+
+            open_string: None,
+            open_comment: None,
+            previous: None,
+            diagnostics: Vec::new(),
+            filepath: String::new(),
+            file_type: String::new(),
         }
     }
 
     pub fn advance(&mut self) -> char {
         // println!("Current token at advance \"{}\", '{}', {}, {}", self.current_char, self.current_line, self.pos, self.current_line.len());
-        if self.pos <= self.current_line.len() {
-            self.pos += 1;
+        if self.pos < self.current_line.len() {
+            self.pos += self.current_char.len_utf8();
             self.current_char = self.current_line[self.pos..]
                 .chars()
                 .next()
@@ -32,42 +50,79 @@ impl Lexer {
 
             self.current_char
         } else {
+            self.current_char = '\0';
             '\0'
         }
     }
 
-    pub fn peek(&mut self) -> char {
-        if self.pos <= self.current_line.len() {
-            let pos = self.pos + 1;
-            self.current_char = self.current_line[pos..]
+    pub fn peek(&self) -> char {
+        if self.pos < self.current_line.len() {
+            let pos = self.pos + self.current_char.len_utf8();
+            self.current_line[pos..]
                 .chars()
                 .next()
-                .unwrap_or('\0');
-
-            self.current_char
+                .unwrap_or('\0')
         } else {
             '\0'
         }
 
     }
 
+    fn jump_to(&mut self, pos: usize) {
+        self.pos = pos.min(self.current_line.len());
+        self.current_char = self.current_line[self.pos..].chars().next().unwrap_or('\0');
+    }
+
+    fn column(&self) -> usize {
+        self.current_line[..self.pos].chars().count() + 1
+    }
+
+    fn warning(&mut self, line: i64, column: usize, message: String) {
+        self.diagnostics.push(Diagnostic {
+            filepath: self.filepath.clone(),
+            line,
+            column,
+            length: 1,
+            severity: Severity::Warning,
+            message,
+        });
+    }
+
     pub fn lex(&mut self) -> Vec<TokenisedFileData>  {
         let mut tokenised_files = Vec::new();
 
         for file in self.files.clone() {
-            let lines = file.lines.clone();
+            // in order, as strings can carry over from one line to the next
+            let mut lines: Vec<(i64, String)> = file.lines.clone().into_iter().collect();
+            lines.sort_by_key(|line| line.0);
             let mut new_lines = HashMap::new();
+            let mut positions = HashMap::new();
+
+            self.filepath = file.filepath.clone();
+            self.file_type = file.file_type.clone();
+            self.open_string = None;
+            self.open_comment = None;
+            self.previous = None;
 
             for line in lines {
                 self.current_line = line.1;
+                self.current_line_no = line.0;
                 self.pos = 0;
                 // println!("{}, {}, {}, {}", self.pos, self.current_char, self.current_line, self.current_line.len());
                 self.current_char = self.current_line.chars().nth(self.pos).unwrap_or('\0');
 
-                let line_tokens = self.handle_line();
+                let (line_tokens, line_positions) = self.handle_line();
                 // println!("line tokens: {:?}, {}", line_tokens, self.current_line);
 
                 new_lines.insert(line.0, line_tokens);
+                positions.insert(line.0, line_positions);
+            }
+
+            if let Some((delimiter, _, line, column)) = self.open_string.take() {
+                self.warning(line, column, format!("unterminated string literal, expected a closing {}", delimiter));
+            }
+            if let Some((line, column)) = self.open_comment.take() {
+                self.warning(line, column, String::from("unterminated block comment, expected a closing */"));
             }
 
             let file_no = file.file_no;
@@ -79,25 +134,67 @@ impl Lexer {
                 file_type,
                 filepath,
                 lines: new_lines,
+                positions,
                 old_lines: file.lines,
-
+                diagnostics: std::mem::take(&mut self.diagnostics),
             };
 
             tokenised_files.push(new_file);
         }
 
-        return tokenised_files
+        tokenised_files
 
     }
 
-    pub fn handle_line(&mut self) -> Vec<Token> {
+    pub fn handle_line(&mut self) -> (Vec<Token>, Vec<TokenPosition>) {
         let mut tokens = Vec::new();
+        let mut positions = Vec::new();
+
+        if let Some((delimiter, escapes, _, _)) = &self.open_string {
+            match closing_delimiter(&self.current_line, delimiter, *escapes) {
+                Some(end) => {
+                    let pos = end + delimiter.len();
+                    self.open_string = None;
+                    self.jump_to(pos);
+                },
+                None => return (tokens, positions), // still inside the string
+            }
+        }
+
+        if self.open_comment.is_some() {
+            match self.current_line.find("*/") {
+                Some(end) => {
+                    self.open_comment = None;
+                    self.jump_to(end + 2);
+                },
+                None => return (tokens, positions),
+            }
+        }
+
         while self.current_char != '\0' {
+            while self.pos != 0 && self.current_char.is_whitespace() {
+                self.advance();
+            }
+
+            let offset = self.pos;
+            let column = self.column();
             let token = self.next_token();
+            if token == Token::Eof {
+                break; // trailing whitespace
+            }
+
+            if !matches!(token, Token::Indent(_) | Token::Comment(_)) {
+                self.previous = Some(token.clone());
+            }
             tokens.push(token);
+            positions.push(TokenPosition {
+                offset,
+                column,
+                length: self.current_line[offset..self.pos].chars().count(),
+            });
         }
 
-        return tokens
+        (tokens, positions)
     }
 
 
@@ -107,8 +204,8 @@ impl Lexer {
         }
 
 
-        let token = match self.current_char {
-            ' ' => self.handle_indent(),
+        match self.current_char {
+            ' ' | '\t' => self.handle_indent(),
 
             'a'..='z' | 'A'..='Z' | '_' => self.handle_indentifier(),
 
@@ -124,8 +221,18 @@ impl Lexer {
 
             '<' | '>' => self.handle_angles(),
 
+            '\'' if self.is_lifetime() => self.handle_lifetime(),
+
+            '$' if self.is_php_variable() => self.handle_php_variable(),
+
             '"' | '\'' => self.handle_string(),
 
+            '@' if self.file_type == "csharp" && self.is_verbatim() => self.handle_verbatim(),
+
+            '`' if self.is_ecmascript() || self.file_type == "go" => self.handle_template(),
+
+            '/' if self.is_ecmascript() && self.is_regex() => self.handle_regex(),
+
             '/' | '#' | '-' => self.handle_comment(),
 
             '\0' => Token::Eof,
@@ -133,18 +240,20 @@ impl Lexer {
             _ => {
                 let c = self.current_char;
                 self.advance();
-                return Token::Other(c)
+                Token::Other(c)
             },
-        };
-
-        return token
+        }
     }
 
     fn handle_indent(&mut self) -> Token {
         let mut indent_level = 0;
 
-        while self.current_char == ' ' {
-            indent_level += 1;
+        while self.current_char == ' ' || self.current_char == '\t' {
+            if self.current_char == '\t' {
+                indent_level += 8 - indent_level % 8; // tabs stop every 8 columns, as in python
+            } else {
+                indent_level += 1;
+            }
             self.advance();
         }
 
@@ -166,23 +275,63 @@ impl Lexer {
             },
             ';' => Token::Semicolon,
             '=' => Token::Equals,
-            _ => panic!("Cannot tokenise punctuation")
+            _ => unreachable!("Cannot tokenise punctuation")
         };
 
         self.advance();
-        return c
+        c
     }
 
     fn handle_indentifier(&mut self) -> Token {
         let mut identifier: String = String::new();
-        while !self.current_char.is_whitespace() && (self.current_char.is_alphabetic() || self.current_char == '_') {
+        while self.current_char.is_alphanumeric() || self.current_char == '_' {
             identifier.push(self.current_char);
             self.advance();
         }
 
+        // r"...", br#"..."#
+        if self.file_type == "rust"
+            && matches!(identifier.as_str(), "r" | "br" | "cr")
+            && matches!(self.current_char, '"' | '#') {
+            return self.handle_raw_string()
+        }
+
         self.match_identifier(identifier)
     }
 
+    // The r has been read, the string ends at a " followed by as many #s as it started with
+    fn handle_raw_string(&mut self) -> Token {
+        let column = self.column();
+        let start = self.pos;
+        let hashes = self.current_line[start..].chars().take_while(|c| *c == '#').count();
+        // r#type is a name that would otherwise be a keyword
+        if !self.current_line[start + hashes..].starts_with('"') {
+            self.advance(); // skip #
+            let mut identifier = String::new();
+            while self.current_char.is_alphanumeric() || self.current_char == '_' {
+                identifier.push(self.current_char);
+                self.advance();
+            }
+            return Token::Identifier(identifier)
+        }
+
+        let delimiter = format!("\"{}", "#".repeat(hashes));
+        let body = start + hashes + 1;
+        match self.current_line[body..].find(&delimiter) {
+            Some(end) => {
+                let string = self.current_line[body..body + end].to_string();
+                self.jump_to(body + end + delimiter.len());
+                Token::StringLiteral(string)
+            },
+            None => {
+                let string = self.current_line[body..].to_string();
+                self.jump_to(self.current_line.len());
+                self.open_string = Some((delimiter, false, self.current_line_no, column));
+                Token::StringLiteral(string)
+            },
+        }
+    }
+
     fn match_identifier(&mut self, id: String) -> Token {
         let mut identifier = id.clone();
         identifier.make_ascii_lowercase();
@@ -208,22 +357,26 @@ impl Lexer {
         ];
 
         if object_keywords.contains(&identifier.as_str()) {
-            return Token::ObjectDeclaration
+            Token::ObjectDeclaration(id)
         } else {
 
             match identifier.as_str() {
-                "pub" | "public" => Token::Publicity(true),
-                "private" => Token::Publicity(false),
-                "self" | "Self" => Token::SelfToken,
-                "def" | "fn" => Token::FunctionDeclaration,
+                // as written, and not a path's last segment such as Access::Public
+                "pub" | "public" if id == identifier && self.previous != Some(Token::Connect) => Token::Publicity(true),
+                "private" if id == identifier && self.previous != Some(Token::Connect) => Token::Publicity(false),
+                // Self is a type
+                "self" if id == "self" => Token::SelfToken,
+                // Fn is a trait
+                "fn" if id == identifier && self.file_type == "rust" => Token::FunctionDeclaration,
+                "def" if id == identifier && matches!(self.file_type.as_str(), "python" | "ruby") => Token::FunctionDeclaration,
                 "trait" => Token::Trait,
 
-                "string" | "str" => Token::Type(Type::String),
-                "integer" | "int" | "i16" | "i32" | "i64" | "i128" => Token::Type(Type::Integer),
-                "float" | "f8" | "f16" | "f32" | "f64" => Token::Type(Type::Float),
-                "bool" | "boolean" => Token::Type(Type::Float),
-                "char" => Token::Type(Type::Char),
-                "none" => Token::Type(Type::NoneType),
+                "string" | "str" => Token::Type(Type::String, id),
+                "integer" | "int" | "i16" | "i32" | "i64" | "i128" => Token::Type(Type::Integer, id),
+                "float" | "f8" | "f16" | "f32" | "f64" => Token::Type(Type::Float, id),
+                "bool" | "boolean" => Token::Type(Type::Boolean, id),
+                "char" => Token::Type(Type::Char, id),
+                "none" => Token::Type(Type::NoneType, id),
 
                 _ => Token::Identifier(id),
             }
@@ -242,15 +395,212 @@ impl Lexer {
     }
 
 
+    // Languages whose comments are // and /* */, where -- is a decrement
+    fn is_c_like(&self) -> bool {
+        matches!(self.file_type.as_str(), "rust" | "java" | "csharp" | "typescript" | "javascript" | "cpp" | "kotlin" | "swift" | "go" | "php")
+    }
+
+    fn is_ecmascript(&self) -> bool {
+        matches!(self.file_type.as_str(), "typescript" | "javascript")
+    }
+
+    // 'a and 'static in rust, as opposed to the char literal 'a'
+    fn is_lifetime(&self) -> bool {
+        if self.file_type != "rust" {
+            return false
+        }
+
+        let mut chars = self.current_line[self.pos..].chars().skip(1);
+        match (chars.next(), chars.next()) {
+            (Some(c), next) => (c.is_alphabetic() || c == '_') && next != Some('\''),
+            _ => false,
+        }
+    }
+
+    fn handle_lifetime(&mut self) -> Token {
+        let mut lifetime = String::from("'");
+        self.advance(); // skip '
+
+        while self.current_char.is_alphanumeric() || self.current_char == '_' {
+            lifetime.push(self.current_char);
+            self.advance();
+        }
+
+        Token::Identifier(lifetime)
+    }
+
+    // $name in php, a variable or property that's never a keyword, eg $class
+    fn is_php_variable(&self) -> bool {
+        let next = self.peek();
+        self.file_type == "php" && (next.is_alphabetic() || next == '_')
+    }
+
+    fn handle_php_variable(&mut self) -> Token {
+        let mut variable = String::from("$");
+        self.advance(); // skip $
+
+        while self.current_char.is_alphanumeric() || self.current_char == '_' {
+            variable.push(self.current_char);
+            self.advance();
+        }
+
+        Token::Identifier(variable)
+    }
+
+    // @"C:\path", @$"{x}" and @class, a keyword used as a name, in c#
+    fn is_verbatim(&self) -> bool {
+        let next = self.peek();
+        next == '"' || next == '_' || next.is_alphabetic() || self.current_line[self.pos..].starts_with("@$\"")
+    }
+
+    // Backslashes don't escape anything in a verbatim string, a quote is written twice
+    fn handle_verbatim(&mut self) -> Token {
+        let column = self.column();
+        self.advance(); // skip @
+        if self.current_char.is_alphabetic() || self.current_char == '_' {
+            let mut identifier = String::new();
+            while self.current_char.is_alphanumeric() || self.current_char == '_' {
+                identifier.push(self.current_char);
+                self.advance();
+            }
+            return Token::Identifier(identifier)
+        }
+        if self.current_char == '$' {
+            self.advance();
+        }
+        self.advance(); // skip "
+
+        let mut string = String::new();
+        loop {
+            match (self.current_char, self.peek()) {
+                ('"', '"') => {
+                    string.push('"');
+                    self.advance();
+                },
+                ('"', _) => break,
+                ('\0', _) => {
+                    // the string carries on over the next lines
+                    self.open_string = Some((String::from("\""), false, self.current_line_no, column));
+                    return Token::StringLiteral(string)
+                },
+                (c, _) => string.push(c),
+            }
+            self.advance();
+        }
+
+        self.advance(); // skip "
+        Token::StringLiteral(string)
+    }
+
+    // `Hello ${name}`, or a go raw string, which may span several lines. The ${} parts are kept as they are written
+    fn handle_template(&mut self) -> Token {
+        let column = self.column();
+        self.advance(); // skip `
+
+        let mut string = String::new();
+        while self.current_char != '`' {
+            if self.current_char == '\0' {
+                self.open_string = Some((String::from("`"), self.is_ecmascript(), self.current_line_no, column));
+                return Token::StringLiteral(string)
+            }
+            // go's raw strings have no escapes
+            if self.current_char == '\\' && self.peek() != '\0' && self.is_ecmascript() {
+                string.push(self.current_char);
+                self.advance();
+            }
+
+            string.push(self.current_char);
+            self.advance();
+        }
+
+        self.advance(); // skip `
+        Token::StringLiteral(string)
+    }
+
+    // /\}/g in javascript, where a value starts rather than after one, where / would be division. A regex
+    // doesn't span lines, so a / without another after it on the line isn't one
+    fn is_regex(&self) -> bool {
+        let value_starts = match &self.previous {
+            None => true,
+            Some(Token::Equals | Token::Comma | Token::Colon | Token::Semicolon | Token::BlockOpen(_) | Token::BlockClose | Token::Arrow) => true,
+            Some(Token::Parenthesis(c) | Token::Bracket(c)) => matches!(c, '(' | '['),
+            Some(Token::AngleBracket(_)) => true,
+            Some(Token::Other(c)) => matches!(c, '!' | '&' | '|' | '?' | '+' | '-' | '*' | '%' | '^' | '~'),
+            Some(Token::Identifier(id)) => matches!(
+                id.as_str(),
+                "return" | "typeof" | "instanceof" | "in" | "of" | "new" | "delete" | "void" | "throw" | "case" | "yield" | "await"
+            ),
+            _ => false,
+        };
+
+        value_starts && !matches!(self.peek(), '/' | '*') && self.regex_end().is_some()
+    }
+
+    // Where the regex starting at the current / ends, after its flags. A / in a [class] or escaped doesn't end it
+    fn regex_end(&self) -> Option<usize> {
+        let mut chars = self.current_line[self.pos..].char_indices().skip(1);
+        let mut class = false;
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                },
+                '[' => class = true,
+                ']' => class = false,
+                '/' if !class => {
+                    let flags = self.current_line[self.pos + offset + 1..].chars().take_while(|c| c.is_alphanumeric()).map(char::len_utf8).sum::<usize>();
+                    return Some(self.pos + offset + 1 + flags)
+                },
+                _ => {},
+            }
+        }
+        None
+    }
+
+    // The regex is kept as it's written, slashes and flags included
+    fn handle_regex(&mut self) -> Token {
+        let end = self.regex_end().unwrap_or(self.current_line.len());
+        let regex = self.current_line[self.pos..end].to_string();
+        self.jump_to(end);
+        Token::StringLiteral(regex)
+    }
+
     // could be possible issues with strings within strings: \" \" hello world! \" \"
     // --- ADDRESS LATER BEFORE MOVING ONTO PARSER
     fn handle_string(&mut self) -> Token {
         let opening_string = self.current_char;
+        let column = self.column();
+
+        // """ and ''' strings can span several lines
+        let delimiter: String = std::iter::repeat_n(opening_string, 3).collect();
+        if self.current_line[self.pos..].starts_with(&delimiter) {
+            let start = self.pos + delimiter.len();
+            let string = match self.current_line[start..].find(&delimiter) {
+                Some(end) => {
+                    let string = self.current_line[start..start + end].to_string();
+                    self.jump_to(start + end + delimiter.len());
+                    string
+                },
+                None => {
+                    let string = self.current_line[start..].to_string();
+                    self.jump_to(self.current_line.len());
+                    self.open_string = Some((delimiter, true, self.current_line_no, column));
+                    string
+                },
+            };
+
+            return Token::StringLiteral(string)
+        }
+
         self.advance(); // skip "
 
         let mut string = String::new();
-        while self.current_char != opening_string {
-            if self.current_char == '\\' {
+        while self.current_char != opening_string && self.current_char != '\0' {
+            if self.is_interpolation() {
+                self.handle_interpolation(&mut string);
+                continue;
+            }
+            if self.current_char == '\\' && self.peek() != '\0' {
                 string.push(self.current_char);
                 self.advance();
             }
@@ -259,10 +609,63 @@ impl Lexer {
             self.advance();
         }
 
+        // the string carries on over the next lines
+        if self.current_char == '\0' && opening_string == '"' && matches!(self.file_type.as_str(), "rust" | "ruby" | "php") {
+            self.open_string = Some((String::from("\""), true, self.current_line_no, column));
+            return Token::StringLiteral(string)
+        }
+
+        // ' is also a quote or a name character in some languages
+        if self.current_char == '\0' && (opening_string == '"' || matches!(self.file_type.as_str(), "python" | "rust")) {
+            self.warning(self.current_line_no, column, String::from("unterminated string literal"));
+        }
+
         self.advance(); // skip \"
         Token::StringLiteral(string)
     }
 
+    // ${...} in kotlin and \(...) in swift, code within a string that can have strings of its own, "${xs.map { "}" }}"
+    fn is_interpolation(&self) -> bool {
+        match self.file_type.as_str() {
+            "kotlin" => self.current_char == '$' && self.peek() == '{',
+            "swift" => self.current_char == '\\' && self.peek() == '(',
+            _ => false,
+        }
+    }
+
+    // Up to the brace or parenthesis that closes the interpolation, which is kept as it's written
+    fn handle_interpolation(&mut self, string: &mut String) {
+        string.push(self.current_char);
+        self.advance(); // skip $ / \
+        let (open, close) = if self.current_char == '{' { ('{', '}') } else { ('(', ')') };
+
+        let mut depth = 0;
+        while self.current_char != '\0' {
+            match self.current_char {
+                quote @ ('"' | '\'') => {
+                    if let Token::StringLiteral(literal) = self.handle_string() {
+                        string.push(quote);
+                        string.push_str(&literal);
+                        string.push(quote);
+                    }
+                    continue;
+                },
+                c if c == open => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        string.push(c);
+                        self.advance();
+                        return
+                    }
+                },
+                _ => {},
+            }
+            string.push(self.current_char);
+            self.advance();
+        }
+    }
+
     fn handle_brackets(&mut self) -> Token {
         let bracket = self.current_char;
         self.advance();
@@ -279,11 +682,11 @@ impl Lexer {
         let b = match self.current_char {
             '{' => Token::BlockOpen('{'),
             '}' => Token::BlockClose,
-            _ => panic!("Not braces")
+            _ => unreachable!("Not braces")
         };
 
         self.advance();
-        return b
+        b
     }
 
     fn handle_angles(&mut self) -> Token {
@@ -293,9 +696,34 @@ impl Lexer {
 
     }
 
+    // /* up to the next */, which may be on a later line
+    fn handle_block_comment(&mut self) -> Token {
+        let column = self.column();
+        let start = self.pos + 2;
+
+        match self.current_line[start..].find("*/") {
+            Some(end) => {
+                let comment = self.current_line[start..start + end].to_string();
+                self.jump_to(start + end + 2);
+                Token::Comment(comment)
+            },
+            None => {
+                let comment = self.current_line[start..].to_string();
+                self.jump_to(self.current_line.len());
+                self.open_comment = Some((self.current_line_no, column));
+                Token::Comment(comment)
+            },
+        }
+    }
+
     fn handle_comment(&mut self) -> Token {
         let mut arrow_flag = false;
         let is_comment: bool = match self.current_char {
+            '/' if self.is_c_like() => match self.peek() {
+                '/' => true,
+                '*' => return self.handle_block_comment(),
+                _ => false,
+            },
             '/' => {
                 let next = self.peek();
                 if next == '/' || next == '*' || next == '=' {
@@ -305,9 +733,11 @@ impl Lexer {
                     false
                 }
             },
-            '#' => true,
+            // attributes in rust, #[derive(Debug)], and php, #[Route("/")], private names in javascript, #count,
+            // and swift's #selector(tap)
+            '#' => !(matches!(self.file_type.as_str(), "rust" | "swift") || self.is_ecmascript() || self.file_type == "php" && self.peek() == '['),
             '-' => {
-                if self.peek() == '-' {
+                if self.peek() == '-' && !self.is_c_like() {
                     self.advance();
                     true
                 } else if self.peek() == '>' {
@@ -330,15 +760,30 @@ impl Lexer {
                 self.advance();
             }
 
-            return Token::Comment(comment)
+            Token::Comment(comment)
         } else if arrow_flag {
             self.advance();
-            return Token::Arrow
+            Token::Arrow
         } else {
             let c = self.current_char;
             self.advance();
 
-            return Token::Other(c)
+            Token::Other(c)
         }
     }
 }
+
+// Where a string still open from an earlier line ends, skipping \" when backslashes escape
+fn closing_delimiter(line: &str, delimiter: &str, escapes: bool) -> Option<usize> {
+    let mut from = 0;
+    while let Some(found) = line[from..].find(delimiter) {
+        let end = from + found;
+        let backslashes = line[..end].chars().rev().take_while(|c| *c == '\\').count();
+        if !escapes || backslashes % 2 == 0 {
+            return Some(end)
+        }
+        from = end + delimiter.len();
+    }
+
+    None
+}
@@ -0,0 +1,23 @@
+pub mod diagnostics;
+pub mod dot;
+pub mod lexer;
+pub mod mermaid;
+pub mod modules;
+pub mod parser;
+pub mod plantuml;
+pub mod resolver;
+pub mod scanner;
+pub mod structs;
+#[cfg(test)]
+mod testing;
+pub mod tree;
+
+pub use crate::diagnostics::Reporter;
+pub use crate::dot::Dot;
+pub use crate::lexer::Lexer;
+pub use crate::mermaid::Mermaid;
+pub use crate::parser::Parser;
+pub use crate::plantuml::PlantUml;
+pub use crate::resolver::Resolver;
+pub use crate::scanner::Scanner;
+pub use crate::tree::Tree;
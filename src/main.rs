@@ -1,63 +1,246 @@
-use std::{collections::HashMap, io::{self, Read}};
-use crate::lexer::Lexer;
-use crate::parser::Parser;
-use crate::structs::FileData;
-use serde_json;
+use std::{collections::HashMap, env, io::{self, IsTerminal, Read}, path::PathBuf, process};
+use clap::{Parser as _, Subcommand, ValueEnum};
+use classtree::{Dot, Lexer, Mermaid, Parser, PlantUml, Reporter, Resolver, Scanner, Tree};
+use classtree::structs::{Diagnostic, FileData, ModelDocument, Severity};
+
+
+// Without a command, files are read from stdin as a JSON array of
+// {fileNo, fileName, fileType, lines: [[n, text], ...]}
+#[derive(clap::Parser)]
+#[command(name = "classtree", about = "Builds the class hierarchy of a codebase")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// How to print the hierarchy
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Only show this object and what inherits from it
+    #[arg(long, global = true, value_name = "NAME")]
+    root: Option<String>,
+
+    /// How many levels of children to show below the roots
+    #[arg(long, global = true)]
+    depth: Option<usize>,
+
+    /// Leave out private objects, fields and methods
+    #[arg(long, global = true)]
+    hide_private: bool,
+
+    /// Graphviz rank direction, for --format dot
+    #[arg(long, global = true, default_value = "BT", value_parser = ["TB", "BT", "LR", "RL"])]
+    rankdir: String,
+}
 
-pub mod lexer;
-pub mod parser;
-pub mod structs;
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Indented class names
+    Text,
+    /// Graphviz digraph, render with `dot -Tsvg`
+    Dot,
+    /// Mermaid classDiagram
+    Mermaid,
+    /// PlantUML class diagram, with a package per file
+    Plantuml,
+    /// The whole parsed model, see ModelDocument for the schema. Ignores --root, --depth and --hide-private
+    Json,
+}
 
+#[derive(Subcommand)]
+enum Command {
+    /// Read source files from a directory, respecting .gitignore
+    Scan {
+        dir: PathBuf,
+
+        /// Only read files matching this glob, relative to <DIR> (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Skip files matching this glob, relative to <DIR> (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+    },
+
+    /// Print the hierarchy like the unix tree command, from base classes down
+    Tree {
+        /// Directory to read, without one files are read from stdin as JSON
+        dir: Option<PathBuf>,
+
+        /// Only read files matching this glob, relative to <DIR> (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Skip files matching this glob, relative to <DIR> (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Colour abstract types and interfaces
+        #[arg(long, value_enum, default_value_t = Colour::Auto)]
+        color: Colour,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Colour {
+    /// When printing to a terminal and NO_COLOR isn't set
+    Auto,
+    Always,
+    Never,
+}
 
 fn main() {
-    let mut buffer = String::new();
-    let _ = io::stdin().read_to_string(&mut buffer);
+    let cli = Cli::parse();
 
-    let json: serde_json::Value = serde_json::from_str(&buffer).unwrap();
-    let all_files = get_all_files(json);
+    let (all_files, mut diagnostics) = match &cli.command {
+        Some(Command::Scan { dir, include, exclude })
+        | Some(Command::Tree { dir: Some(dir), include, exclude, .. }) => Scanner::new(dir, include, exclude).scan(),
+        Some(Command::Tree { dir: None, .. }) | None => read_stdin(),
+    };
 
-    let mut lexer = Lexer::new(all_files);
+    let mut lexer = Lexer::new(all_files.clone());
 
     let tkls = lexer.lex();
 
     let mut parser = Parser::new(tkls);
 
-    let _possible_objects = parser.possible_objects();
+    let mut model = parser.parse();
+
+    let mut hierarchy = Resolver::new(&model).resolve();
+    let starts = match &cli.root {
+        Some(root) => match hierarchy.find(root) {
+            Some(index) => vec![index],
+            None => {
+                diagnostics.push(input_error("<arguments>", format!("no object named `{}` was found", root)));
+                Vec::new()
+            },
+        },
+        None => hierarchy.roots(),
+    };
+    diagnostics.append(&mut model.diagnostics);
+    diagnostics.append(&mut hierarchy.diagnostics);
+
+    match (&cli.command, cli.format) {
+        (Some(Command::Tree { color, .. }), _) => {
+            let colour = match color {
+                Colour::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+                Colour::Always => true,
+                Colour::Never => false,
+            };
+            print!("{}", Tree::new(&hierarchy, colour).render(&starts, cli.depth));
+        },
+        (_, Format::Text) => print!("{}", hierarchy.display(&starts, cli.depth)),
+        (_, Format::Dot) => {
+            let nodes = hierarchy.descendants(&starts, cli.depth);
+            print!("{}", Dot::new(&hierarchy, &cli.rankdir, cli.hide_private).render(&nodes));
+        },
+        (_, Format::Mermaid) => {
+            let nodes = hierarchy.descendants(&starts, cli.depth);
+            print!("{}", Mermaid::new(&hierarchy, cli.hide_private).render(&nodes));
+        },
+        (_, Format::Plantuml) => {
+            let nodes = hierarchy.descendants(&starts, cli.depth);
+            print!("{}", PlantUml::new(&hierarchy, cli.hide_private).render(&nodes));
+        },
+        (_, Format::Json) => {
+            let document = ModelDocument::new(&model, &hierarchy, &diagnostics);
+            match serde_json::to_string_pretty(&document) {
+                Ok(json) => println!("{}", json),
+                Err(error) => {
+                    eprintln!("error: could not serialise the model: {}", error);
+                    process::exit(1);
+                },
+            }
+        },
+    }
 
-    parser.parse();
+    if !diagnostics.is_empty() {
+        eprint!("{}", Reporter::new(&all_files).report(&diagnostics));
+    }
 
 }
 
-fn get_all_files(json: serde_json::Value) -> Vec<FileData> {
-    let mut all_files: Vec<FileData> = Vec::new();
+fn read_stdin() -> (Vec<FileData>, Vec<Diagnostic>) {
+    let mut buffer = String::new();
+    if let Err(error) = io::stdin().read_to_string(&mut buffer) {
+        eprintln!("error: could not read stdin: {}", error);
+        process::exit(1);
+    }
 
-    if let serde_json::Value::Array(files) = &json {
-        for file in files {
+    let json: serde_json::Value = match serde_json::from_str(&buffer) {
+        Ok(json) => json,
+        Err(error) => {
+            eprintln!("error: input is not valid JSON: {}", error);
+            process::exit(1);
+        }
+    };
 
-            let file_no = file["fileNo"].as_i64().unwrap() as i16;
-            let filepath = file["fileName"].as_str().unwrap().to_string();
-            let file_type = file["fileType"].as_str().unwrap().to_string();
-            let mut lines_map = HashMap::new();
+    get_all_files(json)
+}
+
+fn input_error(filepath: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        filepath: filepath.to_string(),
+        line: 0,
+        column: 0,
+        length: 0,
+        severity: Severity::Error,
+        message,
+    }
+}
+
+// Malformed files and lines are reported and skipped rather than ending the run
+fn get_all_files(json: serde_json::Value) -> (Vec<FileData>, Vec<Diagnostic>) {
+    let mut all_files: Vec<FileData> = Vec::new();
+    let mut diagnostics = Vec::new();
 
-           if let serde_json::Value::Array(lines) = &file["lines"] {
-                for line in lines {
-                    let line_number: i64 = line[0].as_i64().unwrap();
-                    let line_body: String = line[1].as_str().unwrap().to_string();
-                    lines_map.insert(line_number, line_body);
+    let files = match &json {
+        serde_json::Value::Array(files) => files,
+        _ => {
+            diagnostics.push(input_error("<stdin>", String::from("expected a JSON array of files")));
+            return (all_files, diagnostics)
+        }
+    };
+
+    for (index, file) in files.iter().enumerate() {
+        let filepath = match file["fileName"].as_str() {
+            Some(filepath) => filepath.to_string(),
+            None => {
+                diagnostics.push(input_error("<stdin>", format!("file entry {} has no `fileName`, skipping it", index)));
+                continue;
+            }
+        };
+
+        let file_no = file["fileNo"].as_u64().and_then(|file_no| u32::try_from(file_no).ok()).unwrap_or(index as u32 + 1);
+        let file_type = match file["fileType"].as_str() {
+            Some(file_type) => file_type.to_string(),
+            None => {
+                diagnostics.push(input_error(&filepath, String::from("file has no `fileType`, skipping it")));
+                continue;
+            }
+        };
+        let mut lines_map = HashMap::new();
+
+        if let serde_json::Value::Array(lines) = &file["lines"] {
+            for (index, line) in lines.iter().enumerate() {
+                match (line[0].as_i64(), line[1].as_str()) {
+                    (Some(line_number), Some(line_body)) => {
+                        lines_map.insert(line_number, line_body.to_string());
+                    },
+                    _ => diagnostics.push(input_error(&filepath, format!("line entry {} is not a [number, text] pair, skipping it", index))),
                 }
             }
+        }
 
-           let file_data = FileData {
-                file_no,
-                filepath,
-                file_type,
-                lines: lines_map,
-           };
+        let file_data = FileData {
+            file_no,
+            filepath,
+            file_type,
+            lines: lines_map,
+        };
 
-           all_files.push(file_data);
-        }
+        all_files.push(file_data);
     }
 
-    return all_files;
+    (all_files, diagnostics)
 }
-
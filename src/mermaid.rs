@@ -0,0 +1,232 @@
+use crate::structs::{visibility, Function, Hierarchy, Variable};
+
+// Renders part of a hierarchy as a Mermaid class diagram:
+//
+// classDiagram
+//     class Base {
+//         +str name
+//         +greet(Base other) str
+//     }
+//     Base <|-- Child
+//     Comparable <.. Child : include
+//     Square *-- Base                 a go struct embedding a type
+pub struct Mermaid<'a> {
+    hierarchy: &'a Hierarchy,
+    hide_private: bool,
+}
+
+impl<'a> Mermaid<'a> {
+    pub fn new(hierarchy: &'a Hierarchy, hide_private: bool) -> Mermaid<'a> {
+        Mermaid {
+            hierarchy,
+            hide_private,
+        }
+    }
+
+    pub fn render(&self, nodes: &[usize]) -> String {
+        let nodes: Vec<usize> = nodes
+            .iter()
+            .copied()
+            .filter(|index| !self.hide_private || self.hierarchy.nodes[*index].object.public)
+            .collect();
+
+        let mut mermaid = String::from("classDiagram\n");
+        for index in &nodes {
+            mermaid.push_str(&self.class(*index, &nodes));
+        }
+
+        for index in &nodes {
+            let node = &self.hierarchy.nodes[*index];
+            for parent in node.parents.iter().filter(|parent| nodes.contains(parent)) {
+                // ruby modules that are mixed in depend on rather than generalise
+                let identifier = &self.hierarchy.nodes[*parent].object.identifier;
+                match self.hierarchy.mixin(*index, identifier) {
+                    Some(mixin) => mermaid.push_str(&format!("    {} <.. {} : {}\n", self.name(*parent, &nodes), self.name(*index, &nodes), mixin)),
+                    // go structs are composed of what they embed
+                    None if self.hierarchy.embeds(*index, identifier) => mermaid.push_str(&format!("    {} *-- {}\n", self.name(*index, &nodes), self.name(*parent, &nodes))),
+                    None => {
+                        let arrow = if self.hierarchy.implements(*index, *parent) { "<|.." } else { "<|--" };
+                        mermaid.push_str(&format!("    {} {} {}\n", self.name(*parent, &nodes), arrow, self.name(*index, &nodes)));
+                    },
+                }
+            }
+
+            // mermaid adds an empty class for names it hasn't seen
+            for parent in &node.unresolved_parents {
+                match self.hierarchy.mixin(*index, parent) {
+                    Some(mixin) => mermaid.push_str(&format!("    {} <.. {} : {}\n", class_name(parent), self.name(*index, &nodes), mixin)),
+                    None if self.hierarchy.embeds(*index, parent) => mermaid.push_str(&format!("    {} *-- {}\n", self.name(*index, &nodes), class_name(parent))),
+                    None => {
+                        let arrow = if self.hierarchy.implements_unresolved(*index, parent) { "<|.." } else { "<|--" };
+                        mermaid.push_str(&format!("    {} {} {}\n", class_name(parent), arrow, self.name(*index, &nodes)));
+                    },
+                }
+            }
+        }
+
+        mermaid
+    }
+
+    fn class(&self, index: usize, nodes: &[usize]) -> String {
+        let object = &self.hierarchy.nodes[index].object;
+        let name = self.name(index, nodes);
+
+        let mut class = if name == object.identifier {
+            format!("    class {}", name)
+        } else {
+            format!("    class {}[\"{}\"]", name, object.identifier)
+        };
+        // class Box~T~
+        if !object.generics.is_empty() {
+            class.push_str(&generics(&format!("<{}>", object.generics.join(", "))));
+        }
+
+        let mut members = Vec::new();
+        for variable in object.variables.iter().filter(|variable| !self.hide_private || variable.access.is_public()) {
+            members.push(field(variable));
+        }
+        for function in object.functions.iter().filter(|function| !self.hide_private || function.access.is_public()) {
+            members.push(method(function));
+        }
+
+        if members.is_empty() {
+            class.push('\n');
+            return class
+        }
+
+        class.push_str(" {\n");
+        for member in members {
+            class.push_str(&format!("        {}\n", member));
+        }
+        class.push_str("    }\n");
+
+        class
+    }
+
+    // Class names have to be unique, later objects sharing a name get their index appended
+    fn name(&self, index: usize, nodes: &[usize]) -> String {
+        let identifier = &self.hierarchy.nodes[index].object.identifier;
+        let first = nodes
+            .iter()
+            .find(|other| self.hierarchy.nodes[**other].object.identifier == *identifier);
+
+        if first != Some(&index) {
+            format!("{}_{}", class_name(identifier), index)
+        } else {
+            class_name(identifier)
+        }
+    }
+}
+
+// +str name
+fn field(variable: &Variable) -> String {
+    match variable.type_string() {
+        Some(vtype) if !vtype.is_empty() => format!("{}{} {}", visibility(variable.access), generics(&vtype), variable.identifier),
+        _ => format!("{}{}", visibility(variable.access), variable.identifier),
+    }
+}
+
+// +greet(Base other) str, +create()$ Base, +area()* float
+fn method(function: &Function) -> String {
+    let arguments: Vec<String> = function.arguments()
+        .iter()
+        .map(|parameter| match &parameter.ptype {
+            Some(ptype) => format!("{} {}", generics(ptype), parameter.identifier),
+            None => parameter.identifier.clone(),
+        })
+        .collect();
+
+    let mut method = format!("{}{}({})", visibility(function.access), function.identifier, arguments.join(", "));
+    if function.is_static || function.is_class_method {
+        method.push('$');
+    } else if function.is_abstract {
+        method.push('*');
+    }
+    if !function.return_type.is_empty() {
+        method.push_str(&format!(" {}", generics(&function.return_type)));
+    }
+
+    method
+}
+
+// Mermaid writes generics with tildes, List[int] / Vec<i32> -> List~int~ / Vec~i32~. Only the outermost can be
+// written that way and not with commas, so the brackets and commas within them are run together,
+// Map<String, List<int>> -> Map~String_List_int~. The brackets of arrays are kept, []byte / int[], as are arrows,
+// Fn(i32) -> i32
+fn generics(text: &str) -> String {
+    let mut generics = String::new();
+    let mut depth = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '[' if chars.peek() == Some(&']') => {
+                chars.next();
+                generics.push_str("[]");
+            },
+            '[' | '<' => {
+                generics.push(if depth == 0 { '~' } else { '_' });
+                depth += 1;
+            },
+            // -> and =>
+            '>' if generics.ends_with(['-', '=']) => generics.push(c),
+            ']' | '>' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    generics.push('~');
+                }
+            },
+            ',' if depth > 0 => {
+                generics.push('_');
+                while chars.next_if_eq(&' ').is_some() {}
+            },
+            c => generics.push(c),
+        }
+    }
+
+    generics
+}
+
+// models.Base / Generic[T] -> models_Base / Generic, the characters mermaid accepts in a class name
+fn class_name(name: &str) -> String {
+    let end = name.find(['[', '<']).unwrap_or(name.len());
+    name[..end]
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generics, Mermaid};
+    use crate::testing::resolve;
+
+    #[test]
+    fn only_the_outermost_generics_are_written_with_tildes() {
+        assert_eq!(generics("List[int]"), "List~int~");
+        assert_eq!(generics("Map<String, List<Integer>>"), "Map~String_List_Integer~");
+        assert_eq!(generics("map[string][]byte"), "map~string~[]byte");
+        assert_eq!(generics("[]byte"), "[]byte");
+        assert_eq!(generics("Box<dyn Fn(i32) -> i32>"), "Box~dyn Fn(i32) -> i32~");
+    }
+
+    #[test]
+    fn external_interfaces_are_drawn_as_realisations() {
+        let hierarchy = resolve(&[("com/example/shapes/Shape.java", "public class Shape extends Base implements Serializable {\n}\n")]);
+        let mermaid = Mermaid::new(&hierarchy, false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        assert!(mermaid.contains("    Base <|-- Shape\n"), "{}", mermaid);
+        assert!(mermaid.contains("    Serializable <|.. Shape\n"), "{}", mermaid);
+    }
+
+    #[test]
+    fn embedded_go_structs_are_composition() {
+        let hierarchy = resolve(&[("shapes/square.go", "package shapes\n\ntype Base struct{}\n\ntype Square struct {\n    Base\n    io.Writer\n}\n")]);
+        let mermaid = Mermaid::new(&hierarchy, false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        assert!(mermaid.contains("    Square *-- Base\n"), "{}", mermaid);
+        assert!(mermaid.contains("    Square *-- io_Writer\n"), "{}", mermaid);
+        assert!(!mermaid.contains("<|--"), "{}", mermaid);
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::structs::ModuleDeclaration;
+
+// Dotted module names for python files. A directory is a package when it has an __init__.py
+// among the files read, so models/base.py is the module models.base if models/__init__.py was read.
+// Every file is also known by its whole path, tmp.repo.models.base, which is what namespace
+// packages and imports from outside the files read fall back to.
+pub struct ModuleMap {
+    modules: Vec<Module>,
+}
+
+struct Module {
+    filepath: String,
+    name: String,
+    path_name: String,
+    // __init__.py, relative imports start from the module itself rather than its package
+    package: bool,
+}
+
+impl ModuleMap {
+    pub fn python(filepaths: &[&str]) -> ModuleMap {
+        let packages: HashSet<Vec<String>> = filepaths
+            .iter()
+            .map(|filepath| segments(filepath))
+            .filter(|segments| segments.last().is_some_and(|file| file == "__init__"))
+            .map(|mut segments| {
+                segments.pop();
+                segments
+            })
+            .collect();
+
+        let mut modules = Vec::new();
+        for filepath in filepaths {
+            let path = segments(filepath);
+            if path.is_empty() {
+                continue;
+            }
+            let package = path.last().is_some_and(|file| file == "__init__");
+
+            // walk up from the file's directory for as long as directories are packages
+            let mut start = path.len() - 1;
+            while start > 0 && packages.contains(&path[..start]) {
+                start -= 1;
+            }
+
+            let mut name = path[start..].to_vec();
+            let mut path_name = path.clone();
+            if package {
+                name.pop();
+                path_name.pop();
+            }
+
+            modules.push(Module {
+                filepath: filepath.to_string(),
+                name: name.join("."),
+                path_name: path_name.join("."),
+                package,
+            });
+        }
+
+        ModuleMap {
+            modules,
+        }
+    }
+
+    // Files a module name could refer to, by package name first, then by path
+    pub fn files(&self, name: &str) -> Vec<&str> {
+        let by_name: Vec<&str> = self.modules
+            .iter()
+            .filter(|module| module.name == name || module.path_name == name)
+            .map(|module| module.filepath.as_str())
+            .collect();
+        if !by_name.is_empty() {
+            return by_name
+        }
+
+        let suffix = format!(".{}", name);
+        self.modules
+            .iter()
+            .filter(|module| module.path_name.ends_with(&suffix))
+            .map(|module| module.filepath.as_str())
+            .collect()
+    }
+
+    // ..base imported from tmp/repo/models/child.py -> tmp.repo.base, absolute names are returned as they are
+    pub fn absolute(&self, filepath: &str, name: &str) -> Option<String> {
+        let dots = name.chars().take_while(|c| *c == '.').count();
+        if dots == 0 {
+            return Some(name.to_string())
+        }
+
+        let module = self.modules.iter().find(|module| module.filepath == filepath)?;
+        let mut package: Vec<&str> = module.path_name.split('.').collect();
+        if !module.package {
+            package.pop();
+        }
+        for _ in 1..dots {
+            package.pop()?;
+        }
+
+        let rest = &name[dots..];
+        if !rest.is_empty() {
+            package.push(rest);
+        }
+
+        Some(package.join("."))
+    }
+}
+
+// Module paths for rust files, found by following mod declarations down from each crate root, lib.rs
+// or main.rs, so src/parser/rust.rs is crate::parser::rust if src/parser.rs has mod rust;. Files no
+// declaration reaches are placed by their path under the nearest src directory.
+pub struct CrateMap {
+    modules: Vec<RustModule>,
+    // library name -> root file, eg classtree -> src/lib.rs, for binaries using their own library
+    libraries: HashMap<String, String>,
+}
+
+struct RustModule {
+    filepath: String,
+    // the root file of the crate the module is in
+    krate: String,
+    // crate::parser::rust -> ["crate", "parser", "rust"]
+    path: Vec<String>,
+}
+
+impl CrateMap {
+    pub fn rust(filepaths: &[&str], declarations: &[ModuleDeclaration]) -> CrateMap {
+        let files: HashMap<PathBuf, &str> = filepaths
+            .iter()
+            .map(|filepath| (normalise(filepath), *filepath))
+            .collect();
+
+        let mut roots: Vec<&str> = filepaths
+            .iter()
+            .copied()
+            .filter(|filepath| matches!(file_name(filepath), "lib.rs" | "main.rs"))
+            .collect();
+        // a file both reach belongs to the library
+        roots.sort_by_key(|filepath| file_name(filepath) != "lib.rs");
+
+        let mut modules: Vec<RustModule> = Vec::new();
+        let mut libraries = HashMap::new();
+        for root in roots {
+            if modules.iter().any(|module| module.filepath == root) {
+                continue;
+            }
+            if file_name(root) == "lib.rs"
+                && let Some(name) = library_name(root) {
+                libraries.insert(name, root.to_string());
+            }
+
+            let mut queue = vec![(root.to_string(), vec![String::from("crate")])];
+            while let Some((filepath, path)) = queue.pop() {
+                let directory = child_directory(&filepath);
+
+                for declaration in declarations.iter().filter(|declaration| declaration.filepath == filepath) {
+                    let namespace: Vec<String> = declaration.namespace
+                        .split("::")
+                        .filter(|segment| !segment.is_empty())
+                        .map(|segment| segment.to_string())
+                        .collect();
+
+                    // #[path] is relative to the declaring file's directory
+                    let candidates = match &declaration.path {
+                        Some(file) => vec![normalise(&filepath).with_file_name(file)],
+                        None => {
+                            let directory = namespace.iter().fold(directory.clone(), |directory, segment| directory.join(segment));
+                            vec![directory.join(format!("{}.rs", declaration.name)), directory.join(&declaration.name).join("mod.rs")]
+                        },
+                    };
+
+                    let file = candidates.iter().find_map(|candidate| files.get(&normalise(&candidate.to_string_lossy())));
+                    if let Some(file) = file
+                        && !modules.iter().any(|module| module.filepath == *file) {
+                        let mut module_path = path.clone();
+                        module_path.extend(namespace);
+                        module_path.push(declaration.name.clone());
+                        queue.push((file.to_string(), module_path));
+                    }
+                }
+
+                modules.push(RustModule {
+                    filepath,
+                    krate: root.to_string(),
+                    path,
+                });
+            }
+        }
+
+        for filepath in filepaths {
+            if !modules.iter().any(|module| module.filepath == *filepath) {
+                modules.push(undeclared_module(filepath, &modules));
+            }
+        }
+
+        CrateMap {
+            modules,
+            libraries,
+        }
+    }
+
+    // The crate a file is in and its path within it
+    pub fn module(&self, filepath: &str) -> Option<(&str, &[String])> {
+        self.modules
+            .iter()
+            .find(|module| module.filepath == filepath)
+            .map(|module| (module.krate.as_str(), module.path.as_slice()))
+    }
+
+    pub fn is_module(&self, krate: &str, path: &[String]) -> bool {
+        self.modules.iter().any(|module| module.krate == krate && module.path == path)
+    }
+
+    // The root file of the library with this name
+    pub fn library(&self, name: &str) -> Option<&str> {
+        self.libraries.get(name).map(|root| root.as_str())
+    }
+}
+
+// ES modules, typescript and javascript files, which import each other by their path relative to the
+// importing file, usually without the extension. ./shapes/base imported from src/app.ts is
+// src/shapes/base.ts, or src/shapes/base/index.ts if it's a directory. Bare specifiers such as react
+// are packages, which aren't read.
+pub struct EsModuleMap {
+    files: HashMap<PathBuf, String>,
+}
+
+const ES_EXTENSIONS: [&str; 8] = ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+impl EsModuleMap {
+    pub fn new(filepaths: &[&str]) -> EsModuleMap {
+        EsModuleMap {
+            files: filepaths
+                .iter()
+                .map(|filepath| (normalise(filepath), filepath.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn contains(&self, filepath: &str) -> bool {
+        self.files.contains_key(&normalise(filepath))
+    }
+
+    // The file a specifier written in filepath refers to
+    pub fn file(&self, filepath: &str, specifier: &str) -> Option<&str> {
+        if !specifier.starts_with('.') {
+            return None
+        }
+
+        let mut path = normalise(filepath);
+        path.pop();
+        for component in Path::new(specifier).components() {
+            match component {
+                Component::ParentDir => {
+                    path.pop();
+                },
+                Component::Normal(segment) => path.push(segment),
+                _ => {},
+            }
+        }
+
+        let mut candidates = vec![path.clone()];
+        // typescript imports ./base.js for base.ts, as that's what it compiles to
+        let written = path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| ES_EXTENSIONS.contains(&extension));
+        let stem = if written { path.with_extension("") } else { path.clone() };
+        for extension in ES_EXTENSIONS {
+            let mut file = stem.clone().into_os_string();
+            file.push(format!(".{}", extension));
+            candidates.push(PathBuf::from(file));
+        }
+        for extension in ES_EXTENSIONS {
+            candidates.push(path.join(format!("index.{}", extension)));
+        }
+
+        candidates
+            .iter()
+            .find_map(|candidate| self.files.get(candidate))
+            .map(|file| file.as_str())
+    }
+}
+
+// Where the files of a module's children are, src/ for src/lib.rs and src/a/mod.rs, src/a/ for src/a.rs
+fn child_directory(filepath: &str) -> PathBuf {
+    let path = normalise(filepath);
+    let directory = path.parent().map(|parent| parent.to_path_buf()).unwrap_or_default();
+
+    match file_name(filepath) {
+        "lib.rs" | "main.rs" | "mod.rs" => directory,
+        _ => directory.join(path.file_stem().unwrap_or_default()),
+    }
+}
+
+// A file no mod declaration reached, in the crate of the nearest src directory above it
+fn undeclared_module(filepath: &str, modules: &[RustModule]) -> RustModule {
+    let path = normalise(filepath);
+    let source = path.ancestors().skip(1).find(|ancestor| ancestor.file_name().is_some_and(|name| name == "src"));
+    let directory = source.or(path.parent()).unwrap_or(Path::new(""));
+
+    let mut module_path = vec![String::from("crate")];
+    module_path.extend(segments(&path.strip_prefix(directory).unwrap_or(&path).to_string_lossy()));
+    if module_path.last().is_some_and(|file| file == "mod") {
+        module_path.pop();
+    }
+
+    // the crate of a lib.rs or main.rs in the same directory, otherwise one named after the directory
+    let krate = modules
+        .iter()
+        .find(|module| module.path.len() == 1 && normalise(&module.filepath).parent() == Some(directory))
+        .map(|module| module.krate.clone())
+        .unwrap_or_else(|| directory.to_string_lossy().to_string());
+
+    RustModule {
+        filepath: filepath.to_string(),
+        krate,
+        path: module_path,
+    }
+}
+
+// The name other crates use for the library rooted at src/lib.rs, from the Cargo.toml beside src/
+fn library_name(root: &str) -> Option<String> {
+    let manifest = fs::read_to_string(normalise(root).parent()?.parent()?.join("Cargo.toml")).ok()?;
+
+    let mut section = "";
+    let mut names = HashMap::new();
+    for line in manifest.lines().map(|line| line.trim()) {
+        if line.starts_with('[') {
+            section = line;
+        } else if let Some(value) = line.strip_prefix("name").and_then(|rest| rest.trim_start().strip_prefix('=')) {
+            names.insert(section, value.trim().trim_matches('"').to_string());
+        }
+    }
+
+    // [lib] name, or the package name with -s made _s
+    names
+        .get("[lib]")
+        .or(names.get("[package]"))
+        .map(|name| name.replace('-', "_"))
+}
+
+fn normalise(filepath: &str) -> PathBuf {
+    Path::new(filepath).components().collect()
+}
+
+fn file_name(filepath: &str) -> &str {
+    Path::new(filepath).file_name().and_then(|name| name.to_str()).unwrap_or("")
+}
+
+// models/base.py -> ["models", "base"]
+fn segments(filepath: &str) -> Vec<String> {
+    let path = Path::new(filepath);
+    let mut segments: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    if let Some(stem) = path.file_stem() {
+        segments.pop();
+        segments.push(stem.to_string_lossy().to_string());
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrateMap, ModuleMap};
+    use crate::structs::ModuleDeclaration;
+
+    fn declaration(filepath: &str, name: &str, path: Option<&str>) -> ModuleDeclaration {
+        ModuleDeclaration {
+            name: name.to_string(),
+            path: path.map(|path| path.to_string()),
+            namespace: String::new(),
+            filepath: filepath.to_string(),
+        }
+    }
+
+    fn path(crates: &CrateMap, filepath: &str) -> Vec<String> {
+        crates.module(filepath).map(|(_, path)| path.to_vec()).unwrap_or_default()
+    }
+
+    #[test]
+    fn python_modules_are_named_from_their_package() {
+        let modules = ModuleMap::python(&["repo/models/__init__.py", "repo/models/base.py", "repo/models/shapes/circle.py", "repo/app.py"]);
+
+        assert_eq!(modules.files("models.base"), ["repo/models/base.py"]);
+        assert_eq!(modules.files("models"), ["repo/models/__init__.py"]);
+        // shapes has no __init__.py, so circle is a top level module, and otherwise known by its path
+        assert_eq!(modules.files("circle"), ["repo/models/shapes/circle.py"]);
+        assert_eq!(modules.files("shapes.circle"), ["repo/models/shapes/circle.py"]);
+        assert_eq!(modules.absolute("repo/models/base.py", ".shapes"), Some(String::from("repo.models.shapes")));
+        assert_eq!(modules.absolute("repo/models/__init__.py", ".base"), Some(String::from("repo.models.base")));
+        assert_eq!(modules.absolute("repo/models/base.py", "..app"), Some(String::from("repo.app")));
+        assert_eq!(modules.absolute("repo/app.py", "models"), Some(String::from("models")));
+    }
+
+    #[test]
+    fn modules_are_found_through_mod_declarations() {
+        let files = ["src/lib.rs", "src/parser.rs", "src/parser/rust.rs", "src/shapes/mod.rs", "src/other.rs"];
+        let declarations = [
+            declaration("src/lib.rs", "parser", None),
+            declaration("src/lib.rs", "shapes", None),
+            declaration("src/parser.rs", "rust", None),
+        ];
+        let crates = CrateMap::rust(&files, &declarations);
+
+        assert_eq!(path(&crates, "src/lib.rs"), ["crate"]);
+        assert_eq!(path(&crates, "src/parser/rust.rs"), ["crate", "parser", "rust"]);
+        assert_eq!(path(&crates, "src/shapes/mod.rs"), ["crate", "shapes"]);
+        // placed by its path, as nothing declares it
+        assert_eq!(path(&crates, "src/other.rs"), ["crate", "other"]);
+        assert_eq!(crates.module("src/other.rs").unwrap().0, "src/lib.rs");
+        assert!(crates.is_module("src/lib.rs", &[String::from("crate"), String::from("parser")]));
+    }
+
+    #[test]
+    fn path_attributes_are_relative_to_the_declaring_file() {
+        let files = ["src/main.rs", "src/generated/shapes.rs"];
+        let declarations = [declaration("src/main.rs", "shapes", Some("generated/shapes.rs"))];
+        let crates = CrateMap::rust(&files, &declarations);
+
+        assert_eq!(path(&crates, "src/generated/shapes.rs"), ["crate", "shapes"]);
+    }
+}
@@ -1,10 +1,21 @@
-use std::collections::HashMap;
-
-use crate::structs::{ASTNode, Function, Object, Parameter, Token, TokenisedFileData, Variable};
+mod cpp;
+mod csharp;
+mod go;
+mod java;
+mod kotlin;
+mod php;
+mod ruby;
+mod rust;
+mod swift;
+mod typescript;
+
+use crate::resolver::base_name;
+use crate::structs::{tokens_to_string, ASTNode, Access, Diagnostic, Function, Import, ModuleDeclaration, Object, ObjectKind, ParsedModel, Parameter, Severity, Span, Token, TokenisedFileData, Variable, VariableKind};
+
+const SUPPORTED_FILE_TYPES: [&str; 12] = ["python", "rust", "java", "csharp", "typescript", "javascript", "cpp", "kotlin", "swift", "go", "ruby", "php"];
 
 pub struct Parser {
     files: Vec<TokenisedFileData>,
-    hash: HashMap<String, TokenisedFileData>,
 
     pos: i64,
     current_line_no: i64,
@@ -12,41 +23,103 @@ pub struct Parser {
     current_line: Vec<Token>,
     current_file: TokenisedFileData,
     current_object: String,
+    // (line, column) just past the last token moved over, where spans end
+    previous_end: (i64, usize),
+    // self.x assignments found in method bodies, claimed by the enclosing object
+    body_variables: Vec<Variable>,
+    // python decorators waiting for the def or class they belong to
+    decorators: Vec<String>,
+    // rust attributes waiting for their item
+    attributes: Vec<String>,
+    // classes declared in method bodies and initialisers, eg java's anonymous classes, claimed by the enclosing class
+    local_objects: Vec<Object>,
+    // members defined outside of their class, c++'s void Shape::draw() { }, kotlin's fun Shape.scale() { } or go's func (s *Shape) Draw() { }
+    definitions: Vec<Object>,
+    // how many function bodies we are inside, assignments in them are locals
+    function_depth: usize,
+    // modules declared around the current position within the file, mod a { mod b { } } in rust or the namespaces of c# and c++
+    namespace: Vec<String>,
+    imports: Vec<Import>,
+    modules: Vec<ModuleDeclaration>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// The annotations and modifiers before a declaration, as far as its language has them
+#[derive(Default)]
+struct Modifiers {
+    // annotation, decorator and swift attribute names, without their arguments
+    decorators: Vec<String>,
+    // c#'s [Serializable] and c++'s [[deprecated("use area")]] with their arguments, php's #[Route] without
+    attributes: Vec<String>,
+    // c++'s template <typename T, int N> -> ["T", "N"]
+    generics: Vec<String>,
+    // None without a modifier, what that means is up to the language and where the declaration is
+    access: Option<Access>,
+    is_static: bool,
+    is_final: bool,
+    is_abstract: bool,
+    // readonly, const, or constexpr and the like in c++, where const is kept as part of the type
+    readonly: bool,
+    // java's @interface
+    annotation_type: bool,
+    // c#'s partial class
+    partial: bool,
+    // c++'s typedef struct { } Name; names the struct after its body
+    typedef: bool,
+    // c++'s friends are declared in a class but aren't its members
+    friend: bool,
+    // kotlin's data class, enum class and companion object
+    data: bool,
+    enum_class: bool,
+    companion: bool,
+    // swift's private(set) and the like, the property can't be set from where it can be read
+    private_setter: bool,
+    // swift's class func and class var, which subclasses can override
+    is_class: bool,
+    // export, and export default
+    exported: bool,
+    default_export: bool,
 }
 
+// Whether a statement carries on from the tokens read so far onto a line starting with the next token, in
+// languages where the end of a line can end one
+type Continues = fn(&[Token], &Token) -> bool;
+
 impl Parser {
     pub fn new(files: Vec<TokenisedFileData>) -> Parser {
-        let mut hash = HashMap::new();
-        for file in &files {
-            hash.insert(file.filepath.clone(), file.clone());
-        }
         Parser {
             files: files.clone(),
-            hash,
 
             pos: 0,
             current_line_no: 0,
             current_token: Token::Other('a'),
             current_line: Vec::new(),
-            current_file: files[0].clone(),
+            current_file: files.first().cloned().unwrap_or_default(),
             current_object: String::new(),
+            previous_end: (0, 0),
+            body_variables: Vec::new(),
+            decorators: Vec::new(),
+            attributes: Vec::new(),
+            local_objects: Vec::new(),
+            definitions: Vec::new(),
+            function_depth: 0,
+            namespace: Vec::new(),
+            imports: Vec::new(),
+            modules: Vec::new(),
+            diagnostics: Vec::new(),
         }
     }
 
-    fn peek(&mut self) -> Token {
+    fn peek(&self) -> Token {
         let next_pos = self.pos + 1;
-        let mut next_token = Token::None;
-        if self.pos < self.current_line.len().try_into().unwrap() {
-            next_token = self.current_line[next_pos as usize].clone();
-            next_token
+        if next_pos < self.current_line.len() as i64 {
+            self.current_line[next_pos as usize].clone()
         } else {
             let line = self.peek_line();
             if line.is_empty() {
-                next_token = Token::Eof;
                 Token::Eof
             } else {
-                next_token = line[0].clone();
-                next_token
+                line[0].clone()
             }
         }
 
@@ -54,8 +127,9 @@ impl Parser {
 
 
     fn advance(&mut self) -> Token {
+        self.previous_end = self.token_end(self.pos);
         self.pos += 1;
-        if self.pos < self.current_line.len().try_into().unwrap() {
+        if self.pos < self.current_line.len() as i64 {
             self.current_token = self.current_line[self.pos as usize].clone();
             self.current_token.clone()
         } else {
@@ -77,55 +151,38 @@ impl Parser {
 
         while self.pos < len {
             tokens.push(self.current_line[self.pos as usize].clone());
+            self.previous_end = self.token_end(self.pos);
             self.pos += 1;
         }
 
         tokens
     }
 
-    fn advance_up_to(&mut self, upto: Token) -> Vec<Token> {
-        let mut tokens = Vec::new(); // : -> <T>
-        self.advance();
-        while self.current_token != upto {
-            tokens.push(self.current_token.clone());
-            self.advance();
-        }
-        self.advance(); // skip upto token
-
-        return tokens
-
-    }
-
-    fn expect(&mut self, expected: Token) -> Token {
-        self.advance();
-
-        if self.current_token != expected {
-            panic!("Expected {:?}, but found {:?}", expected, self.current_token)
+    fn advance_line(&mut self) -> Vec<Token> {
+        if !self.current_line.is_empty() {
+            self.previous_end = self.token_end(self.current_line.len() as i64 - 1);
         }
 
-        self.current_token.clone()
-
-    }
-
-    fn advance_line(&mut self) -> Vec<Token> {
         while (self.current_line_no as usize) < self.current_file.lines.len() {
-            println!("CHANGING LINE FROM {:?}", self.current_line);
             self.pos = 0;
             self.current_line_no += 1;
-            self.current_line = self.current_file.lines[&self.current_line_no].to_vec();
+            self.current_line = self.current_file.lines.get(&self.current_line_no).cloned().unwrap_or_default();
             // println!("CHANGING LINE ---- NEW LINE : {:?}", self.current_line);
             if !self.current_line.is_empty() {
                 self.current_token = self.current_line[0].clone();
                 return self.current_line.clone();
             }
         }
+
+        self.current_line = Vec::new();
+        self.current_token = Token::Eof;
         Vec::new()
     }
 
     fn peek_line(&self) -> Vec<Token> {
-        let mut line_no = self.current_line_no;
-        while (line_no as usize) < self.current_file.lines.len() {
-            let line = self.current_file.lines[&line_no].to_vec();
+        let mut line_no = self.current_line_no + 1;
+        while (line_no as usize) <= self.current_file.lines.len() {
+            let line = self.current_file.lines.get(&line_no).cloned().unwrap_or_default();
             line_no += 1;
             if !line.is_empty() {
                 return line;
@@ -134,48 +191,308 @@ impl Parser {
         Vec::new()
     }
 
-    pub fn parse(&mut self) {
-        self.display_file_as_tokens();
-        println!("\n");
+    // (line, column) of the current token
+    fn position(&self) -> (i64, usize) {
+        let column = self.current_file.positions
+            .get(&self.current_line_no)
+            .and_then(|positions| positions.get(self.pos as usize))
+            .map(|position| position.column)
+            .unwrap_or(0);
+
+        (self.current_line_no, column)
+    }
+
+    fn token_end(&self, pos: i64) -> (i64, usize) {
+        match self.current_file.positions
+            .get(&self.current_line_no)
+            .and_then(|positions| positions.get(pos as usize)) {
+            Some(position) => (self.current_line_no, position.column + position.length),
+            None => self.previous_end,
+        }
+    }
+
+    // From start up to the last token moved over
+    fn span_from(&self, start: (i64, usize)) -> Span {
+        Span {
+            file_no: self.current_file.file_no,
+            start_line: start.0,
+            start_column: start.1,
+            end_line: self.previous_end.0,
+            end_column: self.previous_end.1,
+        }
+    }
 
-        let possible_objects = self.possible_objects();
+    fn diagnostic(&mut self, severity: Severity, position: (i64, usize), message: String) {
+        self.diagnostics.push(Diagnostic {
+            filepath: self.current_file.filepath.clone(),
+            line: position.0,
+            column: position.1,
+            length: 1,
+            severity,
+            message,
+        });
+    }
 
-        let mut nodes: Vec<ASTNode> = Vec::new();
+    // Points at the whole of the current token
+    fn token_diagnostic(&mut self, severity: Severity, message: String) {
+        let (line, column) = self.position();
+        let (_, end) = self.token_end(self.pos);
+
+        self.diagnostics.push(Diagnostic {
+            filepath: self.current_file.filepath.clone(),
+            line,
+            column,
+            length: end.saturating_sub(column).max(1),
+            severity,
+            message,
+        });
+    }
 
-        for possible_object in possible_objects {
-            let filepath = possible_object.0;
+    // Gives up on the rest of the current line
+    fn recover(&mut self) -> ASTNode {
+        self.advance_line();
+        ASTNode::None
+    }
 
-            let line_no = possible_object.1;
-            self.current_line_no = line_no;
+    fn line_indent(&self, line_no: i64) -> i16 {
+        match self.current_file.lines.get(&line_no).and_then(|line| line.first()) {
+            Some(Token::Indent(i)) => *i,
+            _ => 0,
+        }
+    }
+
+    // Where objects are declared within the file, a::b inside mod a { mod b { } }, A.B in namespace A.B { },
+    // A\B after php's namespace A\B;. C++'s anonymous namespaces have no name
+    fn namespace(&self) -> String {
+        let names: Vec<&str> = self.namespace.iter().map(|name| name.as_str()).filter(|name| !name.is_empty()).collect();
+        match self.current_file.file_type.as_str() {
+            "rust" | "cpp" | "ruby" => names.join("::"),
+            "php" => names.join("\\"),
+            _ => names.join("."),
+        }
+    }
+
+    // An object with nothing but its name and where it is filled in
+    fn blank_object(&self, identifier: String, kind: ObjectKind, public: bool, start: (i64, usize)) -> Object {
+        Object {
+            identifier,
+            kind,
+            is_abstract: false,
+            public,
+            decorators: Vec::new(),
+            attributes: Vec::new(),
+            generics: Vec::new(),
+            where_clauses: Vec::new(),
+            metaclass: None,
+            keywords: Vec::new(),
+            variables: Vec::new(),
+            functions: Vec::new(),
+            objects: Vec::new(),
+            parents: Vec::new(),
+            interfaces: Vec::new(),
+            partial: false,
+            implementations: Vec::new(),
+            namespace: self.namespace(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        }
+    }
 
-            let file = self.hash.get(&filepath).unwrap();
-            self.current_file = file.clone();
+    #[allow(clippy::too_many_arguments)]
+    fn new_variable(
+        &self,
+        identifier: String,
+        kind: VariableKind,
+        access: Access,
+        vtype: &[Token],
+        value: Option<Vec<Token>>,
+        parent: &str,
+        span: Span,
+    ) -> Variable {
+        Variable {
+            identifier,
+            kind,
+            readonly: false,
+            value,
+            vtype: if vtype.is_empty() { None } else { Some(vtype.to_vec()) },
+            parent: parent.to_string(),
+            access,
+            filepath: self.current_file.filepath.clone(),
+            span,
+        }
+    }
 
-            let lines = file.lines.clone();
+    // Statements or an expression, up to a token stop matches outside of any brackets, eg the ; after a
+    // field's value or the } closing a method body. Classes declared in them go to local_objects
+    fn code_until(&mut self, stop: fn(&Token) -> bool) -> Vec<Token> {
+        self.until(None, false, stop)
+    }
 
-            let line = lines.get(&line_no);
-            self.current_line = line.unwrap().clone();
+    // Whether the next token, past line breaks and comments, is token, or any { for a BlockOpen
+    fn next_is(&mut self, token: Token) -> bool {
+        self.skip_layout();
+        match token {
+            Token::BlockOpen(_) => matches!(self.current_token, Token::BlockOpen(_)),
+            token => self.current_token == token,
+        }
+    }
 
-            self.pos = 0;
-            self.current_token = self.current_line[self.pos as usize].clone();
+    // As code_until, in a language where a ; or the end of a line ends a statement unless continues says the
+    // next line carries it on. Types count < and > as brackets when angles is set
+    fn until(&mut self, continues: Option<Continues>, angles: bool, stop: fn(&Token) -> bool) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+        // where the last of what was read ends
+        let mut line = None;
 
-            while self.current_token != Token::BlockClose && self.current_token != Token::Eof {
-                // println!("RUNNING PARSE TOKEN FROM WHILE LOOP IN PARSE");
-                let node = self.parse_token();
-                if node == ASTNode::Eof {
-                    break;
-                }
-                nodes.push(node);
+        loop {
+            self.skip_layout();
+            if let (Some(continues), Some(line)) = (continues, line)
+                && depth == 0
+                && self.position().0 > line
+                && !continues(&tokens, &self.current_token) {
+                break
+            }
+
+            match self.current_token {
+                Token::Eof => break,
+                ref token if depth == 0 && stop(token) => break,
+                Token::Semicolon if depth == 0 && continues.is_some() => break,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose if depth == 0 => break,
+                Token::AngleBracket('>') if depth == 0 && angles => break,
+                _ => {},
+            }
+
+            if let Some(declaration) = self.local_declaration() {
+                tokens.extend(declaration);
+                line = Some(self.previous_end.0);
+                continue;
             }
 
+            match self.current_token {
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_) => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose => depth -= 1,
+                Token::AngleBracket('<') if angles => depth += 1,
+                Token::AngleBracket('>') if angles => depth -= 1,
+                _ => {},
+            }
+            line = Some(self.position().0);
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // A class declared within code, along with the tokens moved over reading it
+    fn local_declaration(&mut self) -> Option<Vec<Token>> {
+        match self.current_file.file_type.as_str() {
+            "java" => self.java_local_declaration(),
+            "typescript" | "javascript" => self.typescript_local_declaration(),
+            _ => None,
         }
+    }
 
-        println!("\n \n");
-        for n in nodes.clone() {
-            println!("NODE : {:?}", n);
+    // Keeps a member defined outside of the class it's for, eg c++'s void Shape::draw() { }, for the resolver
+    // to merge into the class. Those for the same class within a file are gathered into one piece
+    fn add_definition(&mut self, class: String, member: ASTNode, start: (i64, usize)) {
+        let namespace = self.namespace();
+        let span = self.span_from(start);
+
+        let index = match self.definitions.iter().position(|piece| piece.identifier == class && piece.namespace == namespace) {
+            Some(index) => index,
+            None => {
+                let piece = self.blank_object(class, ObjectKind::Impl, true, start);
+                self.definitions.push(piece);
+                self.definitions.len() - 1
+            },
+        };
+        let piece = &mut self.definitions[index];
+        piece.span.end_line = span.end_line;
+        piece.span.end_column = span.end_column;
+        match member {
+            ASTNode::Function(function) => piece.functions.push(function),
+            ASTNode::Variable(variable) => piece.variables.push(variable),
+            _ => {},
         }
-        println!("\n");
-        println!("{:?}", nodes.clone());
+    }
+
+    // Moves past the line indents and comments between tokens
+    fn skip_layout(&mut self) {
+        while matches!(self.current_token, Token::Indent(_) | Token::Comment(_)) {
+            self.advance();
+        }
+    }
+
+    // The body of a method or initialiser, from its { to just past its }
+    fn code_block(&mut self) {
+        let open = self.position();
+        self.advance(); // skip {
+        self.code_until(|token| *token == Token::BlockClose);
+
+        if self.current_token == Token::Eof {
+            self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+        }
+        self.advance(); // skip }
+    }
+
+    // Without a modifier, python names are public unless they start with _ (dunders are public)
+    fn default_publicity(&self, identifier: &str) -> bool {
+        self.current_file.file_type == "python"
+            && (!identifier.starts_with('_') || (identifier.starts_with("__") && identifier.ends_with("__")))
+    }
+
+    fn is_ecmascript(&self) -> bool {
+        matches!(self.current_file.file_type.as_str(), "typescript" | "javascript")
+    }
+
+    fn is_comment_line(&self) -> bool {
+        matches!(self.current_line.as_slice(), [Token::Comment(_)] | [Token::Indent(_), Token::Comment(_)])
+    }
+
+    pub fn parse(&mut self) -> ParsedModel {
+        let mut model = ParsedModel::default();
+
+        for file in self.files.clone() {
+            model.files.push((file.filepath.clone(), file.file_type.clone()));
+            model.diagnostics.extend(file.diagnostics.clone());
+            self.current_file = file;
+            self.current_line_no = 0;
+            self.current_line = Vec::new();
+            self.previous_end = (0, 0);
+            self.current_object = String::new();
+            self.body_variables = Vec::new();
+            self.decorators = Vec::new();
+            self.attributes = Vec::new();
+            self.local_objects = Vec::new();
+            self.definitions = Vec::new();
+            self.function_depth = 0;
+            self.namespace = Vec::new();
+            self.advance_line();
+
+            if !SUPPORTED_FILE_TYPES.contains(&self.current_file.file_type.as_str()) {
+                let message = format!("file type `{}` is not supported, only objects and functions will be read", self.current_file.file_type);
+                self.diagnostic(Severity::Warning, (0, 0), message);
+            }
+
+            while self.current_token != Token::Eof {
+                match self.parse_token() {
+                    ASTNode::Object(object) if object.kind == ObjectKind::Module && matches!(self.current_file.file_type.as_str(), "rust" | "csharp" | "typescript" | "javascript" | "cpp" | "go" | "php") => {
+                        add_module_items(object, &mut model);
+                    },
+                    ASTNode::Object(object) => model.objects.push(object),
+                    ASTNode::Function(function) => model.functions.push(function),
+                    ASTNode::Variable(variable) => model.variables.push(variable),
+                    _ => {},
+                }
+            }
+            model.objects.append(&mut self.definitions);
+        }
+
+        model.imports.append(&mut self.imports);
+        model.modules.append(&mut self.modules);
+        model.diagnostics.append(&mut self.diagnostics);
+        model
     }
 
     pub fn display_file_as_tokens(&self) {
@@ -201,14 +518,14 @@ impl Parser {
         let mut line_numbers_of_possible_objects: Vec<(String, i64)> = Vec::new();
         for file in &self.files {
             for lines in &file.lines {
-                if lines.1.contains(&Token::ObjectDeclaration) {
+                if lines.1.iter().any(|token| matches!(token, Token::ObjectDeclaration(_))) {
                     line_numbers_of_possible_objects.push((file.filepath.clone(), *lines.0));
                     // println!("{} {:?} {:?}", lines.0, lines.1, file.filepath)
                 }
             }
         }
 
-        return line_numbers_of_possible_objects
+        line_numbers_of_possible_objects
     }
 
     // class Parent:
@@ -218,210 +535,331 @@ impl Parser {
     fn parse_token(&mut self) -> ASTNode {
         // println!("RUNNING PARSE TOKEN {:?} {:?}", self.current_token, self.current_line);
         match self.current_token {
-            Token::ObjectDeclaration => self.handle_object(false),
+            Token::ObjectDeclaration(_) | Token::Publicity(_) | Token::Identifier(_) | Token::Other('@')
+                if self.current_file.file_type == "java" => self.handle_java_declaration(),
+            Token::ObjectDeclaration(_) | Token::Publicity(_) | Token::Identifier(_) | Token::Bracket('[')
+                if self.current_file.file_type == "csharp" => self.handle_csharp_declaration(),
+            Token::ObjectDeclaration(_) | Token::Publicity(_) | Token::Identifier(_) | Token::Other('@')
+                if self.is_ecmascript() => self.handle_typescript_declaration(),
+            Token::ObjectDeclaration(_) | Token::Identifier(_) | Token::Type(..) | Token::Connect | Token::Bracket('[')
+                if self.current_file.file_type == "cpp" => self.handle_cpp_declaration(),
+            Token::ObjectDeclaration(_) | Token::Publicity(_) | Token::Identifier(_) | Token::Other('@')
+                if self.current_file.file_type == "kotlin" => self.handle_kotlin_declaration(),
+            Token::ObjectDeclaration(_) | Token::Publicity(_) | Token::Identifier(_) | Token::Other('@')
+                if self.current_file.file_type == "swift" => self.handle_swift_declaration(),
+            // before the generic arms, which would read type Name struct as two declarations
+            Token::ObjectDeclaration(_) | Token::Identifier(_)
+                if self.current_file.file_type == "go" => self.handle_go_declaration(),
+            // blocks end with end rather than a brace or dedent
+            _ if self.current_file.file_type == "ruby" => self.handle_ruby_declaration(),
+            // $variables, use declarations and statements as well as types, so all of it
+            _ if self.current_file.file_type == "php" => self.handle_php_declaration(),
+
+            Token::ObjectDeclaration(_) => self.handle_object(false),
+            Token::Trait if self.current_file.file_type == "rust" => self.handle_object(false),
             Token::Publicity(public) => self.handle_public_object(public),
 
             Token::VariableDeclaration | Token::SelfToken => self.handle_variable_declaration(),
+            Token::Identifier(_) if self.is_declared_variable() => self.handle_variable_declaration(),
             Token::FunctionDeclaration => self.handle_function_declaration(),
+            Token::Other('@') if self.current_file.file_type == "python" => self.handle_decorator(),
+            Token::Other('#') if self.current_file.file_type == "rust" => self.handle_attribute(),
+            Token::Identifier(ref id) if id == "use" && self.current_file.file_type == "rust" => self.handle_use(),
+            Token::Identifier(ref id) if id == "mod" && self.current_file.file_type == "rust" => self.handle_module(),
+            Token::Identifier(ref id) if (id == "import" || id == "from")
+                && self.current_file.file_type == "python"
+                && self.at_statement_start() => self.handle_import(),
 
             Token::Indent(_) => {
                 self.advance();
-                self.parse_token()
+                match self.current_token {
+                    Token::BlockClose => ASTNode::None, // left for the enclosing block
+                    _ => self.parse_token(),
+                }
+            },
+
+            // blocks that aren't owned by a declaration, eg if / match / let bodies
+            Token::BlockOpen(_) => {
+                self.handle_block(self.current_line_no);
+                ASTNode::None
             },
 
             Token::Eof => ASTNode::Eof,
 
             // _ => panic!("Cant parse following token : {:?}, at line {}: {:?}", self.current_token, self.current_line_no, self.current_line)
             _ => {
-                println!("Skipping unrecognised token: {:?}", self.current_token);
                 self.advance();
                 ASTNode::None
             }
         }
     }
 
+    // Splits a bracketed list into its top level, comma separated items: (a, b[c, d]) -> [[a], [b, [, c, ,, d, ]]]
+    fn parse_list(&mut self, open: Token, close: Token) -> Vec<Vec<Token>> {
+        self.parse_spanned_list(open, close)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect()
+    }
 
-    fn parse_type(&mut self) -> String {
-        let mut tokens = Vec::new();
-        let mut count = 0;
-        let mut first_loop = true;
+    // parse_list along with where each item is
+    fn parse_spanned_list(&mut self, open: Token, close: Token) -> Vec<(Vec<Token>, Span)> {
+        let mut items = Vec::new();
+        let mut item = Vec::new();
+        let mut item_start = (0, 0);
+        let mut depth = 0;
 
-        loop {
+        if self.current_token != open {
+            return items;
+        }
+        let start = self.position();
+        self.advance(); // skip open
+
+        while self.current_token != Token::Eof {
             match self.current_token {
-                Token::Parenthesis('(') => count += 1,
-                Token::Parenthesis(')') => count -= 1,
-                Token::Bracket('[') => count += 1,
-                Token::Bracket(']') => count -= 1,
-                Token::BlockOpen('{') => count += 1,
-                Token::BlockClose => count -= 1,
-                Token::AngleBracket('<') => count += 1,
-                Token::AngleBracket('>') => count -= 1,
+                ref token if *token == close && depth == 0 => break,
 
+                Token::Comma if depth == 0 => {
+                    items.push((std::mem::take(&mut item), self.span_from(item_start)));
+                    self.advance();
+                    continue;
+                },
 
-                Token::Comma | Token::Colon if count == 0 && first_loop == false => break,
+                // => is an arrow rather than a closing >
+                Token::Equals if self.peek() == Token::AngleBracket('>') => {
+                    if item.is_empty() {
+                        item_start = self.position();
+                    }
+                    item.push(self.current_token.clone());
+                    item.push(self.advance());
+                    self.advance();
+                    continue;
+                },
 
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
 
-                Token::Eof => panic!("eof flag?"),
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_) | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose | Token::AngleBracket('>') => depth -= 1,
 
                 _ => {},
-            };
+            }
 
-            first_loop = false;
-            tokens.push(self.current_token.clone());
+            if item.is_empty() {
+                item_start = self.position();
+            }
+            item.push(self.current_token.clone());
             self.advance();
-        };
-
-
-
-        let mut stokens = Vec::new();
-        for token in tokens {
-            stokens.push(token.to_string());
         }
 
-        stokens.join("")
-
-    }
-
+        if !item.is_empty() {
+            items.push((item, self.span_from(item_start)));
+        }
 
-    fn get_identifier(&mut self) -> String {
-        match self.current_token.clone() {
-            Token::Identifier(id) => id,
-            Token::SelfToken => {
-                self.advance();
-                self.advance();
-                match self.current_token.clone() {
-                    Token::Identifier(id) => id,
-                    _ => panic!()
-                }
-            }
-            _ => panic!()
+        if self.current_token == Token::Eof {
+            self.diagnostic(Severity::Error, start, format!("unclosed `{}`", open));
         }
+
+        self.advance(); // skip close
+        items
     }
 
     fn parse_parenthesis(&mut self, parenth_open: Token, parenth_close: Token) -> Vec<Parameter> {
         let mut parameters = Vec::new();
-        if self.current_token == parenth_open {
-            self.advance(); // skip (
 
-            while self.current_token != parenth_close {
-                match self.current_token {
-
-                    // Cover identfiers and self.
-                    Token::Identifier(_) | Token::SelfToken => {
-                        // get identfier
-                        let identifier = match self.current_token.clone() {
-                            Token::Identifier(id) => id,
-                            Token::SelfToken => {
-
-
-                                let next = self.peek();
-
-
-                                if next == Token::Period || next == Token::Connect {
-                                    self.advance(); // skip . | ::
-                                    self.advance();
-                                    match self.current_token.clone() {
-                                        Token::Identifier(id) => id,
-                                        _ => panic!("Unexpected token {:?}", self.current_token)
-                                    }
-                                } else {
-                                    String::from("Self")
-                                }
-                            }
-                            _ => panic!()
-                        };
-
-                        self.advance(); // move past identifier ( identifier -> ? ...  | possibly3
-
-                        let mut ptype = None;
-                        if self.current_token != Token::Parenthesis(')') {
-                            ptype = Some(self.parse_type());
-                        }
-
-                        let param = Parameter {
-                            identifier,
-                            ptype: ptype,
-                        };
-
-                        parameters.push(param);
+        for tokens in self.parse_list(parenth_open, parenth_close) {
+            if let Some(param) = self.parse_parameter(&tokens) {
+                parameters.push(param);
+            }
+        }
 
-                        if self.current_token == Token::Comma {
-                            self.advance();
-                        }
-                    },
+        parameters
+    }
 
+    // identifier: type = default | type identifier | self | *args
+    fn parse_parameter(&self, tokens: &[Token]) -> Option<Parameter> {
+        // the default value, rather than the => of a function type
+        let end = (0..tokens.len())
+            .find(|index| tokens[*index] == Token::Equals && tokens.get(index + 1) != Some(&Token::AngleBracket('>')))
+            .unwrap_or(tokens.len());
+        let tokens = &tokens[..end];
+
+        if let Some(colon) = tokens.iter().position(|token| *token == Token::Colon) {
+            let identifier = tokens[..colon].iter().rev().find_map(|token| match token {
+                Token::SelfToken => Some(String::from("self")),
+                // names that are keywords elsewhere, eg type or object
+                token => token.name().map(|name| name.to_string()),
+            })?;
+
+            return Some(Parameter {
+                identifier,
+                ptype: Some(tokens_to_string(&tokens[colon + 1..])),
+            })
+        }
 
-                    // Cover all types and other tokens eg ([{
-                    Token::Type(_) | Token::Other(_) => {
-                        let ptype = self.parse_type();
-                        self.advance();
-                        let identifier = self.get_identifier();
+        let (last, rest) = tokens.split_last()?;
+        let name = match last {
+            Token::Identifier(id) => id.clone(),
+            Token::SelfToken => String::from("self"),
+            _ => return None,
+        };
 
-                        let param = Parameter {
-                            identifier,
-                            ptype: Some(ptype)
-                        };
+        // *args, **kwargs, &self
+        if rest.iter().all(|token| matches!(token, Token::Other(_))) {
+            let identifier = if *last == Token::SelfToken {
+                name
+            } else {
+                format!("{}{}", tokens_to_string(rest), name)
+            };
 
-                        parameters.push(param)
-                    }
+            return Some(Parameter {
+                identifier,
+                ptype: None,
+            })
+        }
 
-                    _ => panic!("Unexpected token {:?} {:?}", self.current_token, self.current_line)
-                }
+        Some(Parameter {
+            identifier: name,
+            ptype: Some(tokens_to_string(rest)),
+        })
+    }
 
+    // class Child(Base, mixins.Mixin, Generic[T], metaclass=Meta)
+    //     -> ["Base", "mixins.Mixin", "Generic[T]"], [("metaclass", "Meta")]
+    fn parse_parents(&mut self) -> (Vec<String>, Vec<(String, String)>) {
+        let mut parents = Vec::new();
+        let mut keywords = Vec::new();
+
+        for tokens in self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+            match tokens.as_slice() {
+                [Token::Identifier(name), Token::Equals, value @ ..] => keywords.push((name.clone(), tokens_to_string(value))),
+                [] => {},
+                _ => parents.push(tokens_to_string(&tokens)),
             }
-
-            self.advance(); // skip )
         }
 
-        parameters
-
+        (parents, keywords)
     }
 
     fn handle_object(&mut self, public: bool) -> ASTNode {
+        if self.current_file.file_type == "rust" {
+            return self.handle_rust_item(public)
+        }
+
+        let start = self.position();
+        let keyword = self.current_token.to_string();
+        let decorators = std::mem::take(&mut self.decorators);
+        // abstract class Shape
+        let is_abstract = self.current_line[..self.pos as usize].contains(&Token::Identifier(String::from("abstract")));
         self.advance(); // skip ObjectDeclaration
 
-        let id = self.current_token.clone(); // ObjectDeclaration -> Identifier
-                                             //
-        let identifier = match id {
+        // keywords such as `type` and `object` are also used outside of declarations
+        let identifier = match self.current_token.clone() {
+            // class Record, keywords of other languages are names in python
+            token if self.current_file.file_type == "python" && token.name().is_some() => token.to_string(),
             Token::Identifier(id) => id,
-            _ => panic!("Expected identifier")
+            token if self.current_file.file_type == "python" && keyword == "class" => {
+                self.token_diagnostic(Severity::Error, format!("expected a class name after `class`, found `{}`", token));
+                return self.recover()
+            },
+            _ => return ASTNode::None,
         };
 
-        self.current_object = identifier.clone();
+        let enclosing_object = std::mem::replace(&mut self.current_object, identifier.clone());
+        let enclosing_variables = std::mem::take(&mut self.body_variables);
         self.advance();
 
-        let parents = self.parse_parenthesis(Token::Parenthesis('('), Token::Parenthesis(')'));
+        // class Box[T](Base): in python 3.12
+        let mut generics = Vec::new();
+        if self.current_file.file_type == "python" && self.current_token == Token::Bracket('[') {
+            for tokens in self.parse_list(Token::Bracket('['), Token::Bracket(']')) {
+                generics.push(tokens_to_string(&tokens));
+            }
+        }
 
-        let should_be_block_open = self.current_token.clone();
+        let (parents, mut keywords) = self.parse_parents();
+        let metaclass = keywords
+            .iter()
+            .position(|(name, _)| name == "metaclass")
+            .map(|position| keywords.remove(position).1);
+
+        let mut kind = ObjectKind::from_keyword(&keyword);
+        let mut is_abstract = is_abstract;
+        if self.current_file.file_type == "python" {
+            kind = python_object_kind(&decorators, &parents).unwrap_or(kind);
+            is_abstract = is_abstract || python_is_abstract(&parents, metaclass.as_deref());
+            generics.extend(python_generics(&parents));
+        }
 
         let mut block = Vec::new();
-        if should_be_block_open == Token::BlockOpen('{') && should_be_block_open == Token::Colon {
-            block = self.handle_block();
+        if self.current_token == Token::BlockOpen('{') || self.current_token == Token::Colon {
+            block = self.handle_block(start.0);
             // println!("block {:?}", block);
-        } else {
-            panic!("Panicked at line {:?}", self.current_line);
         }
 
-        let object = Object {
+        let mut object = Object {
+            public: public || self.default_publicity(&identifier),
             identifier,
-            block,
-            variables: HashMap::new(),
-            public,
-            parent: None,
-       };
+            kind,
+            is_abstract,
+            decorators,
+            attributes: Vec::new(),
+            generics,
+            where_clauses: Vec::new(),
+            metaclass,
+            keywords,
+            variables: Vec::new(),
+            functions: Vec::new(),
+            objects: Vec::new(),
+            parents,
+            interfaces: Vec::new(),
+            partial: false,
+            implementations: Vec::new(),
+            namespace: self.namespace(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        };
+
+        for node in block {
+            match node {
+                ASTNode::Variable(variable) => object.add_variable(variable),
+                ASTNode::Function(function) => object.functions.push(function),
+                ASTNode::Object(nested) => object.objects.push(nested),
+                _ => {},
+            }
+        }
+
+        for variable in std::mem::replace(&mut self.body_variables, enclosing_variables) {
+            object.add_variable(variable);
+        }
+        self.current_object = enclosing_object;
+
+        if self.current_file.file_type == "python" {
+            python_fields(&mut object);
+        }
 
         ASTNode::Object(object)
     }
 
-    fn handle_block(&mut self) -> Vec<ASTNode> {
+    // header_line is where the declaration owning the block starts, its signature may span several lines
+    fn handle_block(&mut self, header_line: i64) -> Vec<ASTNode> {
         match self.current_token {
             Token::BlockOpen(_) => {
+                let start = self.position();
                 self.advance(); // skip block open
                 let mut block = Vec::new();
 
-                while self.current_token != Token::BlockClose || self.current_token != Token::Eof {
-                    block.push(self.parse_token());
-                    self.advance();
+                while self.current_token != Token::BlockClose && self.current_token != Token::Eof {
+                    let node = self.parse_token();
+                    if node != ASTNode::None {
+                        block.push(node);
+                    }
+                }
+
+                if self.current_token == Token::Eof {
+                    self.diagnostic(Severity::Error, start, String::from("unclosed `{`"));
                 }
                 self.advance(); // skip }
 
@@ -429,39 +867,63 @@ impl Parser {
             },
 
             Token::Colon => {
+                let colon = self.position();
+                let line_no = self.current_line_no;
                 self.advance(); // skip block open :
                 let mut block = Vec::new();
 
-                while self.current_line.is_empty() {
+                // class Empty: pass
+                if self.current_line_no == line_no {
+                    let node = self.parse_token();
+                    if node != ASTNode::None {
+                        block.push(node);
+                    }
+                    if self.current_line_no == line_no {
+                        self.advance_line();
+                    }
+                    return block;
+                }
+
+                while self.is_comment_line() {
                     self.advance_line();
                 }
 
-                let starting_indent = match self.current_line[0].clone() {
-                    Token::Indent(i) => i,
-                    _ => panic!("Expected indent {:?} {:?}", self.current_token, self.current_line)
+                let starting_indent = match self.current_line.first() {
+                    Some(Token::Indent(i)) if *i > self.line_indent(header_line) => *i,
+                    _ => {
+                        self.diagnostic(Severity::Error, colon, String::from("expected an indented block after `:`"));
+                        return block
+                    },
                 };
 
-
                 loop {
-                    if self.current_line.is_empty() {
+                    if self.current_token == Token::Eof {
+                        break
+                    }
+
+                    if self.is_comment_line() {
                         self.advance_line();
                         continue
                     }
 
-                    let current_indent = match self.current_line[0].clone() {
+                    let current_indent = match self.current_line[0] {
                         Token::Indent(i) => i,
                         _ => break
                     };
 
-                    if current_indent < starting_indent || self.current_token == Token::Eof {
+                    if current_indent < starting_indent {
                         break
                     }
 
+                    // nested blocks leave us on the first line after them
+                    let line_no = self.current_line_no;
                     let node = self.parse_token();
                     if node != ASTNode::None {
                         block.push(node);
                     }
-                    self.advance_line();
+                    if self.current_line_no == line_no {
+                        self.advance_line();
+                    }
                 }
 
                 block
@@ -472,51 +934,279 @@ impl Parser {
         }
     }
 
+    // package a.b; in java, kotlin and go, the namespace of the rest of the file
+    fn handle_package(&mut self) -> ASTNode {
+        self.advance(); // skip package
+
+        let mut name = Vec::new();
+        while self.current_token.name().is_some() {
+            name.push(self.current_token.clone());
+            self.advance();
+            if self.current_token != Token::Period {
+                break
+            }
+            name.push(self.current_token.clone());
+            self.advance();
+        }
+        self.namespace = vec![tokens_to_string(&name)];
+
+        if self.current_token == Token::Semicolon {
+            self.advance();
+        }
+        ASTNode::None
+    }
+
+    // namespace A.B { ... } in c#, typescript and c++, or c#'s namespace A.B; which holds for the rest of the file
+    fn handle_namespace(&mut self) -> ASTNode {
+        let start = self.position();
+        self.advance(); // skip namespace / module
+
+        let mut name = Vec::new();
+        while !matches!(self.current_token, Token::BlockOpen(_) | Token::Semicolon | Token::Equals | Token::Eof) {
+            name.push(self.current_token.clone());
+            self.advance();
+        }
+
+        // c++'s namespace fs = std::filesystem; is an alias
+        if self.current_token == Token::Equals {
+            self.code_until(|token| *token == Token::Semicolon);
+            self.advance(); // skip ;
+            return ASTNode::None
+        }
+        self.namespace.push(tokens_to_string(&name));
+
+        if self.current_token == Token::Semicolon {
+            self.advance();
+            return ASTNode::None
+        }
+
+        let block = self.handle_block(start.0);
+        let name = self.namespace.pop().unwrap_or_default();
+
+        let mut namespace = self.blank_object(name, ObjectKind::Module, false, start);
+        for node in block {
+            match node {
+                ASTNode::Object(object) => namespace.objects.push(object),
+                ASTNode::Function(function) => namespace.functions.push(function),
+                _ => {},
+            }
+        }
+
+        ASTNode::Object(namespace)
+    }
+
     fn handle_public_object(&mut self, public: bool) -> ASTNode {
+        let start = self.position();
         self.advance(); // skip pub
-        self.handle_object(public)
+
+        if self.current_token == Token::Parenthesis('(') {
+            self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')); // pub(crate)
+        }
+
+        if self.current_file.file_type == "rust" {
+            self.skip_rust_qualifiers();
+        }
+
+        let mut node = match self.current_token {
+            Token::ObjectDeclaration(_) | Token::Trait => self.handle_object(public),
+            // the } belongs to the enclosing block
+            Token::BlockClose => ASTNode::None,
+            _ => self.parse_token(),
+        };
+
+        // spans start from the pub
+        let span = match &mut node {
+            ASTNode::Object(object) => Some(&mut object.span),
+            ASTNode::Function(function) => {
+                function.access = public.into();
+                Some(&mut function.span)
+            },
+            ASTNode::Variable(variable) => {
+                variable.access = public.into();
+                Some(&mut variable.span)
+            },
+            _ => None,
+        };
+        if let Some(span) = span {
+            span.start_line = start.0;
+            span.start_column = start.1;
+        }
+
+        node
+    }
+
+
+    // The current token is the first on its line
+    fn at_statement_start(&self) -> bool {
+        let first = match self.current_line.first() {
+            Some(Token::Indent(_)) => 1,
+            _ => 0,
+        };
+
+        self.pos == first
+    }
+
+    // NAME = value or NAME: type at the start of a line in a python module or class body
+    fn is_declared_variable(&self) -> bool {
+        if self.current_file.file_type != "python" || self.function_depth > 0 || !self.at_statement_start() {
+            return false
+        }
+
+        match self.current_line.get(self.pos as usize + 1) {
+            Some(Token::Equals) => true,
+            // else: and try: have nothing after the colon
+            Some(Token::Colon) => self.current_line.len() > self.pos as usize + 2,
+            _ => false,
+        }
+    }
+
+    // import a.b as c, d | from .a import (b as c, d) | from a import *
+    fn handle_import(&mut self) -> ASTNode {
+        let start = self.position();
+        let line_no = self.current_line_no;
+        let from = self.current_token == Token::Identifier(String::from("from"));
+        self.advance(); // skip import / from
+
+        let mut module = Vec::new();
+        if from {
+            while self.current_line_no == line_no && self.current_token != Token::Identifier(String::from("import")) && self.current_token != Token::Eof {
+                module.push(self.current_token.clone());
+                self.advance();
+            }
+            if self.current_token == Token::Identifier(String::from("import")) {
+                self.advance(); // skip import
+            }
+        }
+
+        // the rest of the statement, which may be bracketed over several lines
+        let items = if self.current_token == Token::Parenthesis('(') {
+            self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'))
+        } else {
+            let mut items = vec![Vec::new()];
+            while self.current_line_no == line_no && !matches!(self.current_token, Token::Comment(_) | Token::Semicolon | Token::Eof) {
+                match (&self.current_token, items.last_mut()) {
+                    (Token::Comma, _) => items.push(Vec::new()),
+                    (token, Some(item)) => item.push(token.clone()),
+                    (_, None) => {},
+                }
+                self.advance();
+            }
+            items
+        };
+
+        for item in items {
+            // name as alias
+            let (path, alias) = match item.iter().position(|token| *token == Token::Identifier(String::from("as"))) {
+                Some(position) => (tokens_to_string(&item[..position]), item.get(position + 1).map(|token| token.to_string())),
+                None => (tokens_to_string(&item), None),
+            };
+            if path.is_empty() {
+                continue;
+            }
+
+            let (module, name) = if from {
+                (tokens_to_string(&module), Some(path))
+            } else {
+                (path, None)
+            };
+
+            self.imports.push(Import {
+                module,
+                name,
+                alias,
+                namespace: self.namespace(),
+                filepath: self.current_file.filepath.clone(),
+                span: self.span_from(start),
+            });
+        }
+
+        if self.current_line_no == line_no {
+            self.advance_line();
+        }
+        ASTNode::None
+    }
+
+    // The name of an annotation or decorator, from just after its @, @Name or @a.b.Name, with the arguments
+    // after it skipped
+    fn annotation_name(&mut self) -> String {
+        let mut name = vec![self.current_token.clone()];
+        self.advance();
+        while self.current_token == Token::Period {
+            name.push(self.current_token.clone());
+            name.push(self.advance());
+            self.advance();
+        }
+        if self.current_token == Token::Parenthesis('(') {
+            self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+        }
+
+        tokens_to_string(&name)
     }
 
+    // @name or @name(arguments) on the line before a python def or class
+    fn handle_decorator(&mut self) -> ASTNode {
+        let line_no = self.current_line_no;
+        self.advance(); // skip @
+
+        let mut name = Vec::new();
+        while self.current_line_no == line_no
+            && !matches!(self.current_token, Token::Parenthesis('(') | Token::Comment(_) | Token::Eof) {
+            name.push(self.current_token.clone());
+            self.advance();
+        }
+
+        if self.current_token == Token::Parenthesis('(') {
+            self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+        }
+        self.decorators.push(tokens_to_string(&name));
+
+        if self.current_line_no == line_no {
+            self.advance_line();
+        }
+        ASTNode::None
+    }
 
     fn handle_variable_declaration(&mut self) -> ASTNode {
        match self.current_file.file_type.as_str() {
            "python" => {
-                self.expect(Token::Period); // self -> .
+                let start = self.position();
+
+                // x = 0 directly in a class body is shared by every instance
+                if let Token::Identifier(identifier) = self.current_token.clone() {
+                    let kind = if self.current_object.is_empty() {
+                        VariableKind::Field
+                    } else {
+                        VariableKind::ClassAttribute
+                    };
+
+                    self.advance(); // move to : or =
+                    return self.python_assignment(identifier, kind, start)
+                }
+
+                // self.method(), return self, etc
+                if self.peek() != Token::Period {
+                    self.advance();
+                    return ASTNode::None
+                }
+
+                self.advance(); // self -> .
                 let identifier = match self.advance() {
                     Token::Identifier(id) => id,
-                    _ => panic!("Expected identifier"),
+                    _ => return ASTNode::None,
                 };
                 self.advance(); // move to : or =
 
-                if self.current_token == Token::Equals { // self -> . -> identifier -> = -> value
-                    let value = self.advance_to_eol(); // move onto value
-
-                    return ASTNode::Variable(Variable {
-                        identifier,
-                        value: Some(value),
-                        vtype: None,
-                        parent: self.current_object.clone(),
-                    })
-                } else if self.current_token == Token::Colon {
-                    let vtype = self.advance_up_to(Token::Equals);
-                    let value = self.advance_to_eol();
-
-                    return ASTNode::Variable(Variable {
-                        identifier,
-                        value: Some(value),
-                        vtype: Some(vtype),
-                        parent: self.current_object.clone(),
-                    })
-
-                } else {
-                    panic!("Error with python variable ObjectDeclaration")
-                }
+                self.python_assignment(identifier, VariableKind::Field, start)
            },
 
            "rust" => {
+                let start = self.position();
                 let identifier = match &self.current_token {
                     Token::Identifier(id) => id.to_string(),
-                    _ => panic!("Expected identifier, not {:?}", self.current_token)
+                    _ => {
+                        self.advance();
+                        return ASTNode::None
+                    }
                 };
                 self.advance(); // Identifier -> :
 
@@ -525,69 +1215,499 @@ impl Parser {
                 let parent = self.current_object.clone();
                 let variable = Variable {
                     identifier,
+                    kind: VariableKind::Field,
+                    readonly: false,
                     value: None,
                     vtype: Some(vtype),
-                    parent
-
+                    parent,
+                    access: Access::Private,
+                    filepath: self.current_file.filepath.clone(),
+                    span: self.span_from(start),
                 };
 
                 ASTNode::Variable(variable)
            }
 
-           _ => panic!("File type not currently supported")
+           _ => {
+                self.advance();
+                ASTNode::None
+           }
        }
 
     }
 
-    fn handle_function_declaration(&mut self) -> ASTNode {
-        let identifier = match self.advance() {
-            Token::Identifier(id) => id,
-            _ => panic!("Expected identifier")
-        }; // skip def| fn | etc  -> Identifier
+    // (: type)? = value, up to the end of the line
+    fn python_assignment(&mut self, identifier: String, kind: VariableKind, start: (i64, usize)) -> ASTNode {
+        if self.current_token != Token::Equals && self.current_token != Token::Colon {
+            return ASTNode::None
+        }
 
+        let mut tokens = self.advance_to_eol();
+        let value = tokens
+            .iter()
+            .position(|token| *token == Token::Equals)
+            .map(|equals| tokens.split_off(equals + 1));
+        tokens.pop_if(|token| *token == Token::Equals);
 
-        self.expect(Token::Parenthesis('('));
-        let parameters = self.parse_parenthesis(Token::Parenthesis('('), Token::Parenthesis(')'));
+        let vtype = if tokens.first() == Some(&Token::Colon) {
+            Some(tokens.split_off(1))
+        } else {
+            None
+        };
 
-        let mut ftype = Vec::new();
-        match &self.current_token {
-            Token::Arrow => { // class Parent(child) -> Type:
-                self.advance(); // skip ->
-                ftype = self.advance_to_eol();
-                ftype.pop(); // remove block open : | {
-                self.handle_block();
-            },
+        ASTNode::Variable(Variable {
+            access: self.default_publicity(&identifier).into(),
+            identifier,
+            kind,
+            readonly: false,
+            value,
+            vtype,
+            parent: self.current_object.clone(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        })
+    }
+
+    // Collects a return type up to the function body, eg -> Result<(), String> { | -> Dict[str, int]:
+    fn parse_return_type(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
 
-            Token::Identifier(id) => { //
+        loop {
+            match self.current_token {
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::AngleBracket('>') => depth -= 1,
 
+                Token::Colon | Token::BlockOpen(_) | Token::Semicolon if depth == 0 => break,
+                Token::Identifier(ref id) if depth == 0 && id == "where" => break,
 
-            },
+                Token::Eof => break,
+                Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                _ => {},
+            }
 
-            Token::Other(o) => {
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
 
-            },
+        tokens
+    }
 
-            Token::BlockOpen('{') | Token::Colon => {
-                self.handle_block();
+    fn handle_function_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+        let decorators = std::mem::take(&mut self.decorators);
+        let attributes = std::mem::take(&mut self.attributes);
+
+        // skip def| fn | etc  -> Identifier
+        let identifier = match self.advance() {
+            Token::Identifier(id) => id,
+            // fn(i32) -> i32 is a type in rust
+            token if self.current_file.file_type == "python" => {
+                self.token_diagnostic(Severity::Error, format!("expected a function name after `def`, found `{}`", token));
+                return self.recover()
             },
+            _ => return ASTNode::None,
+        };
 
-            _ => panic!("Unexpected token in function declaration {:?}", self.current_token)
+        self.advance();
+        if self.current_token == Token::AngleBracket('<') {
+            self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>')); // fn name<T>(
         }
 
-        let mut ftype_strings = Vec::new();
-        for token in ftype {
-            ftype_strings.push(token.to_string());
+        if self.current_token != Token::Parenthesis('(') {
+            return ASTNode::None
+        }
+        let parameters = self.parse_parenthesis(Token::Parenthesis('('), Token::Parenthesis(')'));
+        // fn name(&self) /* note */ -> T
+        if self.current_file.file_type == "rust" {
+            self.skip_layout();
         }
 
-        let return_type: String = ftype_strings.join("");
+        let mut ftype = Vec::new();
+        if self.current_token == Token::Arrow { // def name(params) -> Type:
+            self.advance(); // skip ->
+            ftype = self.parse_return_type();
+        }
+
+        if self.current_token == Token::Identifier(String::from("where")) { // fn name() where T: Trait {
+            while !matches!(self.current_token, Token::BlockOpen(_) | Token::Semicolon | Token::Eof) {
+                self.advance();
+            }
+        }
+
+        let has_body = self.current_token == Token::BlockOpen('{') || self.current_token == Token::Colon;
+        if has_body {
+            self.function_depth += 1;
+            for node in self.handle_block(start.0) {
+                if let ASTNode::Variable(variable) = node
+                    && !variable.parent.is_empty() {
+                    self.body_variables.push(variable);
+                }
+            }
+            self.function_depth -= 1;
+            self.attributes.clear(); // any left were on statements in the body
+        }
+
+        // @property def name(self) -> T: and @name.setter def name(self, value: T):
+        let getter = decorators.iter().any(|decorator| matches!(base_name(decorator), "property" | "cached_property"));
+        let setter = decorators.iter().any(|decorator| *decorator == format!("{}.setter", identifier));
+        if getter || setter {
+            return ASTNode::Variable(Variable {
+                access: self.default_publicity(&identifier).into(),
+                identifier,
+                kind: VariableKind::Property,
+                readonly: !setter,
+                value: None,
+                vtype: if ftype.is_empty() { None } else { Some(ftype) },
+                parent: self.current_object.clone(),
+                filepath: self.current_file.filepath.clone(),
+                span: self.span_from(start),
+            })
+        }
+
+        let return_type = tokens_to_string(&ftype);
+        // rust methods without a body are required by their trait, those without self are associated functions
+        let rust_method = self.current_file.file_type == "rust" && !self.current_object.is_empty();
+        let has_receiver = parameters.first().is_some_and(|parameter| parameter.identifier == "self");
 
         let function = Function {
+            access: self.default_publicity(&identifier).into(),
+            is_abstract: decorators.iter().any(|decorator| base_name(decorator) == "abstractmethod") || (rust_method && !has_body),
+            is_static: decorators.iter().any(|decorator| decorator == "staticmethod") || (rust_method && !has_receiver),
+            is_class_method: decorators.iter().any(|decorator| decorator == "classmethod"),
+            decorators,
+            attributes,
+            throws: Vec::new(),
             identifier,
             parameters,
             return_type,
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
         };
 
         ASTNode::Function(function)
 
     }
 }
+
+// The items of a namespace, mod name { } in rust or namespace Name { } in c#, go in the model along with
+// the rest of the file's. Their namespace says which one they're in
+fn add_module_items(module: Object, model: &mut ParsedModel) {
+    for object in module.objects {
+        if object.kind == ObjectKind::Module {
+            add_module_items(object, model);
+        } else {
+            model.objects.push(object);
+        }
+    }
+
+    model.functions.extend(module.functions);
+    model.variables.extend(module.variables);
+}
+
+// Splits tokens on a separator outside of brackets, Display + Fn(A) -> B on + -> ["Display", "Fn(A)->B"]
+fn split_top_level(tokens: &[Token], separator: &Token) -> Vec<String> {
+    split_entries(tokens, separator, true)
+        .iter()
+        .map(|entry| tokens_to_string(entry))
+        .collect()
+}
+
+// Splits tokens on a separator outside of brackets, leaving out empty entries. Types count < and > as
+// brackets when angles is set
+fn split_entries(tokens: &[Token], separator: &Token, angles: bool) -> Vec<Vec<Token>> {
+    let mut entries = vec![Vec::new()];
+    let mut depth = 0;
+
+    for token in tokens {
+        match token {
+            Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_) => depth += 1,
+            Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose => depth -= 1,
+            Token::AngleBracket('<') if angles => depth += 1,
+            Token::AngleBracket('>') if angles => depth -= 1,
+            token if token == separator && depth == 0 => {
+                entries.push(Vec::new());
+                continue;
+            },
+            _ => {},
+        }
+
+        if let Some(entry) = entries.last_mut() {
+            entry.push(token.clone());
+        }
+    }
+
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+// Whether an expression or type carries on onto the next line, because of how that line starts or how
+// the last one ends, eg with a . or an operator, as in kotlin, swift and typescript
+fn line_continues(tokens: &[Token], next: &Token) -> bool {
+    let ends = matches!(tokens.last(), Some(Token::Other(_) | Token::Equals | Token::Comma | Token::Colon | Token::Period | Token::Arrow | Token::AngleBracket('<')))
+        // typescript's =>
+        || tokens.ends_with(&[Token::Equals, Token::AngleBracket('>')]);
+    let starts = matches!(next, Token::Period | Token::Other('?' | '|' | '&' | '+' | '-' | '*' | '/' | '%'));
+
+    ends || starts
+}
+
+// Dataclasses and typing's special bases, from the decorators and parents of a python class
+fn python_object_kind(decorators: &[String], parents: &[String]) -> Option<ObjectKind> {
+    if decorators.iter().any(|decorator| matches!(base_name(decorator), "dataclass" | "define" | "frozen")) {
+        return Some(ObjectKind::Dataclass)
+    }
+
+    parents.iter().find_map(|parent| match base_name(parent) {
+        "NamedTuple" => Some(ObjectKind::NamedTuple),
+        "TypedDict" => Some(ObjectKind::TypedDict),
+        "Protocol" => Some(ObjectKind::Interface),
+        "Enum" | "IntEnum" | "StrEnum" | "Flag" | "IntFlag" => Some(ObjectKind::Enum),
+        _ => None,
+    })
+}
+
+// class Shape(ABC) or class Shape(metaclass=ABCMeta)
+fn python_is_abstract(parents: &[String], metaclass: Option<&str>) -> bool {
+    parents.iter().any(|parent| base_name(parent) == "ABC")
+        || metaclass.is_some_and(|metaclass| base_name(metaclass) == "ABCMeta")
+}
+
+// The type parameters a class declares through Generic[K, V] or Protocol[T]
+fn python_generics(parents: &[String]) -> Vec<String> {
+    let mut generics = Vec::new();
+
+    for parent in parents {
+        if !matches!(base_name(parent), "Generic" | "Protocol") {
+            continue;
+        }
+
+        let (open, close) = match (parent.find('['), parent.rfind(']')) {
+            (Some(open), Some(close)) if open < close => (open, close),
+            _ => continue,
+        };
+
+        // split on commas that aren't nested, Generic[K, Dict[K, V]]
+        let mut depth = 0;
+        let mut argument = String::new();
+        for c in parent[open + 1..close].chars() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    generics.push(argument.trim().to_string());
+                    argument.clear();
+                    continue;
+                },
+                _ => {},
+            }
+            argument.push(c);
+        }
+        if !argument.trim().is_empty() {
+            generics.push(argument.trim().to_string());
+        }
+    }
+
+    generics
+}
+
+// Annotated class attributes of dataclasses and the like are fields, as are the names in __slots__, and
+// those an enum assigns are its members
+fn python_fields(object: &mut Object) {
+    if object.kind.has_declared_fields() {
+        for variable in &mut object.variables {
+            let class_var = variable.type_string().is_some_and(|vtype| base_name(&vtype) == "ClassVar");
+            if variable.kind == VariableKind::ClassAttribute && variable.vtype.is_some() && !class_var {
+                variable.kind = VariableKind::Field;
+            }
+        }
+    }
+
+    // the members of an enum are the class attributes it assigns, RED = 1, other than _ignore_ and the like
+    if object.kind == ObjectKind::Enum {
+        for variable in &mut object.variables {
+            if variable.kind == VariableKind::ClassAttribute && variable.value.is_some() && !variable.identifier.starts_with('_') {
+                variable.kind = VariableKind::Variant;
+            }
+        }
+    }
+
+    // __slots__ = ("x", "y")
+    let slots = match object.variables.iter().position(|variable| variable.identifier == "__slots__") {
+        Some(position) => object.variables.remove(position),
+        None => return,
+    };
+
+    for token in slots.value.iter().flatten() {
+        if let Token::StringLiteral(identifier) = token {
+            object.add_variable(Variable {
+                identifier: identifier.clone(),
+                kind: VariableKind::Field,
+                readonly: false,
+                value: None,
+                vtype: None,
+                parent: object.identifier.clone(),
+                access: (!identifier.starts_with('_')).into(),
+                filepath: slots.filepath.clone(),
+                span: slots.span,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::{Object, ObjectKind, Span, VariableKind};
+    use crate::testing::parse;
+
+    fn lines_and_columns(span: Span) -> ((i64, usize), (i64, usize)) {
+        ((span.start_line, span.start_column), (span.end_line, span.end_column))
+    }
+
+    fn kinds(object: &Object) -> Vec<(&str, VariableKind)> {
+        object.variables.iter().map(|variable| (variable.identifier.as_str(), variable.kind)).collect()
+    }
+
+    #[test]
+    fn spans_run_from_the_first_to_the_last_column_of_a_declaration() {
+        let model = parse(&[("shapes.py", "# é\nclass Shape(Base):\n    name: str = \"é\"\n\n    def area(self) -> int:\n        return 0\n")]);
+        let shape = &model.objects[0];
+
+        assert_eq!(lines_and_columns(shape.span), ((2, 1), (6, 17)));
+        assert_eq!(lines_and_columns(shape.variables[0].span), ((3, 5), (3, 20)));
+        assert_eq!(lines_and_columns(shape.functions[0].span), ((5, 5), (6, 17)));
+    }
+
+    #[test]
+    fn an_unfinished_from_import_ends_at_the_end_of_the_file() {
+        let model = parse(&[("shapes.py", "class Shape:\n    pass\n\nfrom abc")]);
+
+        assert_eq!(model.objects.len(), 1);
+        assert_eq!(model.imports.len(), 0);
+    }
+
+    #[test]
+    fn class_attributes_properties_and_dataclass_fields() {
+        let model = parse(&[("shapes.py", "\
+@dataclass(frozen=True)
+class Point:
+    x: int = 0
+    y: int = 0
+
+class Shape:
+    __slots__ = ('name', 'colour')
+    sides = 0
+
+    def __init__(self):
+        self.name = 'shape'
+
+    @property
+    def area(self) -> float:
+        return 0.0
+
+    @property
+    def label(self):
+        return self.name
+
+    @label.setter
+    def label(self, value):
+        self.name = value
+
+    @staticmethod
+    def create():
+        return Shape()
+
+    @classmethod
+    def named(cls, name):
+        return cls()
+
+class Pair(NamedTuple):
+    first: int
+    second: int
+")]);
+        let point = &model.objects[0];
+        assert_eq!(point.kind, ObjectKind::Dataclass);
+        assert_eq!(point.decorators, vec!["dataclass"]);
+        assert_eq!(kinds(point), vec![("x", VariableKind::Field), ("y", VariableKind::Field)]);
+
+        let shape = &model.objects[1];
+        assert_eq!(shape.kind, ObjectKind::Class);
+        let mut shape_kinds = kinds(shape);
+        shape_kinds.sort_by_key(|(identifier, _)| *identifier);
+        assert_eq!(shape_kinds, vec![
+            ("area", VariableKind::Property),
+            ("colour", VariableKind::Field),
+            ("label", VariableKind::Property),
+            ("name", VariableKind::Field),
+            ("sides", VariableKind::ClassAttribute),
+        ]);
+        let area = shape.variables.iter().find(|variable| variable.identifier == "area").unwrap();
+        let label = shape.variables.iter().find(|variable| variable.identifier == "label").unwrap();
+        assert_eq!((area.kind, area.readonly), (VariableKind::Property, true));
+        assert_eq!((label.kind, label.readonly), (VariableKind::Property, false));
+
+        let create = shape.functions.iter().find(|function| function.identifier == "create").unwrap();
+        let named = shape.functions.iter().find(|function| function.identifier == "named").unwrap();
+        assert!(create.is_static && !create.is_class_method);
+        assert!(named.is_class_method && !named.is_static);
+
+        let pair = &model.objects[2];
+        assert_eq!(pair.kind, ObjectKind::NamedTuple);
+        assert_eq!(kinds(pair), vec![("first", VariableKind::Field), ("second", VariableKind::Field)]);
+    }
+
+    #[test]
+    fn enum_members_are_variants() {
+        let model = parse(&[("colours.py", "\
+class Colour(Enum):
+    _ignore_ = ['scratch']
+    RED = 1
+    GREEN = auto()
+    default: ClassVar[str]
+
+    @property
+    def hex(self) -> str:
+        return ''
+")]);
+
+        assert_eq!(kinds(&model.objects[0]), vec![
+            ("_ignore_", VariableKind::ClassAttribute),
+            ("RED", VariableKind::Variant),
+            ("GREEN", VariableKind::Variant),
+            ("default", VariableKind::ClassAttribute),
+            ("hex", VariableKind::Property),
+        ]);
+    }
+
+    #[test]
+    fn base_lists_are_split_into_bases_metaclass_keywords_and_generics() {
+        let model = parse(&[("shapes.py", "\
+class Shape(Base, Generic[K, V], metaclass=ABCMeta):
+    pass
+
+class Options(TypedDict, total=False):
+    pass
+
+class Drawable(Protocol[T]):
+    pass
+")]);
+
+        let shape = &model.objects[0];
+        assert_eq!(shape.parents, vec!["Base", "Generic[K, V]"]);
+        assert_eq!(shape.metaclass.as_deref(), Some("ABCMeta"));
+        assert_eq!(shape.generics, vec!["K", "V"]);
+        assert!(shape.is_abstract);
+
+        let options = &model.objects[1];
+        assert_eq!(options.parents, vec!["TypedDict"]);
+        assert_eq!(options.keywords, vec![("total".to_string(), "False".to_string())]);
+        assert_eq!(options.metaclass, None);
+
+        let drawable = &model.objects[2];
+        assert_eq!(drawable.kind, ObjectKind::Interface);
+        assert_eq!(drawable.generics, vec!["T"]);
+    }
+}
@@ -0,0 +1,818 @@
+use super::{Modifiers, Parser};
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Object, ObjectKind, Severity, Token, VariableKind};
+
+// C++ classes, structs, unions and enums. Nested types are nested objects, and members defined outside
+// of their class are merged into it by the resolver:
+//
+// namespace geo {                                      the namespace of everything inside it
+// template <typename T> class Square : public Shape    Square<T>, with the base Shape
+// public:                                              the members below are public
+// virtual double area() const = 0;                     a pure virtual method, Square is abstract
+// double Square::area() const { ... }                  area, defined in square.cpp for the Square in square.h
+impl Parser {
+    // A namespace, a type or something defined at namespace scope. Anything else, eg using directives, is skipped
+    pub(super) fn handle_cpp_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+
+        // using namespace std; using Id = int;
+        if matches!(&self.current_token, Token::Identifier(id) if id == "using" || id == "static_assert") {
+            self.cpp_skip_statement();
+            return ASTNode::None
+        }
+
+        let modifiers = self.cpp_modifiers();
+        // inline namespace v1 { }
+        if self.current_token == Token::Identifier(String::from("namespace")) {
+            return self.handle_namespace()
+        }
+        if self.is_cpp_type() {
+            return self.cpp_type(modifiers, true, start)
+        }
+
+        match self.current_token {
+            Token::Identifier(_) | Token::Type(..) | Token::Connect => self.cpp_definition(modifiers, start),
+            _ => {
+                if self.position() == start {
+                    self.advance();
+                }
+                ASTNode::None
+            },
+        }
+    }
+
+    // class, struct, union or enum
+    fn is_cpp_type(&self) -> bool {
+        matches!(&self.current_token, Token::ObjectDeclaration(keyword) if matches!(keyword.as_str(), "class" | "struct" | "union" | "enum"))
+    }
+
+    // Attributes, templates and specifiers, eg template <typename T> [[nodiscard]] static constexpr
+    fn cpp_modifiers(&mut self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+
+        loop {
+            match self.current_token.clone() {
+                Token::Bracket('[') if self.peek() == Token::Bracket('[') => {
+                    self.advance(); // skip the outer [
+                    for tokens in self.parse_list(Token::Bracket('['), Token::Bracket(']')) {
+                        if !tokens.is_empty() {
+                            modifiers.attributes.push(tokens_to_string(&tokens));
+                        }
+                    }
+                },
+
+                Token::Identifier(id) => match id.as_str() {
+                    "template" => {
+                        self.advance(); // skip template
+                        modifiers.generics = self.cpp_template();
+                        continue;
+                    },
+                    "static" => modifiers.is_static = true,
+                    "constexpr" | "consteval" | "constinit" => modifiers.readonly = true,
+                    "typedef" => modifiers.typedef = true,
+                    "friend" => modifiers.friend = true,
+                    // extern "C"
+                    "extern" => {
+                        self.advance();
+                        if matches!(self.current_token, Token::StringLiteral(_)) {
+                            self.advance();
+                        }
+                        continue;
+                    },
+                    "virtual" | "inline" | "explicit" | "mutable" | "volatile" | "thread_local" | "register" => {},
+                    _ => break,
+                },
+
+                Token::Indent(_) | Token::Comment(_) => {},
+                _ => break,
+            }
+            self.advance();
+        }
+
+        modifiers
+    }
+
+    // <typename T, int N = 3, typename... Rest> -> ["T", "N", "Rest"]
+    fn cpp_template(&mut self) -> Vec<String> {
+        self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'))
+            .iter()
+            .filter_map(|tokens| {
+                let end = tokens.iter().position(|token| *token == Token::Equals).unwrap_or(tokens.len());
+                tokens[..end].iter().rev().find_map(|token| token.name())
+            })
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    // class Name final : public Base { ... }; and the like, the current token is the keyword
+    fn cpp_type(&mut self, modifiers: Modifiers, public: bool, start: (i64, usize)) -> ASTNode {
+        let keyword = self.current_token.to_string();
+        self.advance(); // skip class / struct / union / enum
+
+        // enum class Color
+        if keyword == "enum" && matches!(&self.current_token, Token::ObjectDeclaration(kind) if kind == "class" || kind == "struct") {
+            self.advance();
+        }
+
+        let mut attributes = modifiers.attributes;
+        let mut identifier = None;
+        loop {
+            match self.current_token.clone() {
+                Token::Bracket('[') if self.peek() == Token::Bracket('[') => {
+                    self.advance(); // skip the outer [
+                    attributes.extend(self.parse_list(Token::Bracket('['), Token::Bracket(']')).iter().map(|tokens| tokens_to_string(tokens)));
+                    self.advance(); // skip the outer ]
+                    continue;
+                },
+                // alignas(16)
+                Token::Identifier(id) if id == "alignas" => {
+                    self.advance();
+                    self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                    continue;
+                },
+                Token::Identifier(id) if id == "final" => {},
+                // a specialisation, class Box<int>
+                Token::AngleBracket('<') => {
+                    self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'));
+                    continue;
+                },
+                // class Outer::Inner { } defines a nested class declared in Outer
+                Token::Connect => {},
+                token => match token.name() {
+                    Some(name) => identifier = Some(name.to_string()),
+                    None => break,
+                },
+            }
+            self.advance();
+        }
+
+        self.skip_layout();
+        match self.current_token {
+            Token::BlockOpen(_) | Token::Colon => {},
+            // class Shape; is only a declaration, struct stat info; declares a variable
+            _ => {
+                self.cpp_skip_statement();
+                return ASTNode::None
+            },
+        }
+
+        let kind = ObjectKind::from_keyword(&keyword);
+        let mut object = self.blank_object(identifier.clone().unwrap_or_default(), kind, public, start);
+        object.attributes = attributes;
+        object.generics = modifiers.generics;
+
+        if self.current_token == Token::Colon {
+            self.advance(); // skip :
+            let clause = self.cpp_clause();
+
+            // enum Color : uint8_t gives the type of its values
+            if kind != ObjectKind::Enum {
+                for base in split_bases(&clause) {
+                    // bases are private unless they say otherwise, or public for a struct
+                    let mut access = if keyword == "class" { "private" } else { "public" };
+                    let mut virtual_base = false;
+                    let mut tokens = base.as_slice();
+                    while let Some((first, rest)) = tokens.split_first() {
+                        match first {
+                            Token::Publicity(true) => access = "public",
+                            Token::Publicity(false) => access = "private",
+                            Token::Identifier(id) if id == "protected" => access = "protected",
+                            Token::Identifier(id) if id == "virtual" => virtual_base = true,
+                            _ => break,
+                        }
+                        tokens = rest;
+                    }
+                    if tokens.is_empty() {
+                        continue;
+                    }
+
+                    let parent = tokens_to_string(tokens);
+                    if access != "public" {
+                        object.keywords.push((access.to_string(), parent.clone()));
+                    }
+                    if virtual_base {
+                        object.keywords.push((String::from("virtual"), parent.clone()));
+                    }
+                    object.parents.push(parent);
+                }
+            }
+        }
+
+        match self.current_token {
+            Token::BlockOpen(_) if kind == ObjectKind::Enum => {
+                for (tokens, span) in self.parse_spanned_list(Token::BlockOpen('{'), Token::BlockClose) {
+                    let identifier = match tokens.first().and_then(|token| token.name()) {
+                        Some(name) => name.to_string(),
+                        None => continue,
+                    };
+                    let value = tokens
+                        .iter()
+                        .position(|token| *token == Token::Equals)
+                        .map(|equals| tokens[equals + 1..].to_vec());
+
+                    object.variables.push(self.new_variable(identifier, VariableKind::Variant, public.into(), &[], value, &object.identifier, span));
+                }
+            },
+            // the members of a struct or union are public unless they say otherwise, those of a class private
+            Token::BlockOpen(_) => self.cpp_type_body(&mut object, if keyword == "class" { Access::Private } else { Access::Public }),
+            _ => {},
+        }
+
+        // typedef struct { ... } Point;
+        self.skip_layout();
+        if identifier.is_none() && modifiers.typedef
+            && let Some(name) = self.current_token.name() {
+            object.identifier = name.to_string();
+            identifier = Some(object.identifier.clone());
+        }
+        // struct Point { ... } origin; declares variables too
+        self.cpp_skip_statement();
+        object.span = self.span_from(start);
+
+        match identifier {
+            Some(_) => ASTNode::Object(object),
+            // union { int i; float f; };
+            None => ASTNode::None,
+        }
+    }
+
+    // Tokens up to the body of a class, eg its base list
+    fn cpp_clause(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            match self.current_token {
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::AngleBracket('>') => depth -= 1,
+
+                Token::BlockOpen(_) | Token::Semicolon if depth == 0 => break,
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                Token::BlockClose | Token::Eof => break,
+                _ => {},
+            }
+
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // The members between the braces of a class, struct or union, in public:, protected: and private: sections
+    fn cpp_type_body(&mut self, object: &mut Object, mut access: Access) {
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        self.advance(); // skip {
+
+        loop {
+            match self.current_token.clone() {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+
+                Token::Publicity(section) if self.peek() == Token::Colon => {
+                    access = section.into();
+                    self.advance();
+                    self.advance(); // skip :
+                },
+                Token::Identifier(id) if id == "protected" && self.peek() == Token::Colon => {
+                    access = Access::Protected;
+                    self.advance();
+                    self.advance(); // skip :
+                },
+
+                _ => {
+                    for node in self.cpp_member(access) {
+                        match node {
+                            ASTNode::Variable(variable) => object.variables.push(variable),
+                            ASTNode::Function(function) => {
+                                // a pure virtual method makes the class abstract
+                                object.is_abstract = object.is_abstract || function.is_abstract;
+                                object.functions.push(function);
+                            },
+                            ASTNode::Object(nested) => object.objects.push(nested),
+                            _ => {},
+                        }
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        self.current_object = enclosing_object;
+    }
+
+    // A field, method, constructor or nested type
+    fn cpp_member(&mut self, access: Access) -> Vec<ASTNode> {
+        let start = self.position();
+
+        // using Base::Base; typedef int Id;
+        if matches!(&self.current_token, Token::Identifier(id) if matches!(id.as_str(), "using" | "typedef" | "static_assert")) {
+            self.cpp_skip_statement();
+            return Vec::new()
+        }
+
+        let modifiers = self.cpp_modifiers();
+        if self.is_cpp_type() {
+            let nested = self.cpp_type(modifiers, access.is_public(), start);
+            return match nested {
+                ASTNode::Object(_) => vec![nested],
+                _ => Vec::new(),
+            }
+        }
+
+        let header = self.cpp_header();
+        if self.position() == start {
+            self.advance();
+            return Vec::new()
+        }
+
+        let members = match self.current_token {
+            // void (*callback)(int);
+            Token::Parenthesis('(') if matches!(self.peek(), Token::Other('*') | Token::Other('&')) => {
+                self.cpp_skip_statement();
+                Vec::new()
+            },
+            Token::Parenthesis('(') => self.cpp_method(&header, &modifiers, access, start).into_iter().collect(),
+            Token::Equals | Token::Comma | Token::Semicolon | Token::BlockOpen(_) | Token::Colon | Token::Bracket('[') => self.cpp_fields(&header, &modifiers, access, start),
+            _ => Vec::new(),
+        };
+
+        if modifiers.friend {
+            return Vec::new()
+        }
+        members
+    }
+
+    // The type and name of a declaration, up to the parameters of a function or the value of a variable
+    fn cpp_header(&mut self) -> Vec<Token> {
+        let mut header: Vec<Token> = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            match self.current_token {
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+
+                // operator==, operator() and operator bool have symbols and types for names
+                Token::Identifier(ref id) if depth == 0 && id == "operator" => {
+                    header.push(self.current_token.clone());
+                    self.advance();
+                    if self.current_token == Token::Parenthesis('(') {
+                        header.push(self.current_token.clone());
+                        header.push(self.advance());
+                        self.advance();
+                    }
+                    while !matches!(self.current_token, Token::Parenthesis('(') | Token::Semicolon | Token::BlockClose | Token::Eof) {
+                        header.push(self.current_token.clone());
+                        self.advance();
+                    }
+                    break
+                },
+
+                // the parameters follow a name or Name<T>
+                Token::Parenthesis('(') if depth == 0
+                    && header.last().is_some_and(|token| token.name().is_some() || *token == Token::AngleBracket('>')) => break,
+
+                Token::Parenthesis('(') | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::AngleBracket('>') if depth > 0 => depth -= 1,
+                Token::BlockOpen(_) | Token::Equals | Token::Comma | Token::Semicolon | Token::Colon | Token::Bracket('[') if depth == 0 => break,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose | Token::Eof => break,
+                _ => {},
+            }
+
+            header.push(self.current_token.clone());
+            self.advance();
+        }
+
+        header
+    }
+
+    // Type name(parameters) const override = 0; or { ... }, with the current token at the parameters
+    fn cpp_method(&mut self, header: &[Token], modifiers: &Modifiers, access: Access, start: (i64, usize)) -> Option<ASTNode> {
+        let operator = header.contains(&Token::Identifier(String::from("operator")));
+        let mut parameters = Vec::new();
+        for tokens in self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+            parameters.extend(self.parse_parameter(&tokens));
+        }
+        let (pure, _, trailing_return) = self.cpp_function_tail();
+
+        if operator {
+            return None
+        }
+        let (_, identifier, return_type) = cpp_name(header)?;
+
+        Some(ASTNode::Function(Function {
+            identifier,
+            parameters,
+            return_type: tokens_to_string(&trailing_return.unwrap_or(return_type)),
+            access,
+            is_abstract: pure,
+            is_static: modifiers.is_static,
+            is_class_method: false,
+            decorators: Vec::new(),
+            attributes: modifiers.attributes.clone(),
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        }))
+    }
+
+    // What follows a function's parameters: qualifiers, a trailing return type, a constructor's initialisers
+    // and its body or = 0. Returns whether it is pure virtual, whether it is defined and its trailing return type
+    fn cpp_function_tail(&mut self) -> (bool, bool, Option<Vec<Token>>) {
+        let mut pure = false;
+        let mut defined = false;
+        let mut trailing_return = None;
+
+        loop {
+            match self.current_token.clone() {
+                Token::Indent(_) | Token::Comment(_) | Token::Other('&') => {
+                    self.advance();
+                },
+                Token::Identifier(id) => match id.as_str() {
+                    "const" | "volatile" | "override" | "final" | "mutable" => {
+                        self.advance();
+                    },
+                    "noexcept" | "throw" => {
+                        self.advance();
+                        self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                    },
+                    // requires std::integral<T>
+                    "requires" => {
+                        self.code_until(|token| matches!(token, Token::BlockOpen(_) | Token::Semicolon));
+                    },
+                    _ => break,
+                },
+
+                // auto area() -> double
+                Token::Arrow => {
+                    self.advance(); // skip ->
+                    let mut tokens = Vec::new();
+                    let mut depth = 0;
+                    loop {
+                        match self.current_token {
+                            Token::AngleBracket('<') | Token::Parenthesis('(') => depth += 1,
+                            Token::AngleBracket('>') | Token::Parenthesis(')') => depth -= 1,
+                            Token::BlockOpen(_) | Token::Semicolon | Token::Equals | Token::BlockClose | Token::Eof if depth == 0 => break,
+                            Token::Identifier(ref id) if depth == 0 && matches!(id.as_str(), "override" | "final" | "requires") => break,
+                            Token::Indent(_) | Token::Comment(_) => {
+                                self.advance();
+                                continue;
+                            },
+                            _ => {},
+                        }
+                        tokens.push(self.current_token.clone());
+                        self.advance();
+                    }
+                    trailing_return = Some(tokens);
+                },
+
+                // = 0, = default or = delete
+                Token::Equals => {
+                    self.advance(); // skip =
+                    match self.current_token.clone() {
+                        Token::Number(number) if number == "0" => pure = true,
+                        Token::Identifier(id) if id == "default" => defined = true,
+                        _ => {},
+                    }
+                    self.cpp_skip_statement();
+                    break
+                },
+
+                // Shape::Shape(int x) : Base(x), size_{x} { }
+                Token::Colon => {
+                    self.advance(); // skip :
+                    loop {
+                        while !matches!(self.current_token, Token::Parenthesis('(') | Token::BlockOpen(_) | Token::Semicolon | Token::BlockClose | Token::Eof) {
+                            self.advance();
+                        }
+                        match self.current_token {
+                            Token::Parenthesis('(') => self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')),
+                            Token::BlockOpen(_) => self.parse_list(Token::BlockOpen('{'), Token::BlockClose),
+                            _ => break,
+                        };
+                        self.skip_layout();
+                        if self.current_token != Token::Comma {
+                            break
+                        }
+                        self.advance(); // skip ,
+                    }
+                },
+
+                Token::BlockOpen(_) => {
+                    self.code_block();
+                    defined = true;
+                    break
+                },
+                Token::Semicolon => {
+                    self.advance();
+                    break
+                },
+                _ => break,
+            }
+        }
+
+        (pure, defined, trailing_return)
+    }
+
+    // Type a = 1, *b, c[3]; with the current token just after the first name
+    fn cpp_fields(&mut self, header: &[Token], modifiers: &Modifiers, access: Access, start: (i64, usize)) -> Vec<ASTNode> {
+        let mut fields = Vec::new();
+        let (vtype, mut identifier) = match header.split_last() {
+            Some((name, vtype)) => (vtype, name.name().map(|name| name.to_string())),
+            None => (header, None),
+        };
+
+        loop {
+            // int values[10]
+            while self.current_token == Token::Bracket('[') {
+                self.parse_list(Token::Bracket('['), Token::Bracket(']'));
+            }
+
+            let mut value = None;
+            match self.current_token {
+                Token::Equals => {
+                    self.advance(); // skip =
+                    value = Some(self.code_until(|token| matches!(token, Token::Comma | Token::Semicolon)));
+                },
+                // std::vector<int> sizes{1, 2};
+                Token::BlockOpen(_) => {
+                    let items = self.parse_list(Token::BlockOpen('{'), Token::BlockClose);
+                    let mut tokens = vec![Token::BlockOpen('{')];
+                    tokens.extend(items.join(&Token::Comma));
+                    tokens.push(Token::BlockClose);
+                    value = Some(tokens);
+                },
+                // unsigned flags : 3;
+                Token::Colon => {
+                    self.advance(); // skip :
+                    self.code_until(|token| matches!(token, Token::Comma | Token::Semicolon));
+                },
+                _ => {},
+            }
+
+            if let Some(identifier) = identifier.take() {
+                let kind = if modifiers.is_static { VariableKind::ClassAttribute } else { VariableKind::Field };
+                let mut variable = self.new_variable(identifier, kind, access, vtype, value, &self.current_object, self.span_from(start));
+                variable.readonly = modifiers.readonly || is_const(vtype);
+                fields.push(ASTNode::Variable(variable));
+            }
+
+            if self.current_token != Token::Comma {
+                break
+            }
+            self.advance(); // skip ,
+            self.skip_layout();
+            while matches!(self.current_token, Token::Other('*') | Token::Other('&')) {
+                self.advance();
+            }
+            identifier = self.current_token.name().map(|name| name.to_string());
+            self.advance();
+        }
+
+        self.cpp_skip_statement();
+        fields
+    }
+
+    // A function or variable at namespace scope. Those of a class defined outside of it, void Shape::draw() { }
+    // or int Shape::count = 0;, are kept in definitions for the resolver to merge into the class, and free
+    // functions are returned
+    fn cpp_definition(&mut self, modifiers: Modifiers, start: (i64, usize)) -> ASTNode {
+        let header = self.cpp_header();
+        if self.position() == start {
+            self.advance();
+            return ASTNode::None
+        }
+        let operator = header.contains(&Token::Identifier(String::from("operator")));
+
+        let node = match self.current_token {
+            // int (*handler)(int);
+            Token::Parenthesis('(') if matches!(self.peek(), Token::Other('*') | Token::Other('&')) => {
+                self.cpp_skip_statement();
+                return ASTNode::None
+            },
+            Token::Parenthesis('(') => {
+                let mut parameters = Vec::new();
+                for tokens in self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+                    parameters.extend(self.parse_parameter(&tokens));
+                }
+                let (_, defined, trailing_return) = self.cpp_function_tail();
+
+                // declarations are only worth having in the class
+                if operator || !defined {
+                    return ASTNode::None
+                }
+                let Some((qualifier, identifier, return_type)) = cpp_name(&header) else {
+                    return ASTNode::None
+                };
+
+                let function = ASTNode::Function(Function {
+                    identifier,
+                    parameters,
+                    return_type: tokens_to_string(&trailing_return.unwrap_or(return_type)),
+                    access: Access::Public,
+                    is_abstract: false,
+                    is_static: modifiers.is_static,
+                    is_class_method: false,
+                    decorators: Vec::new(),
+                    attributes: modifiers.attributes,
+                    throws: Vec::new(),
+                    filepath: self.current_file.filepath.clone(),
+                    span: self.span_from(start),
+                });
+                // a free function
+                if qualifier.is_empty() {
+                    return function
+                }
+
+                (qualifier, function)
+            },
+
+            Token::Equals | Token::Semicolon | Token::BlockOpen(_) | Token::Bracket('[') => {
+                let Some((qualifier, identifier, vtype)) = cpp_name(&header) else {
+                    self.cpp_skip_statement();
+                    return ASTNode::None
+                };
+
+                while self.current_token == Token::Bracket('[') {
+                    self.parse_list(Token::Bracket('['), Token::Bracket(']'));
+                }
+                let value = match self.current_token {
+                    Token::Equals => {
+                        self.advance(); // skip =
+                        Some(self.code_until(|token| *token == Token::Semicolon))
+                    },
+                    _ => None,
+                };
+                self.cpp_skip_statement();
+                if qualifier.is_empty() {
+                    return ASTNode::None
+                }
+
+                let parent = qualifier.last().cloned().unwrap_or_default();
+                let mut variable = self.new_variable(identifier, VariableKind::ClassAttribute, Access::Public, &vtype, value, &parent, self.span_from(start));
+                variable.readonly = modifiers.readonly || is_const(&vtype);
+                (qualifier, ASTNode::Variable(variable))
+            },
+
+            _ => return ASTNode::None,
+        };
+
+        let (qualifier, member) = node;
+        self.add_definition(qualifier.join("::"), member, start);
+        ASTNode::None
+    }
+
+    // Up to and past the ; ending a declaration
+    fn cpp_skip_statement(&mut self) {
+        self.code_until(|token| *token == Token::Semicolon);
+        if self.current_token == Token::Semicolon {
+            self.advance();
+        }
+    }
+}
+
+// const int, but not const char*, whose characters are what's const
+fn is_const(vtype: &[Token]) -> bool {
+    (vtype.first() == Some(&Token::Identifier(String::from("const"))) || vtype.last() == Some(&Token::Identifier(String::from("const"))))
+        && !vtype.contains(&Token::Other('*'))
+}
+
+// Splits a base list on its top level commas, public Base<A, B>, virtual Other -> [[public, Base<A, B>], [virtual, Other]]
+fn split_bases(tokens: &[Token]) -> Vec<Vec<Token>> {
+    let mut bases = vec![Vec::new()];
+    let mut depth = 0;
+
+    for token in tokens {
+        match token {
+            Token::Parenthesis('(') | Token::AngleBracket('<') => depth += 1,
+            Token::Parenthesis(')') | Token::AngleBracket('>') => depth -= 1,
+            Token::Comma if depth == 0 => {
+                bases.push(Vec::new());
+                continue;
+            },
+            _ => {},
+        }
+
+        if let Some(base) = bases.last_mut() {
+            base.push(token.clone());
+        }
+    }
+
+    bases.retain(|base| !base.is_empty());
+    bases
+}
+
+// The class, name and type of a declaration: std::string geo::Shape::name -> ["geo", "Shape"], name and std::string,
+// Shape<T>::~Shape -> ["Shape"], ~Shape and nothing
+fn cpp_name(header: &[Token]) -> Option<(Vec<String>, String, Vec<Token>)> {
+    let (last, _) = header.split_last()?;
+    let mut name = last.name()?.to_string();
+    let mut end = header.len() - 1;
+
+    if end >= 1 && header[end - 1] == Token::Other('~') {
+        name = format!("~{}", name);
+        end -= 1;
+    }
+
+    let mut qualifier = Vec::new();
+    while end >= 2 && header[end - 1] == Token::Connect {
+        let mut segment = end - 2;
+
+        // Shape<T>::
+        if header[segment] == Token::AngleBracket('>') {
+            let mut depth = 0;
+            loop {
+                match header[segment] {
+                    Token::AngleBracket('>') => depth += 1,
+                    Token::AngleBracket('<') => depth -= 1,
+                    _ => {},
+                }
+                if depth == 0 || segment == 0 {
+                    break
+                }
+                segment -= 1;
+            }
+            if segment == 0 {
+                break
+            }
+            segment -= 1;
+        }
+
+        match header[segment].name() {
+            Some(class) => qualifier.insert(0, class.to_string()),
+            None => break,
+        }
+        end = segment;
+    }
+
+    Some((qualifier, name, header[..end].to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Access;
+    use crate::testing::{methods, node, parse, resolve};
+
+    const HEADER: &str = "namespace geo {
+class Shape {
+public:
+    virtual double area() const = 0;
+};
+
+class Square : public Shape {
+public:
+    double area() const override;
+protected:
+    double side_;
+};
+}
+";
+
+    const SOURCE: &str = "#include \"square.h\"
+
+namespace geo {
+double Square::area() const { return side_ * side_; }
+}
+";
+
+    #[test]
+    fn out_of_line_definitions_are_merged_into_their_class() {
+        let hierarchy = resolve(&[("geo/square.h", HEADER), ("geo/square.cpp", SOURCE)]);
+        let square = node(&hierarchy, "Square");
+
+        assert_eq!(hierarchy.nodes.len(), 2);
+        assert_eq!(methods(&hierarchy, "Square"), ["area"]);
+        assert_eq!(square.parents, [hierarchy.find("Shape").unwrap()]);
+        assert_eq!(square.object.variables[0].access, Access::Protected);
+    }
+
+    #[test]
+    fn pure_virtual_methods_make_a_class_abstract() {
+        let hierarchy = resolve(&[("geo/square.h", HEADER)]);
+
+        assert!(node(&hierarchy, "Shape").object.is_abstract);
+        assert!(!node(&hierarchy, "Square").object.is_abstract);
+    }
+
+    #[test]
+    fn free_functions_are_kept() {
+        let model = parse(&[("geo/main.cpp", "#include \"square.h\"\n\nstatic double total(const geo::Square& square);\n\nnamespace geo {\nint sides() { return 4; }\n}\n\nint main(int argc, char** argv) {\n    return 0;\n}\n")]);
+        let functions: Vec<&str> = model.functions.iter().map(|function| function.identifier.as_str()).collect();
+
+        assert_eq!(functions, ["sides", "main"]);
+        assert_eq!(model.functions[1].return_type, "int");
+        assert_eq!(model.functions[1].parameters.len(), 2);
+    }
+}
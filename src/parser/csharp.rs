@@ -0,0 +1,574 @@
+use super::{split_top_level, Modifiers, Parser};
+use crate::resolver::base_name;
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Object, ObjectKind, Severity, Token, VariableKind};
+
+// C# types. Classes, structs, interfaces, records and enums are objects, nested types are nested objects,
+// and the pieces of a partial class are merged by the resolver:
+//
+// namespace Shapes;                                    the namespace of everything below it
+// public partial class Square : Shape, IComparable     Square, with the base class Shape and the interface IComparable
+// public int Side { get; private set; }                a property of Square
+// public record Point(int X, int Y);                   Point, with the init only properties X and Y
+impl Parser {
+    // A namespace, or a type along with its modifiers. Anything else, eg using directives, is skipped
+    pub(super) fn handle_csharp_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+        let modifiers = self.csharp_modifiers();
+
+        // [assembly: ...] attributes come before the namespace
+        if self.current_token == Token::Identifier(String::from("namespace")) {
+            return self.handle_namespace()
+        }
+        if self.is_csharp_type() {
+            // types outside of a class are internal unless they say otherwise
+            let public = modifiers.access == Some(Access::Public);
+            return self.handle_csharp_type(modifiers, public, start)
+        }
+
+        if self.position() == start {
+            self.advance();
+        }
+        ASTNode::None
+    }
+
+    // class, struct, interface, enum or record followed by its name
+    fn is_csharp_type(&self) -> bool {
+        let keyword = matches!(&self.current_token, Token::ObjectDeclaration(keyword) if matches!(keyword.as_str(), "class" | "struct" | "interface" | "enum" | "record"));
+        keyword && self.peek().name().is_some()
+    }
+
+    // Attributes and modifiers, eg [Obsolete] public static readonly
+    fn csharp_modifiers(&mut self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+
+        loop {
+            match self.current_token.clone() {
+                Token::Bracket('[') => {
+                    for tokens in self.parse_list(Token::Bracket('['), Token::Bracket(']')) {
+                        match tokens.as_slice() {
+                            // [assembly: InternalsVisibleTo("Tests")] is about the whole assembly
+                            [Token::Identifier(target), Token::Colon, ..] if target == "assembly" || target == "module" => {},
+                            // [return: NotNull]
+                            [Token::Identifier(_), Token::Colon, attribute @ ..] => modifiers.attributes.push(tokens_to_string(attribute)),
+                            [] => {},
+                            attribute => modifiers.attributes.push(tokens_to_string(attribute)),
+                        }
+                    }
+                    continue;
+                },
+
+                Token::Publicity(public) if modifiers.access != Some(Access::Protected) => modifiers.access = Some(public.into()),
+                Token::Publicity(_) => {},
+                Token::Identifier(id) => match id.as_str() {
+                    // protected internal and private protected are drawn as protected
+                    "protected" => modifiers.access = Some(Access::Protected),
+                    "internal" if modifiers.access != Some(Access::Protected) => modifiers.access = Some(Access::Package),
+                    "internal" => {},
+                    "static" => modifiers.is_static = true,
+                    "abstract" => modifiers.is_abstract = true,
+                    "readonly" => modifiers.readonly = true,
+                    // constants belong to the type
+                    "const" => {
+                        modifiers.is_static = true;
+                        modifiers.readonly = true;
+                    },
+                    "partial" => modifiers.partial = true,
+                    "sealed" | "virtual" | "override" | "async" | "extern" | "unsafe" | "new" | "volatile" | "ref" | "file"
+                    | "required" | "event" | "fixed" => {},
+                    _ => break,
+                },
+
+                Token::Indent(_) | Token::Comment(_) => {},
+                _ => break,
+            }
+            self.advance();
+        }
+
+        modifiers
+    }
+
+    // class Name<T> : Base, IFoo where T : new() { ... } and the like, the current token is the keyword
+    fn handle_csharp_type(&mut self, modifiers: Modifiers, public: bool, start: (i64, usize)) -> ASTNode {
+        let mut keyword = self.current_token.to_string();
+        self.advance(); // skip class / struct / interface / enum / record
+
+        // record class and record struct
+        let record = keyword == "record";
+        if record && matches!(&self.current_token, Token::ObjectDeclaration(kind) if kind == "class" || kind == "struct") {
+            keyword = self.current_token.to_string();
+            self.advance();
+        }
+
+        let identifier = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => return ASTNode::None,
+        };
+        self.advance();
+
+        let kind = ObjectKind::from_keyword(&keyword);
+        let mut object = self.blank_object(identifier, kind, public, start);
+        object.is_abstract = modifiers.is_abstract;
+        object.attributes = modifiers.attributes;
+        object.partial = modifiers.partial;
+        object.generics = self.csharp_generics();
+
+        // the parameters of a record are init only properties
+        if record && self.current_token == Token::Parenthesis('(') {
+            for (tokens, span) in self.parse_spanned_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+                let tokens = csharp_parameter(&tokens);
+                let (declaration, value) = match tokens.iter().position(|token| *token == Token::Equals) {
+                    Some(equals) => (&tokens[..equals], Some(tokens[equals + 1..].to_vec())),
+                    None => (tokens.as_slice(), None),
+                };
+
+                if let Some((name, vtype)) = declaration.split_last()
+                    && let Some(name) = name.name() {
+                    let mut variable = self.new_variable(name.to_string(), VariableKind::Property, Access::Public, vtype, value, &object.identifier, span);
+                    variable.readonly = true;
+                    object.variables.push(variable);
+                }
+            }
+        }
+
+        self.skip_layout();
+        if self.current_token == Token::Colon {
+            self.advance(); // skip :
+            let bases = split_top_level(&self.csharp_clause(), &Token::Comma);
+
+            // enum Flags : byte gives the type of its values
+            if kind != ObjectKind::Enum {
+                // record Child(string Name) : Base(Name) passes arguments to the base's constructor
+                let bases: Vec<String> = bases
+                    .into_iter()
+                    .map(|base| match base.find('(') {
+                        Some(open) => base[..open].to_string(),
+                        None => base,
+                    })
+                    .collect();
+
+                object.interfaces = csharp_interfaces(kind, &bases);
+                object.parents = bases;
+            }
+        }
+        object.where_clauses = self.csharp_constraints();
+
+        match self.current_token {
+            Token::BlockOpen(_) if kind == ObjectKind::Enum => {
+                for (tokens, span) in self.parse_spanned_list(Token::BlockOpen('{'), Token::BlockClose) {
+                    let tokens = csharp_parameter(&tokens);
+                    let identifier = match tokens.first().and_then(|token| token.name()) {
+                        Some(name) => name.to_string(),
+                        None => continue,
+                    };
+                    let value = tokens
+                        .iter()
+                        .position(|token| *token == Token::Equals)
+                        .map(|equals| tokens[equals + 1..].to_vec());
+
+                    object.variables.push(self.new_variable(identifier, VariableKind::Variant, public.into(), &[], value, &object.identifier, span));
+                }
+            },
+            Token::BlockOpen(_) => self.csharp_type_body(&mut object),
+            // record Point(int X, int Y);
+            Token::Semicolon => {
+                self.advance();
+            },
+            _ => {},
+        }
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // <in T, out U> -> ["T", "U"]
+    fn csharp_generics(&mut self) -> Vec<String> {
+        self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'))
+            .iter()
+            .filter_map(|tokens| tokens.iter().rev().find_map(|token| token.name()))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    // Tokens up to the body, a where clause or => of a declaration, eg its base list
+    fn csharp_clause(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            match self.current_token {
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::AngleBracket('>') => depth -= 1,
+
+                Token::BlockOpen(_) | Token::Semicolon | Token::Equals if depth == 0 => break,
+                Token::Identifier(ref id) if depth == 0 && id == "where" => break,
+
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                Token::Eof => break,
+                _ => {},
+            }
+
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // where T : class, new() where U : IComparable<U> -> ["T: class, new()", "U: IComparable<U>"]
+    fn csharp_constraints(&mut self) -> Vec<String> {
+        let mut constraints = Vec::new();
+
+        self.skip_layout();
+        while self.current_token == Token::Identifier(String::from("where")) {
+            self.advance(); // skip where
+            let clause = self.csharp_clause();
+            if let Some(colon) = clause.iter().position(|token| *token == Token::Colon) {
+                constraints.push(format!("{}: {}", tokens_to_string(&clause[..colon]), split_top_level(&clause[colon + 1..], &Token::Comma).join(", ")));
+            }
+        }
+
+        constraints
+    }
+
+    // The members between the braces of a class, struct, interface or record
+    fn csharp_type_body(&mut self, object: &mut Object) {
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        self.advance(); // skip {
+
+        loop {
+            match self.current_token {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+
+                _ => {
+                    for node in self.csharp_member(object.kind) {
+                        match node {
+                            ASTNode::Variable(variable) => object.variables.push(variable),
+                            ASTNode::Function(function) => object.functions.push(function),
+                            ASTNode::Object(nested) => object.objects.push(nested),
+                            _ => {},
+                        }
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        self.current_object = enclosing_object;
+    }
+
+    // A field, property, method, constructor or nested type
+    fn csharp_member(&mut self, kind: ObjectKind) -> Vec<ASTNode> {
+        let start = self.position();
+        let modifiers = self.csharp_modifiers();
+        // the members of an interface are public unless they say otherwise, those of classes and structs private
+        let access = modifiers.access.unwrap_or(if kind == ObjectKind::Interface { Access::Public } else { Access::Private });
+
+        if self.is_csharp_type() {
+            return vec![self.handle_csharp_type(modifiers, access.is_public(), start)]
+        }
+
+        // delegate void Handler(object sender);
+        if self.current_token == Token::Identifier(String::from("delegate")) {
+            self.code_until(|token| *token == Token::Semicolon);
+            self.advance(); // skip ;
+            return Vec::new()
+        }
+
+        // the type and name, up to the parameters of a method, the accessors of a property or the value of a field
+        let mut header: Vec<Token> = Vec::new();
+        let mut depth = 0;
+        loop {
+            match self.current_token {
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                // the parameters follow a name, or Name<T>, otherwise it's a tuple type, (int, string) Pair()
+                Token::Parenthesis('(') if depth == 0
+                    && (header.last().is_some_and(|token| token.name().is_some() || *token == Token::AngleBracket('>'))
+                        || header.contains(&Token::Identifier(String::from("operator")))) => break,
+
+                Token::Parenthesis('(') | Token::AngleBracket('<') | Token::Bracket('[') => depth += 1,
+                Token::Parenthesis(')') | Token::AngleBracket('>') | Token::Bracket(']') => depth -= 1,
+                Token::BlockOpen(_) | Token::Equals | Token::Comma | Token::Semicolon if depth == 0 => break,
+                Token::BlockClose | Token::Eof => break,
+                _ => {},
+            }
+
+            header.push(self.current_token.clone());
+            self.advance();
+        }
+
+        // this[int index] { get; }
+        let indexer = header.windows(2).any(|pair| pair[0] == Token::Identifier(String::from("this")) && pair[1] == Token::Bracket('['));
+
+        match self.current_token {
+            Token::Parenthesis('(') => self.csharp_method(&header, modifiers, access, kind, start).into_iter().collect(),
+
+            Token::BlockOpen(_) if indexer => {
+                self.code_block();
+                Vec::new()
+            },
+            Token::BlockOpen(_) => self.csharp_property(&header, modifiers, access, start).into_iter().collect(),
+            // public int Area => Width * Height;
+            Token::Equals if self.peek() == Token::AngleBracket('>') => self.csharp_property(&header, modifiers, access, start).into_iter().collect(),
+
+            Token::Equals | Token::Comma | Token::Semicolon => self.csharp_fields(&header, &modifiers, access, start),
+            _ => Vec::new(),
+        }
+    }
+
+    // Type Name(parameters) where T : new() { ... } | => expression; | ;
+    fn csharp_method(&mut self, header: &[Token], modifiers: Modifiers, access: Access, kind: ObjectKind, start: (i64, usize)) -> Option<ASTNode> {
+        // Name<T>( -> Name
+        let mut header = header.to_vec();
+        if header.last() == Some(&Token::AngleBracket('>')) {
+            let mut depth = 0;
+            while let Some(token) = header.pop() {
+                match token {
+                    Token::AngleBracket('>') => depth += 1,
+                    Token::AngleBracket('<') => depth -= 1,
+                    _ => {},
+                }
+                if depth == 0 {
+                    break
+                }
+            }
+        }
+        let operator = header.contains(&Token::Identifier(String::from("operator")));
+
+        let mut parameters = Vec::new();
+        for tokens in self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+            parameters.extend(self.parse_parameter(&csharp_parameter(&tokens)));
+        }
+
+        // Child(int x) : base(x) calls another constructor first
+        self.skip_layout();
+        if self.current_token == Token::Colon {
+            self.advance(); // skip :
+            self.advance(); // skip base / this
+            self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+        }
+        self.csharp_constraints();
+
+        let mut has_body = true;
+        match self.current_token {
+            Token::BlockOpen(_) => self.code_block(),
+            Token::Equals if self.peek() == Token::AngleBracket('>') => {
+                self.code_until(|token| *token == Token::Semicolon);
+                self.advance(); // skip ;
+            },
+            _ => {
+                has_body = false;
+                if self.current_token == Token::Semicolon {
+                    self.advance();
+                }
+            },
+        }
+
+        // operators have symbols for names
+        if operator {
+            return None
+        }
+        let (identifier, return_type) = csharp_name(&header)?;
+
+        Some(ASTNode::Function(Function {
+            identifier,
+            parameters,
+            return_type: tokens_to_string(&return_type),
+            access,
+            is_abstract: modifiers.is_abstract || (kind == ObjectKind::Interface && !has_body),
+            is_static: modifiers.is_static,
+            is_class_method: false,
+            decorators: Vec::new(),
+            attributes: modifiers.attributes,
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        }))
+    }
+
+    // Type Name { get; set; } = value; or Type Name => expression;
+    fn csharp_property(&mut self, header: &[Token], modifiers: Modifiers, access: Access, start: (i64, usize)) -> Option<ASTNode> {
+        let mut settable = false;
+        let mut value = None;
+
+        if matches!(self.current_token, Token::BlockOpen(_)) {
+            let accessors = self.parse_list(Token::BlockOpen('{'), Token::BlockClose).concat();
+            settable = accessors.contains(&Token::Identifier(String::from("set")));
+
+            self.skip_layout();
+            if self.current_token == Token::Equals {
+                self.advance(); // skip =
+                value = Some(self.code_until(|token| *token == Token::Semicolon));
+                self.advance(); // skip ;
+            }
+        } else {
+            self.code_until(|token| *token == Token::Semicolon);
+            self.advance(); // skip ;
+        }
+
+        let (identifier, vtype) = csharp_name(header)?;
+        let mut variable = self.new_variable(identifier, VariableKind::Property, access, &vtype, value, &self.current_object, self.span_from(start));
+        variable.readonly = !settable || modifiers.readonly;
+
+        Some(ASTNode::Variable(variable))
+    }
+
+    // Type a = 1, b; with the current token just after the first name
+    fn csharp_fields(&mut self, header: &[Token], modifiers: &Modifiers, access: Access, start: (i64, usize)) -> Vec<ASTNode> {
+        let mut fields = Vec::new();
+        let (vtype, mut identifier) = match header.split_last() {
+            Some((name, vtype)) => (vtype, name.name().map(|name| name.to_string())),
+            None => (header, None),
+        };
+
+        loop {
+            let mut value = None;
+            if self.current_token == Token::Equals {
+                self.advance(); // skip =
+                value = Some(self.code_until(|token| matches!(token, Token::Comma | Token::Semicolon)));
+            }
+
+            if let Some(identifier) = identifier.take() {
+                let kind = if modifiers.is_static { VariableKind::ClassAttribute } else { VariableKind::Field };
+                let mut variable = self.new_variable(identifier, kind, access, vtype, value, &self.current_object, self.span_from(start));
+                variable.readonly = modifiers.readonly;
+                fields.push(ASTNode::Variable(variable));
+            }
+
+            if self.current_token != Token::Comma {
+                break
+            }
+            self.advance(); // skip ,
+            self.skip_layout();
+            identifier = self.current_token.name().map(|name| name.to_string());
+            self.advance();
+        }
+
+        self.code_until(|token| *token == Token::Semicolon);
+        if self.current_token == Token::Semicolon {
+            self.advance();
+        }
+        fields
+    }
+}
+
+// A class's base list starts with its base class if it has one, interface names start with I by
+// convention. Structs, interfaces and records structs can only have interfaces. The resolver corrects
+// this for the bases it finds
+fn csharp_interfaces(kind: ObjectKind, bases: &[String]) -> Vec<String> {
+    bases
+        .iter()
+        .enumerate()
+        .filter(|(index, base)| {
+            let mut name = base_name(base).chars();
+            let interface_name = name.next() == Some('I') && name.next().is_some_and(|c| c.is_uppercase());
+            kind != ObjectKind::Class || *index > 0 || interface_name
+        })
+        .map(|(_, base)| base.clone())
+        .collect()
+}
+
+// The name and type of a member, void IShape.Draw -> Draw, void and ~Finalizer -> ~Finalizer
+fn csharp_name(header: &[Token]) -> Option<(String, Vec<Token>)> {
+    let (last, _) = header.split_last()?;
+    let mut name = last.name()?.to_string();
+
+    // an explicit interface implementation is qualified with the interface
+    let mut end = header.len() - 1;
+    while end >= 2 && header[end - 1] == Token::Period {
+        end -= 2;
+    }
+    if end >= 1 && header[end - 1] == Token::Other('~') {
+        name = format!("~{}", name);
+        end -= 1;
+    }
+
+    Some((name, header[..end].to_vec()))
+}
+
+// A parameter without its attributes and modifiers, [NotNull] this ref string name -> string name
+fn csharp_parameter(tokens: &[Token]) -> Vec<Token> {
+    let mut tokens = tokens;
+
+    loop {
+        match tokens {
+            [Token::Bracket('['), rest @ ..] => {
+                let mut depth = 0;
+                let close = rest.iter().position(|token| {
+                    match token {
+                        Token::Bracket('[') => depth += 1,
+                        Token::Bracket(']') if depth == 0 => return true,
+                        Token::Bracket(']') => depth -= 1,
+                        _ => {},
+                    }
+                    false
+                });
+                tokens = match close {
+                    Some(close) => &rest[close + 1..],
+                    None => &[],
+                };
+            },
+            [Token::Identifier(modifier), rest @ ..] if matches!(modifier.as_str(), "this" | "ref" | "out" | "in" | "params" | "scoped" | "readonly") => tokens = rest,
+            _ => break,
+        }
+    }
+
+    tokens.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Access;
+    use crate::testing::{node, resolve};
+
+    const SQUARE: &str = "namespace Shapes;
+
+public class Shape { }
+
+public partial class Square : Shape, IComparable
+{
+    public int Side { get; }
+    protected internal string name;
+
+    public int CompareTo(object other) => 0;
+}
+
+public record Point(int X, int Y);
+";
+
+    #[test]
+    fn base_classes_are_told_apart_from_interfaces() {
+        let hierarchy = resolve(&[("Shapes/Square.cs", SQUARE)]);
+        let square = node(&hierarchy, "Square");
+
+        assert_eq!(square.parents, [hierarchy.find("Shape").unwrap()]);
+        assert_eq!(square.unresolved_parents, ["IComparable"]);
+        assert_eq!(square.object.interfaces, ["IComparable"]);
+        assert_eq!(square.object.namespace, "Shapes");
+    }
+
+    #[test]
+    fn properties_and_record_parameters_are_read() {
+        let hierarchy = resolve(&[("Shapes/Square.cs", SQUARE)]);
+        let square = &node(&hierarchy, "Square").object;
+        let point = &node(&hierarchy, "Point").object;
+
+        assert_eq!(square.variables[0].identifier, "Side");
+        assert!(square.variables[0].readonly);
+        assert_eq!(square.variables[1].access, Access::Protected);
+        assert_eq!(point.variables.iter().map(|variable| variable.identifier.as_str()).collect::<Vec<_>>(), ["X", "Y"]);
+    }
+}
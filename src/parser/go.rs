@@ -0,0 +1,386 @@
+use super::Parser;
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Object, ObjectKind, Parameter, Severity, Token, VariableKind};
+
+// Go. Named types are objects, the fields a struct embeds are its parents, as are the interfaces an interface
+// embeds, and methods are merged into the type of their receiver by the resolver, which also works out the
+// interfaces each type satisfies from their methods:
+//
+// type Square struct { Shape; side float64 }    Square, embedding Shape, with the field side
+// type Drawer interface { io.Closer; Draw() }   Drawer, embedding io.Closer, with the method Draw
+// func (s *Square) Draw() { ... }               Draw, a method of Square
+impl Parser {
+    // The package clause, a type declaration, or a group of them, a method or a function. Anything else, eg
+    // import declarations, is skipped along with function bodies and variable values
+    pub(super) fn handle_go_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+
+        match self.current_token.clone() {
+            Token::ObjectDeclaration(keyword) if keyword == "type" => {
+                self.advance(); // skip type
+                if self.current_token != Token::Parenthesis('(') {
+                    return self.go_type_spec(start).map_or(ASTNode::None, ASTNode::Object)
+                }
+
+                // type ( A struct { }; B int ), the types are flattened out of the group by parse
+                let mut group = self.blank_object(String::new(), ObjectKind::Module, false, start);
+                self.advance(); // skip (
+                loop {
+                    match self.current_token {
+                        Token::Parenthesis(')') => break,
+                        Token::Eof => {
+                            self.diagnostic(Severity::Error, start, String::from("unclosed `(`"));
+                            break
+                        },
+                        Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                            self.advance();
+                        },
+                        _ => {
+                            let start = self.position();
+                            group.objects.extend(self.go_type_spec(start));
+                            if self.position() == start {
+                                self.advance();
+                            }
+                        },
+                    }
+                }
+                self.advance(); // skip )
+                ASTNode::Object(group)
+            },
+            Token::Identifier(id) if id == "package" => self.handle_package(),
+            Token::Identifier(id) if id == "func" => {
+                match self.go_function() {
+                    (Some(receiver), Some(function)) => {
+                        self.add_definition(receiver, ASTNode::Function(function), start);
+                        ASTNode::None
+                    },
+                    (None, Some(function)) => ASTNode::Function(function),
+                    _ => ASTNode::None,
+                }
+            },
+            Token::Identifier(id) if matches!(id.as_str(), "var" | "const" | "import") => {
+                self.advance();
+                self.until(Some(go_continues), false, |_| false);
+                ASTNode::None
+            },
+            _ => {
+                self.advance();
+                ASTNode::None
+            },
+        }
+    }
+
+    // Name[T any] struct { ... }, interface { ... } or any other type, after the type keyword. Aliases,
+    // type Name = Other, aren't types of their own
+    fn go_type_spec(&mut self, start: (i64, usize)) -> Option<Object> {
+        let identifier = self.current_token.name()?.to_string();
+        self.advance();
+
+        // type parameters rather than an array, type Grid [4]int
+        let mut parameters = Vec::new();
+        if self.current_token == Token::Bracket('[') && matches!(self.peek(), Token::Identifier(_)) {
+            parameters = go_parameters(self.parse_list(Token::Bracket('['), Token::Bracket(']')));
+        }
+        let generics = parameters.iter().map(|parameter| parameter.identifier.clone()).collect();
+        let where_clauses = parameters
+            .iter()
+            .filter_map(|parameter| Some(format!("{}: {}", parameter.identifier, parameter.ptype.as_ref()?)))
+            .collect();
+
+        if self.current_token == Token::Equals {
+            self.advance(); // skip =
+            self.until(Some(go_continues), false, |_| false);
+            return None
+        }
+
+        let public = go_exported(&identifier);
+        let kind = match &self.current_token {
+            Token::ObjectDeclaration(keyword) if keyword == "interface" => ObjectKind::Interface,
+            _ => ObjectKind::Struct,
+        };
+        let mut object = self.blank_object(identifier, kind, public, start);
+        object.generics = generics;
+        object.where_clauses = where_clauses;
+
+        match &self.current_token {
+            Token::ObjectDeclaration(keyword) if keyword == "struct" || keyword == "interface" => {
+                self.advance(); // skip struct / interface
+                self.go_type_body(&mut object);
+            },
+            // type Celsius float64 or type HandlerFunc func(w Writer), which can have methods too
+            _ => {
+                self.until(Some(go_continues), false, |_| false);
+            },
+        }
+        object.span = self.span_from(start);
+
+        Some(object)
+    }
+
+    // The fields of a struct or the methods of an interface, one per line, along with what they embed
+    fn go_type_body(&mut self, object: &mut Object) {
+        if !matches!(self.current_token, Token::BlockOpen(_)) {
+            return
+        }
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        self.advance(); // skip {
+
+        loop {
+            match self.current_token {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+                _ if object.kind == ObjectKind::Interface => self.go_interface_element(object),
+                _ => self.go_fields(object),
+            }
+        }
+        self.advance(); // skip }
+
+        self.current_object = enclosing_object;
+    }
+
+    // Name, Other Type `tag` or an embedded Base, *Base or pkg.Base
+    fn go_fields(&mut self, object: &mut Object) {
+        let start = self.position();
+        let mut tokens = self.until(Some(go_continues), false, |_| false);
+        // the tag is for encoders
+        if let Some(Token::StringLiteral(_)) = tokens.last() {
+            tokens.pop();
+        }
+        if tokens.is_empty() {
+            self.advance();
+            return
+        }
+
+        if go_embedded(&tokens) {
+            let parent = tokens_to_string(tokens.strip_prefix(&[Token::Other('*')]).unwrap_or(&tokens));
+            object.keywords.push((String::from("embedded"), parent.clone()));
+            object.parents.push(parent);
+            return
+        }
+
+        // a, b Type, each name is followed by a comma or the type
+        let mut names = Vec::new();
+        let mut rest = &tokens[..];
+        while let [Token::Identifier(name), separator, tail @ ..] = rest {
+            names.push(name.clone());
+            if *separator != Token::Comma {
+                rest = &rest[1..];
+                break
+            }
+            rest = tail;
+        }
+
+        for name in &names {
+            let variable = self.new_variable(name.clone(), VariableKind::Field, go_access(name), rest, None, &object.identifier, self.span_from(start));
+            object.variables.push(variable);
+        }
+    }
+
+    // Method(parameters) results, or an embedded Base or pkg.Base. Type sets, ~int | ~float64, are skipped
+    fn go_interface_element(&mut self, object: &mut Object) {
+        let start = self.position();
+
+        // String() string, where String is read as a type
+        if self.current_token.name().is_some() && self.peek() == Token::Parenthesis('(') {
+            let identifier = self.current_token.to_string();
+            self.advance();
+            let mut function = self.go_signature(identifier, start);
+            function.is_abstract = true;
+            object.functions.push(function);
+            return
+        }
+
+        let tokens = self.until(Some(go_continues), false, |_| false);
+        if tokens.is_empty() {
+            self.advance();
+        } else if go_embedded(&tokens) {
+            let parent = tokens_to_string(&tokens);
+            object.keywords.push((String::from("embedded"), parent.clone()));
+            object.interfaces.push(parent.clone());
+            object.parents.push(parent);
+        }
+    }
+
+    // func (receiver *Type) Name[T any](parameters) results { ... }, the current token is func. Along with a
+    // method comes the name of the type it's for, functions have no receiver
+    fn go_function(&mut self) -> (Option<String>, Option<Function>) {
+        let start = self.position();
+        self.advance(); // skip func
+
+        let mut receiver = None;
+        if self.current_token == Token::Parenthesis('(') {
+            // (s *Stack[T]) -> Stack
+            let tokens = self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')).concat();
+            let end = tokens.iter().position(|token| *token == Token::Bracket('[')).unwrap_or(tokens.len());
+            receiver = tokens[..end].iter().rev().find_map(|token| token.name()).map(|name| name.to_string());
+        }
+
+        let identifier = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => {
+                self.until(Some(go_continues), false, |_| false);
+                return (None, None)
+            },
+        };
+        self.advance();
+        if self.current_token == Token::Bracket('[') {
+            self.parse_list(Token::Bracket('['), Token::Bracket(']'));
+        }
+
+        let function = self.go_signature(identifier, start);
+        if matches!(self.current_token, Token::BlockOpen(_)) {
+            self.code_block();
+        }
+
+        (receiver, Some(function))
+    }
+
+    // (parameters) results, after the name of a method
+    fn go_signature(&mut self, identifier: String, start: (i64, usize)) -> Function {
+        let parameters = go_parameters(self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')));
+
+        // (int, error) or a single result, up to the body
+        let return_type = if self.current_token == Token::Parenthesis('(') {
+            let results = go_parameters(self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')));
+            let types: Vec<String> = results.into_iter().filter_map(|result| result.ptype).collect();
+            format!("({})", types.join(", "))
+        } else {
+            tokens_to_string(&self.until(Some(go_continues), false, |token| matches!(token, Token::BlockOpen(_))))
+        };
+
+        Function {
+            access: go_access(&identifier),
+            identifier,
+            parameters,
+            return_type,
+            is_abstract: false,
+            is_static: false,
+            is_class_method: false,
+            decorators: Vec::new(),
+            attributes: Vec::new(),
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        }
+    }
+}
+
+// Capitalised names are exported from their package
+fn go_exported(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_uppercase())
+}
+
+// and the others can only be used within it
+fn go_access(name: &str) -> Access {
+    if go_exported(name) { Access::Public } else { Access::Package }
+}
+
+// A field or interface element that's only a type, Base, *Base, pkg.Base or Base[T]
+fn go_embedded(tokens: &[Token]) -> bool {
+    let tokens = tokens.strip_prefix(&[Token::Other('*')]).unwrap_or(tokens);
+    match tokens {
+        [Token::Identifier(_)] => true,
+        [Token::Identifier(_), Token::Period, Token::Identifier(_), rest @ ..] => rest.is_empty() || rest.first() == Some(&Token::Bracket('[')),
+        [Token::Identifier(_), Token::Bracket('['), Token::Identifier(_), ..] => tokens.last() == Some(&Token::Bracket(']')),
+        _ => false,
+    }
+}
+
+// a, b int, c string -> a: int, b: int, c: string. Either every parameter has a name or none do, in which
+// case they're called _
+fn go_parameters(entries: Vec<Vec<Token>>) -> Vec<Parameter> {
+    let named = entries.iter().any(|entry| {
+        matches!(entry.as_slice(), [Token::Identifier(name), second, ..] if !matches!(name.as_str(), "func" | "map" | "chan") && *second != Token::Period)
+    });
+
+    let mut parameters: Vec<Parameter> = entries
+        .iter()
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_first() {
+            Some((name, ptype)) if named => Parameter {
+                identifier: name.to_string(),
+                ptype: if ptype.is_empty() { None } else { Some(tokens_to_string(ptype)) },
+            },
+            _ => Parameter {
+                identifier: String::from("_"),
+                ptype: Some(tokens_to_string(entry)),
+            },
+        })
+        .collect();
+
+    // a, b int, a is an int too
+    for index in (1..parameters.len()).rev() {
+        if parameters[index - 1].ptype.is_none() {
+            parameters[index - 1].ptype = parameters[index].ptype.clone();
+        }
+    }
+
+    parameters
+}
+
+// Whether a line carries on onto the next, which it does when it ends with an operator or comma
+fn go_continues(tokens: &[Token], _next: &Token) -> bool {
+    matches!(tokens.last(), Some(Token::Other(_) | Token::Equals | Token::Comma | Token::Colon | Token::Period | Token::Arrow))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{methods, node, parse, resolve};
+
+    const SHAPES: &str = "package shapes
+
+type Stringer interface {
+    String() string
+}
+
+type Point struct {
+    X, Y int
+}
+
+func (p Point) String() string { return \"point\" }
+
+func NewPoint(x, y int) Point { return Point{x, y} }
+";
+
+    #[test]
+    fn interface_methods_named_like_types_are_read() {
+        let hierarchy = resolve(&[("shapes/point.go", SHAPES)]);
+
+        assert_eq!(methods(&hierarchy, "Stringer"), ["String"]);
+        assert!(node(&hierarchy, "Stringer").object.functions[0].is_abstract);
+    }
+
+    #[test]
+    fn types_satisfy_interfaces_with_their_methods() {
+        let hierarchy = resolve(&[("shapes/point.go", SHAPES)]);
+        let stringer = hierarchy.find("Stringer").unwrap();
+
+        assert_eq!(node(&hierarchy, "Point").parents, [stringer]);
+        assert_eq!(methods(&hierarchy, "Point"), ["String"]);
+    }
+
+    #[test]
+    fn functions_without_a_receiver_are_kept() {
+        let model = parse(&[("shapes/point.go", SHAPES)]);
+        let functions: Vec<&str> = model.functions.iter().map(|function| function.identifier.as_str()).collect();
+
+        assert_eq!(functions, ["NewPoint"]);
+        assert_eq!(model.functions[0].return_type, "Point");
+    }
+
+    #[test]
+    fn fn_and_def_are_names() {
+        let hierarchy = resolve(&[("shapes/visitor.go", "package shapes\n\ntype Visitor struct {\n    fn  func(Point)\n    def string\n}\n\nfunc (v Visitor) Visit(fn func(Point)) {}\n")]);
+        let fields: Vec<&str> = node(&hierarchy, "Visitor").object.variables.iter().map(|variable| variable.identifier.as_str()).collect();
+
+        assert_eq!(fields, ["fn", "def"]);
+        assert_eq!(methods(&hierarchy, "Visitor"), ["Visit"]);
+        assert_eq!(node(&hierarchy, "Visitor").object.functions[0].parameters[0].identifier, "fn");
+    }
+}
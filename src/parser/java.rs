@@ -0,0 +1,559 @@
+use super::{split_top_level, Modifiers, Parser};
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Object, ObjectKind, Parameter, Severity, Token, VariableKind};
+
+// Java types. Classes, interfaces, enums, records and @interface annotation types are objects, with
+// their nested types, and anonymous and local classes from their method bodies, as nested objects:
+//
+// class Box<T extends Number> extends Base implements Sized { }    Box, with the parents Base and Sized
+// record Point(int x, int y) { }                                  Point, with the final fields x and y
+// new Runnable() { public void run() { } }                        Outer$1, with the parent Runnable
+impl Parser {
+    // The package statement, or a top level type along with its modifiers. Anything else, eg import statements,
+    // is skipped
+    pub(super) fn handle_java_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+        if self.current_token == Token::Identifier(String::from("package")) {
+            return self.handle_package()
+        }
+        let modifiers = self.java_modifiers();
+
+        if self.is_java_type() {
+            let public = modifiers.access == Some(Access::Public);
+            return self.handle_java_type(modifiers, public, start)
+        }
+
+        self.advance();
+        ASTNode::None
+    }
+
+    // class, interface, enum or record followed by its name, record and the others can be names elsewhere
+    fn is_java_type(&self) -> bool {
+        let keyword = matches!(&self.current_token, Token::ObjectDeclaration(keyword) if matches!(keyword.as_str(), "class" | "interface" | "enum" | "record"));
+        keyword && self.peek().name().is_some()
+    }
+
+    // Annotations and modifiers, eg @Override public static final
+    fn java_modifiers(&mut self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+
+        loop {
+            match self.current_token.clone() {
+                Token::Other('@') => {
+                    self.advance(); // skip @
+                    if self.current_token == Token::ObjectDeclaration(String::from("interface")) {
+                        modifiers.annotation_type = true;
+                        break
+                    }
+
+                    modifiers.decorators.push(self.annotation_name());
+                    continue;
+                },
+
+                Token::Publicity(public) => modifiers.access = Some(public.into()),
+                Token::Identifier(id) => match id.as_str() {
+                    "protected" => modifiers.access = Some(Access::Protected),
+                    "static" => modifiers.is_static = true,
+                    "final" => modifiers.is_final = true,
+                    "abstract" => modifiers.is_abstract = true,
+                    // non-sealed
+                    "non" if self.peek() == Token::Other('-') => {
+                        self.advance();
+                        self.advance();
+                    },
+                    "default" | "sealed" | "synchronized" | "native" | "transient" | "volatile" | "strictfp" => {},
+                    _ => break,
+                },
+
+                Token::Indent(_) | Token::Comment(_) => {},
+                _ => break,
+            }
+            self.advance();
+        }
+
+        modifiers
+    }
+
+    // class Name<T> extends Base implements A, B { ... } and the like, the current token is the keyword
+    fn handle_java_type(&mut self, modifiers: Modifiers, public: bool, start: (i64, usize)) -> ASTNode {
+        let keyword = self.current_token.to_string();
+        self.advance(); // skip class / interface / enum / record
+
+        let identifier = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => return ASTNode::None,
+        };
+        self.advance();
+
+        let kind = if modifiers.annotation_type {
+            ObjectKind::Interface
+        } else {
+            ObjectKind::from_keyword(&keyword)
+        };
+        let mut object = self.blank_object(identifier, kind, public, start);
+        object.is_abstract = modifiers.is_abstract;
+        object.decorators = modifiers.decorators;
+        (object.generics, object.where_clauses) = self.java_generics();
+
+        // the components of a record are its fields
+        if keyword == "record" {
+            for (tokens, span) in self.parse_spanned_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+                let tokens = java_parameter(&tokens);
+                if let Some((name, vtype)) = tokens.split_last()
+                    && let Some(name) = name.name() {
+                    let mut variable = self.new_variable(name.to_string(), VariableKind::Field, Access::Private, vtype, None, &object.identifier, span);
+                    variable.readonly = true;
+                    object.variables.push(variable);
+                }
+            }
+        }
+
+        loop {
+            match &self.current_token {
+                Token::Identifier(id) if id == "extends" || id == "implements" => {
+                    // an interface extends other interfaces
+                    let interfaces = id == "implements" || object.kind == ObjectKind::Interface;
+                    self.advance();
+                    let parents = split_top_level(&self.java_clause(), &Token::Comma);
+                    if interfaces {
+                        object.interfaces.extend(parents.iter().cloned());
+                    }
+                    object.parents.extend(parents);
+                },
+                // sealed classes list the classes that may extend them
+                Token::Identifier(id) if id == "permits" => {
+                    self.advance();
+                    self.java_clause();
+                },
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                },
+                _ => break,
+            }
+        }
+
+        self.java_type_body(&mut object);
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // <T extends Number & Comparable<T>, U> -> ["T", "U"], ["T extends Number & Comparable<T>"]
+    fn java_generics(&mut self) -> (Vec<String>, Vec<String>) {
+        let mut generics = Vec::new();
+        let mut where_clauses = Vec::new();
+
+        for tokens in self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>')) {
+            let tokens = java_parameter(&tokens);
+            let name = match tokens.first().and_then(|token| token.name()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            if let Some(extends) = tokens.iter().position(|token| *token == Token::Identifier(String::from("extends"))) {
+                where_clauses.push(format!("{} extends {}", name, split_top_level(&tokens[extends + 1..], &Token::Other('&')).join(" & ")));
+            }
+            generics.push(name);
+        }
+
+        (generics, where_clauses)
+    }
+
+    // Tokens up to the body or the next clause of a declaration, eg the list after implements or throws
+    fn java_clause(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+
+        loop {
+            match &self.current_token {
+                Token::BlockOpen(_) | Token::Semicolon | Token::Eof => break,
+                Token::Identifier(id) if matches!(id.as_str(), "extends" | "implements" | "permits" | "throws" | "default") => break,
+                Token::Indent(_) | Token::Comment(_) => {},
+                token => tokens.push(token.clone()),
+            }
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // The members between the braces of a type. Anonymous and local classes from its method bodies and
+    // initialisers are nested in it
+    fn java_type_body(&mut self, object: &mut Object) {
+        if !matches!(self.current_token, Token::BlockOpen(_)) {
+            return
+        }
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        let enclosing_locals = std::mem::take(&mut self.local_objects);
+        self.advance(); // skip {
+
+        if object.kind == ObjectKind::Enum {
+            self.java_enum_constants(object);
+        }
+
+        loop {
+            match &self.current_token {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+
+                // static { } and { } initialisers
+                Token::Identifier(id) if id == "static" && matches!(self.peek(), Token::BlockOpen(_)) => {
+                    self.advance();
+                    self.code_block();
+                },
+                Token::BlockOpen(_) => self.code_block(),
+
+                _ => {
+                    for node in self.java_member(object.kind) {
+                        match node {
+                            ASTNode::Variable(variable) => object.variables.push(variable),
+                            ASTNode::Function(function) => object.functions.push(function),
+                            ASTNode::Object(nested) => object.objects.push(nested),
+                            _ => {},
+                        }
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        object.objects.append(&mut self.local_objects);
+        self.local_objects = enclosing_locals;
+        self.current_object = enclosing_object;
+    }
+
+    // RED, GREEN("g"), BLUE { ... }; at the start of an enum's body
+    fn java_enum_constants(&mut self, object: &mut Object) {
+        loop {
+            match self.current_token {
+                Token::Indent(_) | Token::Comment(_) | Token::Comma => {
+                    self.advance();
+                    continue;
+                },
+                Token::Semicolon => {
+                    self.advance();
+                    break
+                },
+                Token::BlockClose | Token::Eof => break,
+                _ => {},
+            }
+
+            let start = self.position();
+            self.java_modifiers();
+            let identifier = match self.current_token.name() {
+                Some(name) => name.to_string(),
+                None => break,
+            };
+            self.advance();
+
+            let mut value = None;
+            if self.current_token == Token::Parenthesis('(') {
+                value = Some(self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')).join(&Token::Comma));
+            }
+            // a constant with a body is an anonymous subclass of the enum
+            if matches!(self.current_token, Token::BlockOpen(_)) {
+                self.java_anonymous_class(object.identifier.clone(), start);
+            }
+
+            let span = self.span_from(start);
+            object.variables.push(self.new_variable(identifier, VariableKind::Variant, Access::Public, &[], value, &object.identifier, span));
+        }
+    }
+
+    // A field, method, constructor or nested type
+    fn java_member(&mut self, kind: ObjectKind) -> Vec<ASTNode> {
+        let start = self.position();
+        let modifiers = self.java_modifiers();
+        // members without a modifier have package access, those of an interface are public
+        let access = modifiers.access.unwrap_or(if kind == ObjectKind::Interface { Access::Public } else { Access::Package });
+
+        if self.is_java_type() {
+            return vec![self.handle_java_type(modifiers, access.is_public(), start)]
+        }
+
+        // <T> T first(List<T> items)
+        if self.current_token == Token::AngleBracket('<') {
+            self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'));
+        }
+
+        // the type and name, up to the parameters of a method or the value of a field
+        let mut header = Vec::new();
+        let mut depth = 0;
+        loop {
+            match self.current_token {
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                Token::AngleBracket('<') | Token::Bracket('[') => depth += 1,
+                Token::AngleBracket('>') | Token::Bracket(']') => depth -= 1,
+                Token::Parenthesis('(') | Token::BlockOpen(_) | Token::Equals | Token::Comma | Token::Semicolon if depth == 0 => break,
+                Token::BlockClose | Token::Eof => break,
+                _ => {},
+            }
+
+            header.push(self.current_token.clone());
+            self.advance();
+        }
+
+        match self.current_token {
+            Token::Parenthesis('(') => {
+                // the constructors of an enum are private
+                let access = match header.as_slice() {
+                    [_] if kind == ObjectKind::Enum => Access::Private,
+                    _ => access,
+                };
+                self.java_method(&header, modifiers, access, kind, start).into_iter().collect()
+            },
+
+            // public Point { ... }, the compact constructor of a record
+            Token::BlockOpen(_) => {
+                self.code_block();
+                match header.as_slice() {
+                    [name] if name.name().is_some() => {
+                        let function = self.java_function(name.to_string(), Vec::new(), String::new(), &modifiers, access, start);
+                        vec![ASTNode::Function(function)]
+                    },
+                    _ => Vec::new(),
+                }
+            },
+
+            Token::Equals | Token::Comma | Token::Semicolon => self.java_fields(&header, &modifiers, access, kind, start),
+            _ => Vec::new(),
+        }
+    }
+
+    // type name(parameters) throws A, B { ... }, the current token is the (
+    fn java_method(&mut self, header: &[Token], modifiers: Modifiers, access: Access, kind: ObjectKind, start: (i64, usize)) -> Option<ASTNode> {
+        let name = header.last().and_then(|token| token.name()).map(|name| name.to_string());
+        let return_type = tokens_to_string(&header[..header.len().saturating_sub(1)]);
+
+        let mut parameters = Vec::new();
+        for tokens in self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+            parameters.extend(self.parse_parameter(&java_parameter(&tokens)));
+        }
+
+        let mut throws = Vec::new();
+        loop {
+            match &self.current_token {
+                Token::Identifier(id) if id == "throws" => {
+                    self.advance();
+                    throws = split_top_level(&self.java_clause(), &Token::Comma);
+                },
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                },
+                _ => break,
+            }
+        }
+
+        let has_body = matches!(self.current_token, Token::BlockOpen(_));
+        if has_body {
+            self.code_block();
+        } else {
+            // abstract methods, and the default values of annotation members
+            self.code_until(|token| *token == Token::Semicolon);
+            if self.current_token == Token::Semicolon {
+                self.advance();
+            }
+        }
+
+        let mut function = self.java_function(name?, parameters, return_type, &modifiers, access, start);
+        function.is_abstract = modifiers.is_abstract || (kind == ObjectKind::Interface && !has_body);
+        function.throws = throws;
+
+        Some(ASTNode::Function(function))
+    }
+
+    fn java_function(&self, identifier: String, parameters: Vec<Parameter>, return_type: String, modifiers: &Modifiers, access: Access, start: (i64, usize)) -> Function {
+        Function {
+            identifier,
+            parameters,
+            return_type,
+            access,
+            is_abstract: modifiers.is_abstract,
+            is_static: modifiers.is_static,
+            is_class_method: false,
+            decorators: modifiers.decorators.clone(),
+            attributes: Vec::new(),
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        }
+    }
+
+    // Type a = 1, b; with the current token just after the first name
+    fn java_fields(&mut self, header: &[Token], modifiers: &Modifiers, access: Access, kind: ObjectKind, start: (i64, usize)) -> Vec<ASTNode> {
+        let mut fields = Vec::new();
+        // int counts[] is an int[] called counts
+        let name = header.iter().rposition(|token| token.name().is_some());
+        let vtype: Vec<Token> = header.iter().enumerate().filter(|(index, _)| Some(*index) != name).map(|(_, token)| token.clone()).collect();
+        let mut identifier = name.and_then(|name| header[name].name()).map(|name| name.to_string());
+
+        loop {
+            let mut value = None;
+            if self.current_token == Token::Equals {
+                self.advance(); // skip =
+                value = Some(self.code_until(|token| matches!(token, Token::Comma | Token::Semicolon)));
+            }
+
+            if let Some(identifier) = identifier.take() {
+                // interface fields are constants
+                let constant = kind == ObjectKind::Interface;
+                let field_kind = if modifiers.is_static || constant { VariableKind::ClassAttribute } else { VariableKind::Field };
+                let mut variable = self.new_variable(identifier, field_kind, access, &vtype, value, &self.current_object, self.span_from(start));
+                variable.readonly = modifiers.is_final || constant;
+                fields.push(ASTNode::Variable(variable));
+            }
+
+            if self.current_token != Token::Comma {
+                break
+            }
+            self.advance(); // skip ,
+            while matches!(self.current_token, Token::Indent(_) | Token::Comment(_)) {
+                self.advance();
+            }
+            identifier = self.current_token.name().map(|name| name.to_string());
+            self.advance();
+        }
+
+        self.code_until(|token| *token == Token::Semicolon);
+        if self.current_token == Token::Semicolon {
+            self.advance();
+        }
+        fields
+    }
+
+    // new Type() { } and class Local { } in a method body or field value, in which case the tokens moved over
+    pub(super) fn java_local_declaration(&mut self) -> Option<Vec<Token>> {
+        match &self.current_token {
+            Token::Identifier(id) if id == "new" => Some(self.java_new()),
+            Token::ObjectDeclaration(_) if self.is_java_type() => {
+                let start = self.position();
+                if let ASTNode::Object(object) = self.handle_java_type(Modifiers::default(), false, start) {
+                    self.local_objects.push(object);
+                }
+                Some(Vec::new())
+            },
+            _ => None,
+        }
+    }
+
+    // new Type(arguments), followed by a body if it's an anonymous class
+    fn java_new(&mut self) -> Vec<Token> {
+        let start = self.position();
+        let mut tokens = vec![self.current_token.clone()];
+        self.advance(); // skip new
+
+        let mut class = Vec::new();
+        let mut depth = 0;
+        loop {
+            match self.current_token {
+                Token::AngleBracket('<') => depth += 1,
+                Token::AngleBracket('>') => depth -= 1,
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                Token::Parenthesis(_) | Token::Bracket(_) | Token::BlockOpen(_) | Token::BlockClose
+                | Token::Semicolon | Token::Comma | Token::Eof if depth == 0 => break,
+                _ => {},
+            }
+
+            class.push(self.current_token.clone());
+            self.advance();
+        }
+        tokens.extend(class.iter().cloned());
+
+        if self.current_token != Token::Parenthesis('(') {
+            return tokens
+        }
+        tokens.push(self.current_token.clone());
+        self.advance(); // skip (
+        tokens.extend(self.code_until(|token| *token == Token::Parenthesis(')')));
+        tokens.push(self.current_token.clone());
+        self.advance(); // skip )
+
+        if matches!(self.current_token, Token::BlockOpen(_)) {
+            self.java_anonymous_class(tokens_to_string(&class), start);
+            tokens.extend([Token::BlockOpen('{'), Token::BlockClose]);
+        }
+
+        tokens
+    }
+
+    // The body of an anonymous class, which is named after the class it's in, Outer$1, Outer$2
+    fn java_anonymous_class(&mut self, parent: String, start: (i64, usize)) {
+        let number = self.local_objects.iter().filter(|object| object.identifier.contains('$')).count() + 1;
+        let identifier = format!("{}${}", self.current_object, number);
+
+        let mut object = self.blank_object(identifier, ObjectKind::Class, false, start);
+        object.parents.push(parent);
+        self.java_type_body(&mut object);
+        object.span = self.span_from(start);
+
+        self.local_objects.push(object);
+    }
+}
+
+// A parameter without its annotations and final, @NonNull final String name -> String name
+fn java_parameter(tokens: &[Token]) -> Vec<Token> {
+    let mut parameter = Vec::new();
+    let mut tokens = tokens.iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Other('@') => {
+                tokens.next();
+                while tokens.next_if_eq(&&Token::Period).is_some() {
+                    tokens.next();
+                }
+                if tokens.next_if_eq(&&Token::Parenthesis('(')).is_some() {
+                    let mut depth = 0;
+                    for token in tokens.by_ref() {
+                        match token {
+                            Token::Parenthesis('(') => depth += 1,
+                            Token::Parenthesis(')') if depth == 0 => break,
+                            Token::Parenthesis(')') => depth -= 1,
+                            _ => {},
+                        }
+                    }
+                }
+            },
+            Token::Identifier(id) if id == "final" => {},
+            token => parameter.push(token.clone()),
+        }
+    }
+
+    parameter
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{node, resolve};
+
+    const SHAPE: &str = "package com.example.shapes;
+
+public class Shape extends Base implements Serializable {
+    private int sides;
+    protected String name;
+    int corners;
+
+    public double area() { return 0; }
+}
+";
+
+    #[test]
+    fn external_interfaces_are_kept_apart_from_the_base_class() {
+        let hierarchy = resolve(&[("com/example/shapes/Shape.java", SHAPE)]);
+        let shape = node(&hierarchy, "Shape");
+
+        assert_eq!(shape.unresolved_parents, ["Base", "Serializable"]);
+        assert_eq!(shape.object.interfaces, ["Serializable"]);
+    }
+}
@@ -0,0 +1,550 @@
+use super::{line_continues, split_entries, Modifiers, Parser};
+use crate::resolver::base_name;
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Object, ObjectKind, Parameter, Severity, Span, Token, Variable, VariableKind};
+
+// Kotlin. Classes, interfaces, objects and companion objects are objects, nested ones nested objects, and
+// extension functions and properties are merged into their receiver by the resolver:
+//
+// data class Point(val x: Int, var y: Int) : Shape(x), Named    Point, with the properties x and y, the
+//                                                               base class Shape and the interface Named
+// companion object { fun origin() = Point(0, 0) }               Companion, nested in Point
+// fun Point.distance(other: Point): Double { ... }              distance, a method of Point
+impl Parser {
+    // The package directive, or a top level type, extension function or extension property along with its
+    // modifiers. Anything else, eg import directives, is skipped
+    pub(super) fn handle_kotlin_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+        if self.current_token == Token::Identifier(String::from("package")) {
+            return self.handle_package()
+        }
+        let modifiers = self.kotlin_modifiers();
+        // declarations without a modifier are public
+        let access = modifiers.access.unwrap_or(Access::Public);
+
+        match &self.current_token {
+            _ if self.is_kotlin_type(&modifiers) => self.kotlin_type(modifiers, access.is_public(), start),
+            Token::Identifier(id) if id == "fun" => {
+                let (receiver, function) = self.kotlin_function(modifiers, access, ObjectKind::Class, start);
+                if let (Some(receiver), Some(function)) = (receiver, function) {
+                    self.add_definition(receiver, function, start);
+                }
+                ASTNode::None
+            },
+            Token::Identifier(id) if id == "val" || id == "var" => {
+                let (receiver, property) = self.kotlin_property(access, start);
+                if let (Some(receiver), Some(property)) = (receiver, property) {
+                    self.add_definition(receiver, property, start);
+                }
+                ASTNode::None
+            },
+            _ => {
+                if self.position() == start {
+                    self.advance();
+                }
+                ASTNode::None
+            },
+        }
+    }
+
+    // class, interface or object, along with its name unless it's a companion object
+    fn is_kotlin_type(&self, modifiers: &Modifiers) -> bool {
+        match &self.current_token {
+            Token::ObjectDeclaration(keyword) if keyword == "class" || keyword == "interface" => self.peek().name().is_some(),
+            Token::ObjectDeclaration(keyword) if keyword == "object" => modifiers.companion || self.peek().name().is_some(),
+            _ => false,
+        }
+    }
+
+    // Annotations and modifiers, eg @Serializable private data
+    fn kotlin_modifiers(&mut self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+
+        loop {
+            match self.current_token.clone() {
+                Token::Other('@') => {
+                    self.advance(); // skip @
+
+                    // @get:JvmName says what the annotation is on
+                    if self.peek() == Token::Colon {
+                        self.advance();
+                        self.advance();
+                    }
+                    modifiers.decorators.push(self.annotation_name());
+                    continue;
+                },
+
+                // enum class
+                Token::ObjectDeclaration(keyword) if keyword == "enum" => modifiers.enum_class = true,
+
+                Token::Publicity(public) => modifiers.access = Some(public.into()),
+                Token::Identifier(id) => match id.as_str() {
+                    "protected" => modifiers.access = Some(Access::Protected),
+                    "internal" => modifiers.access = Some(Access::Package),
+                    "abstract" | "sealed" => modifiers.is_abstract = true,
+                    "data" => modifiers.data = true,
+                    "companion" => modifiers.companion = true,
+                    // fun interface
+                    "fun" if self.peek() == Token::ObjectDeclaration(String::from("interface")) => {},
+                    "open" | "final" | "override" | "inner" | "value" | "inline" | "annotation" | "lateinit" | "const"
+                    | "suspend" | "operator" | "infix" | "tailrec" | "external" | "expect" | "actual" | "vararg" => {},
+                    _ => break,
+                },
+
+                Token::Indent(_) | Token::Comment(_) => {},
+                _ => break,
+            }
+            self.advance();
+        }
+
+        modifiers
+    }
+
+    // class Name<T>(val a: Int) : Base(a), Other where T : Any { ... } and the like, the current token is the keyword
+    fn kotlin_type(&mut self, modifiers: Modifiers, public: bool, start: (i64, usize)) -> ASTNode {
+        let keyword = self.current_token.to_string();
+        self.advance(); // skip class / interface / object
+
+        let identifier = match self.current_token.name() {
+            Some(name) => {
+                let name = name.to_string();
+                self.advance();
+                name
+            },
+            // companion object { } is called Companion
+            None => String::from("Companion"),
+        };
+
+        let kind = if modifiers.enum_class {
+            ObjectKind::Enum
+        } else if modifiers.data {
+            ObjectKind::Data
+        } else {
+            ObjectKind::from_keyword(&keyword)
+        };
+        let mut object = self.blank_object(identifier, kind, public, start);
+        object.is_abstract = modifiers.is_abstract;
+        object.decorators = modifiers.decorators;
+        let (generics, mut where_clauses) = self.kotlin_generics();
+        object.generics = generics;
+
+        // the primary constructor, whose val and var parameters are properties, private constructor(...) when
+        // it has modifiers of its own
+        if self.position().0 == self.previous_end.0 && !matches!(self.current_token, Token::Colon | Token::BlockOpen(_)) {
+            self.kotlin_modifiers();
+            if self.current_token == Token::Identifier(String::from("constructor")) {
+                self.advance();
+            }
+        }
+        if self.current_token == Token::Parenthesis('(') {
+            for (tokens, span) in self.parse_spanned_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+                if let Some(variable) = self.kotlin_constructor_property(&tokens, &object.identifier, span) {
+                    object.variables.push(variable);
+                }
+            }
+        }
+
+        if self.next_is(Token::Colon) {
+            self.advance(); // skip :
+            let clause = self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_)) || *token == Token::Identifier(String::from("where")));
+
+            for entry in split_entries(&clause, &Token::Comma, true) {
+                // Base by delegate implements Base through delegate
+                let end = entry.iter().position(|token| *token == Token::Identifier(String::from("by"))).unwrap_or(entry.len());
+                let entry = &entry[..end];
+                // Base(a) calls the constructor of the base class, interfaces have none
+                let call = entry.last() == Some(&Token::Parenthesis(')'));
+                let parent = match entry.iter().position(|token| *token == Token::Parenthesis('(')) {
+                    Some(open) => tokens_to_string(&entry[..open]),
+                    None => tokens_to_string(entry),
+                };
+                if parent.is_empty() {
+                    continue;
+                }
+
+                if !call || kind == ObjectKind::Interface {
+                    object.interfaces.push(parent.clone());
+                }
+                object.parents.push(parent);
+            }
+        }
+        where_clauses.extend(self.kotlin_constraints());
+        object.where_clauses = where_clauses;
+
+        if self.next_is(Token::BlockOpen('{')) {
+            self.kotlin_type_body(&mut object);
+        }
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // <out T : Comparable<T>, in R> -> ["T", "R"], ["T: Comparable<T>"]
+    fn kotlin_generics(&mut self) -> (Vec<String>, Vec<String>) {
+        let mut generics = Vec::new();
+        let mut where_clauses = Vec::new();
+
+        for tokens in self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>')) {
+            let colon = tokens.iter().position(|token| *token == Token::Colon).unwrap_or(tokens.len());
+            let name = match tokens[..colon].iter().rev().find_map(|token| token.name()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            if colon < tokens.len() {
+                where_clauses.push(format!("{}: {}", name, tokens_to_string(&tokens[colon + 1..])));
+            }
+            generics.push(name);
+        }
+
+        (generics, where_clauses)
+    }
+
+    // where T : Comparable<T>, T : Serializable -> ["T: Comparable<T>", "T: Serializable"]
+    fn kotlin_constraints(&mut self) -> Vec<String> {
+        if !self.next_is(Token::Identifier(String::from("where"))) {
+            return Vec::new()
+        }
+        self.advance(); // skip where
+
+        let clause = self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_) | Token::Equals));
+        split_entries(&clause, &Token::Comma, true)
+            .iter()
+            .filter_map(|entry| {
+                let colon = entry.iter().position(|token| *token == Token::Colon)?;
+                Some(format!("{}: {}", tokens_to_string(&entry[..colon]), tokens_to_string(&entry[colon + 1..])))
+            })
+            .collect()
+    }
+
+    // private val name: String = "" in a primary constructor, parameters without val or var aren't properties
+    fn kotlin_constructor_property(&mut self, tokens: &[Token], parent: &str, span: Span) -> Option<Variable> {
+        let mut access = Access::Public;
+        let mut readonly = None;
+        let mut rest = tokens;
+
+        while let Some((first, tail)) = rest.split_first() {
+            match first {
+                Token::Other('@') => {
+                    // @Json(name = "x")
+                    rest = tail.get(1..).unwrap_or_default();
+                    if rest.first() == Some(&Token::Parenthesis('(')) {
+                        let close = rest.iter().position(|token| *token == Token::Parenthesis(')')).unwrap_or(rest.len() - 1);
+                        rest = &rest[close + 1..];
+                    }
+                    continue;
+                },
+                Token::Publicity(public) => access = (*public).into(),
+                Token::Identifier(id) if id == "protected" => access = Access::Protected,
+                Token::Identifier(id) if id == "internal" => access = Access::Package,
+                Token::Identifier(id) if id == "val" => readonly = Some(true),
+                Token::Identifier(id) if id == "var" => readonly = Some(false),
+                Token::Identifier(id) if matches!(id.as_str(), "override" | "open" | "vararg" | "final" | "crossinline" | "noinline") => {},
+                _ => break,
+            }
+            rest = tail;
+        }
+
+        let readonly = readonly?;
+        let (identifier, vtype, value) = kotlin_binding(rest)?;
+        let mut variable = self.new_variable(identifier, VariableKind::Property, access, &vtype, value, parent, span);
+        variable.readonly = readonly;
+        Some(variable)
+    }
+
+    // The members between the braces of a class, interface or object, and the entries of an enum class
+    fn kotlin_type_body(&mut self, object: &mut Object) {
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        self.advance(); // skip {
+
+        if object.kind == ObjectKind::Enum {
+            self.kotlin_enum_entries(object);
+        }
+
+        loop {
+            match self.current_token.clone() {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+
+                // init { } blocks
+                Token::Identifier(id) if id == "init" && matches!(self.peek(), Token::BlockOpen(_)) => {
+                    self.advance();
+                    self.code_block();
+                },
+
+                _ => {
+                    let start = self.position();
+                    let modifiers = self.kotlin_modifiers();
+                    let access = modifiers.access.unwrap_or(Access::Public);
+
+                    match self.current_token.clone() {
+                        _ if self.is_kotlin_type(&modifiers) => {
+                            if let ASTNode::Object(nested) = self.kotlin_type(modifiers, access.is_public(), start) {
+                                object.objects.push(nested);
+                            }
+                        },
+                        Token::Identifier(id) if id == "fun" => {
+                            if let (_, Some(ASTNode::Function(function))) = self.kotlin_function(modifiers, access, object.kind, start) {
+                                object.functions.push(function);
+                            }
+                        },
+                        Token::Identifier(id) if id == "val" || id == "var" => {
+                            if let (_, Some(ASTNode::Variable(variable))) = self.kotlin_property(access, start) {
+                                object.variables.push(variable);
+                            }
+                        },
+                        // constructor(x: Int) : this(x, 0) { }
+                        Token::Identifier(id) if id == "constructor" => {
+                            self.advance();
+                            let parameters = self.parse_parenthesis(Token::Parenthesis('('), Token::Parenthesis(')'));
+                            if self.next_is(Token::Colon) {
+                                self.advance(); // skip :
+                                self.advance(); // skip this / super
+                                self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                            }
+                            if self.next_is(Token::BlockOpen('{')) {
+                                self.code_block();
+                            }
+                            object.functions.push(self.kotlin_function_node(String::from("constructor"), parameters, Vec::new(), &modifiers, access, false, start));
+                        },
+                        _ => {
+                            if self.position() == start {
+                                self.advance();
+                            }
+                        },
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        self.current_object = enclosing_object;
+    }
+
+    // RED(0xFF0000), GREEN { override fun toString() = "green" }, BLUE; up to the ; or }
+    fn kotlin_enum_entries(&mut self, object: &mut Object) {
+        loop {
+            self.skip_layout();
+            let start = self.position();
+            self.kotlin_modifiers();
+
+            let identifier = match self.current_token.clone() {
+                Token::Identifier(name) if matches!(self.peek(), Token::Parenthesis('(') | Token::Comma | Token::Semicolon | Token::BlockOpen(_) | Token::BlockClose | Token::Comment(_))
+                    || self.pos + 1 >= self.current_line.len() as i64 => name,
+                Token::Semicolon => {
+                    self.advance();
+                    return
+                },
+                _ => return,
+            };
+            self.advance();
+
+            let mut value = None;
+            if self.current_token == Token::Parenthesis('(') {
+                value = Some(self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')).join(&Token::Comma));
+            }
+            // an entry with a body of its own
+            if matches!(self.current_token, Token::BlockOpen(_)) {
+                self.code_block();
+            }
+            let access = object.public.into();
+            object.variables.push(self.new_variable(identifier, VariableKind::Variant, access, &[], value, &object.identifier, self.span_from(start)));
+
+            self.skip_layout();
+            if self.current_token == Token::Comma {
+                self.advance();
+            }
+        }
+    }
+
+    // fun <T> Receiver.name(parameters): ReturnType { ... } or = expression, the current token is fun. Along
+    // with the function comes the name of its receiver if it's an extension
+    fn kotlin_function(&mut self, modifiers: Modifiers, access: Access, kind: ObjectKind, start: (i64, usize)) -> (Option<String>, Option<ASTNode>) {
+        self.advance(); // skip fun
+        if self.current_token == Token::AngleBracket('<') {
+            self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'));
+        }
+
+        // Receiver<T>.name, up to the parameters
+        let header = self.until(Some(line_continues), true, |token| matches!(token, Token::Parenthesis('(') | Token::BlockOpen(_) | Token::Equals | Token::Colon));
+        if self.current_token != Token::Parenthesis('(') {
+            return (None, None)
+        }
+        let (receiver, identifier) = match kotlin_receiver(&header) {
+            Some(declaration) => declaration,
+            None => return (None, None),
+        };
+
+        let parameters = self.parse_parenthesis(Token::Parenthesis('('), Token::Parenthesis(')'));
+        let mut return_type = Vec::new();
+        if self.next_is(Token::Colon) {
+            self.advance(); // skip :
+            return_type = self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_) | Token::Equals) || *token == Token::Identifier(String::from("where")));
+        }
+        self.kotlin_constraints();
+
+        let mut has_body = true;
+        if self.next_is(Token::BlockOpen('{')) {
+            self.code_block();
+        } else if self.next_is(Token::Equals) {
+            self.advance(); // skip =
+            self.until(Some(line_continues), false, |token| *token == Token::Comma);
+        } else {
+            has_body = false;
+        }
+
+        let is_abstract = modifiers.is_abstract || (kind == ObjectKind::Interface && !has_body);
+        let function = self.kotlin_function_node(identifier, parameters, return_type, &modifiers, access, is_abstract, start);
+        (receiver, Some(ASTNode::Function(function)))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn kotlin_function_node(&self, identifier: String, parameters: Vec<Parameter>, return_type: Vec<Token>, modifiers: &Modifiers, access: Access, is_abstract: bool, start: (i64, usize)) -> Function {
+        Function {
+            identifier,
+            parameters,
+            return_type: tokens_to_string(&return_type),
+            access,
+            is_abstract,
+            is_static: false,
+            is_class_method: false,
+            decorators: modifiers.decorators.clone(),
+            attributes: Vec::new(),
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        }
+    }
+
+    // val Receiver.name: Type = value, along with its getter and setter, the current token is val or var.
+    // Along with the property comes the name of its receiver if it's an extension
+    fn kotlin_property(&mut self, access: Access, start: (i64, usize)) -> (Option<String>, Option<ASTNode>) {
+        let readonly = self.current_token == Token::Identifier(String::from("val"));
+        self.advance(); // skip val / var
+        if self.current_token == Token::AngleBracket('<') {
+            self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'));
+        }
+
+        let header = self.until(Some(line_continues), true, |token| matches!(token, Token::Colon | Token::Equals | Token::BlockOpen(_)) || *token == Token::Identifier(String::from("by")));
+        let declaration = kotlin_receiver(&header);
+
+        let mut vtype = Vec::new();
+        if self.current_token == Token::Colon {
+            self.advance(); // skip :
+            vtype = self.until(Some(line_continues), true, |token| matches!(token, Token::Equals | Token::BlockOpen(_)) || *token == Token::Identifier(String::from("by")) || kotlin_accessor(token));
+        }
+        let mut value = None;
+        if self.next_is(Token::Equals) {
+            self.advance(); // skip =
+            value = Some(self.until(Some(line_continues), false, |token| *token == Token::Comma));
+        } else if self.next_is(Token::Identifier(String::from("by"))) {
+            // by lazy { ... } delegates the property
+            self.until(Some(line_continues), false, |token| *token == Token::Comma);
+        }
+
+        // get() = ... and private set on the lines after
+        loop {
+            self.skip_layout();
+            if matches!(&self.current_token, Token::Publicity(_) | Token::Identifier(_)) && kotlin_accessor(&self.peek()) {
+                self.advance(); // skip private / protected / internal
+            }
+            match &self.current_token {
+                token if kotlin_accessor(token) => {
+                    self.advance();
+                    if self.current_token == Token::Parenthesis('(') {
+                        self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                    }
+                    if self.current_token == Token::Colon {
+                        self.advance();
+                        self.until(Some(line_continues), true, |token| matches!(token, Token::Equals | Token::BlockOpen(_)));
+                    }
+                    if self.next_is(Token::BlockOpen('{')) {
+                        self.code_block();
+                    } else if self.next_is(Token::Equals) {
+                        self.advance(); // skip =
+                        self.until(Some(line_continues), false, |token| *token == Token::Comma);
+                    }
+                },
+                _ => break,
+            }
+        }
+
+        let Some((receiver, identifier)) = declaration else {
+            return (None, None)
+        };
+        let mut variable = self.new_variable(identifier, VariableKind::Property, access, &vtype, value, &self.current_object, self.span_from(start));
+        variable.readonly = readonly;
+        (receiver, Some(ASTNode::Variable(variable)))
+    }
+}
+
+// The receiver and name of a declaration, List<T>.second -> Some("List"), "second", and area -> None, "area"
+fn kotlin_receiver(header: &[Token]) -> Option<(Option<String>, String)> {
+    let (last, rest) = header.split_last()?;
+    let name = last.name()?.to_string();
+
+    match rest.split_last() {
+        Some((Token::Period, receiver)) => {
+            // String?.orEmpty
+            let receiver = receiver.strip_suffix(&[Token::Other('?')]).unwrap_or(receiver);
+            let receiver = tokens_to_string(receiver);
+            let end = receiver.find('<').unwrap_or(receiver.len());
+            Some((Some(base_name(&receiver[..end]).to_string()), name))
+        },
+        _ => Some((None, name)),
+    }
+}
+
+// get or set, starting a property's accessor
+fn kotlin_accessor(token: &Token) -> bool {
+    matches!(token, Token::Identifier(id) if id == "get" || id == "set")
+}
+
+// name: Type = value -> ("name", [Type], Some([value]))
+fn kotlin_binding(tokens: &[Token]) -> Option<(String, Vec<Token>, Option<Vec<Token>>)> {
+    let (declaration, value) = match tokens.iter().position(|token| *token == Token::Equals) {
+        Some(equals) => (&tokens[..equals], Some(tokens[equals + 1..].to_vec())),
+        None => (tokens, None),
+    };
+    let (name, vtype) = match declaration.iter().position(|token| *token == Token::Colon) {
+        Some(colon) => (&declaration[..colon], declaration[colon + 1..].to_vec()),
+        None => (declaration, Vec::new()),
+    };
+
+    let name = name.iter().rev().find_map(|token| token.name())?;
+    Some((name.to_string(), vtype, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::{ObjectKind, VariableKind};
+    use crate::testing::{methods, node, resolve};
+
+    #[test]
+    fn strings_within_templates_dont_close_the_class_body() {
+        let hierarchy = resolve(&[("shapes/Shape.kt", "class Shape(val sides: List<Int>) {
+    fun label() = \"${ sides.joinToString { \"}\" } }\"
+
+    fun area(): Double = 0.0
+}
+")]);
+
+        assert_eq!(methods(&hierarchy, "Shape"), ["label", "area"]);
+    }
+
+    #[test]
+    fn data_classes_are_their_own_kind() {
+        let hierarchy = resolve(&[("shapes/Point.kt", "data class Point(val x: Int, var y: Int)\n")]);
+        let point = &node(&hierarchy, "Point").object;
+        let fields: Vec<(&str, VariableKind)> = point.variables.iter().map(|variable| (variable.identifier.as_str(), variable.kind)).collect();
+
+        assert_eq!(point.kind, ObjectKind::Data);
+        assert_eq!(fields, [("x", VariableKind::Property), ("y", VariableKind::Property)]);
+    }
+}
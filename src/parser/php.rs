@@ -0,0 +1,554 @@
+use super::{split_entries, Modifiers, Parser};
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Import, Object, ObjectKind, Parameter, Severity, Token, Variable, VariableKind};
+
+// PHP. Classes, interfaces, traits and enums are objects, and the traits a class uses are parents too, told
+// apart from its base class and interfaces by the keyword use. Parents are resolved through the namespace
+// and use declarations of their file:
+//
+// namespace App\Models;
+// use App\Contracts\HasName;
+// final class User extends Model implements HasName {    User in App\Models, with the base class Model and the
+//     use SoftDeletes;                                   interface App\Contracts\HasName, and the mixin
+//     public function __construct(                       ("use", "SoftDeletes")
+//         private readonly string $name,                 the promoted, read only property name
+//     ) { }
+// }
+impl Parser {
+    // A namespace, use declaration or type along with its modifiers. Anything else, eg functions and the
+    // statements of a script, is skipped
+    pub(super) fn handle_php_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+
+        match &self.current_token {
+            Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                self.advance();
+                return ASTNode::None
+            },
+            // <?php and ?>, which aren't statements
+            Token::AngleBracket('<') if self.peek() == Token::Other('?') => {
+                self.advance(); // skip <
+                self.advance(); // skip ?
+                if matches!(&self.current_token, Token::Identifier(id) if id == "php") {
+                    self.advance();
+                }
+                return ASTNode::None
+            },
+            Token::Other('?') if self.peek() == Token::AngleBracket('>') => {
+                self.advance(); // skip ?
+                self.advance(); // skip >
+                return ASTNode::None
+            },
+            Token::Identifier(id) if id == "namespace" && self.peek() != Token::Other('\\') => return self.php_namespace(start),
+            Token::Identifier(id) if id == "use" => {
+                self.php_use(start);
+                return ASTNode::None
+            },
+            _ => {},
+        }
+
+        let modifiers = self.php_modifiers();
+        match &self.current_token {
+            _ if self.is_php_type() => self.php_type(modifiers, start),
+            Token::Identifier(id) if id == "function" => {
+                self.php_function(modifiers, ObjectKind::Module, start);
+                ASTNode::None
+            },
+            _ => {
+                if self.position() == start {
+                    self.php_statement();
+                }
+                if self.position() == start {
+                    self.advance();
+                }
+                ASTNode::None
+            },
+        }
+    }
+
+    // namespace App\Models; for the rest of the file, or namespace App\Models { ... } for what's in the braces
+    fn php_namespace(&mut self, start: (i64, usize)) -> ASTNode {
+        self.advance(); // skip namespace
+        let name = self.code_until(|token| matches!(token, Token::BlockOpen(_) | Token::Semicolon));
+        let segments: Vec<String> = tokens_to_string(&name).split('\\').filter(|segment| !segment.is_empty()).map(|segment| segment.to_string()).collect();
+
+        if !matches!(self.current_token, Token::BlockOpen(_)) {
+            self.advance(); // skip ;
+            self.namespace = segments;
+            return ASTNode::None
+        }
+
+        // the objects are flattened out of it by parse
+        let mut namespace = self.blank_object(segments.join("\\"), ObjectKind::Module, false, start);
+        let enclosing = std::mem::replace(&mut self.namespace, segments);
+        self.advance(); // skip {
+
+        loop {
+            match self.current_token {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, start, String::from("unclosed `{`"));
+                    break
+                },
+                _ => {
+                    if let ASTNode::Object(object) = self.handle_php_declaration() {
+                        namespace.objects.push(object);
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        self.namespace = enclosing;
+        ASTNode::Object(namespace)
+    }
+
+    // use App\Models\User; use App\Models\User as Member; or use App\Models\{User, Post as Article};
+    // Functions and constants imported with use function and use const aren't types
+    fn php_use(&mut self, start: (i64, usize)) {
+        self.advance(); // skip use
+        if matches!(&self.current_token, Token::Identifier(id) if id == "function" || id == "const") {
+            self.code_until(|token| *token == Token::Semicolon);
+            self.advance(); // skip ;
+            return
+        }
+
+        let tokens = self.code_until(|token| *token == Token::Semicolon);
+        self.advance(); // skip ;
+        let span = self.span_from(start);
+        let namespace = self.namespace();
+
+        let mut imports = Vec::new();
+        match tokens.iter().position(|token| matches!(token, Token::BlockOpen(_))) {
+            // a group shares the prefix before its braces
+            Some(open) => {
+                let prefix = tokens_to_string(&tokens[..open]);
+                let end = tokens.iter().rposition(|token| *token == Token::BlockClose).unwrap_or(tokens.len());
+                for entry in split_entries(&tokens[open + 1..end], &Token::Comma, false) {
+                    imports.push(format!("{}{}", prefix, tokens_to_string(&entry)));
+                }
+            },
+            None => {
+                for entry in split_entries(&tokens, &Token::Comma, false) {
+                    imports.push(tokens_to_string(&entry));
+                }
+            },
+        }
+
+        for import in imports {
+            // App\Models\User as Member
+            let (path, alias) = match import.split_once(" as ") {
+                Some((path, alias)) => (path.to_string(), Some(alias.trim().to_string())),
+                None => (import, None),
+            };
+            let path = path.trim_start_matches('\\');
+            let (module, name) = path.rsplit_once('\\').unwrap_or(("", path));
+
+            self.imports.push(Import {
+                module: module.to_string(),
+                name: Some(name.to_string()),
+                alias,
+                namespace: namespace.clone(),
+                filepath: self.current_file.filepath.clone(),
+                span,
+            });
+        }
+    }
+
+    // class, interface, trait or enum followed by its name, rather than Foo::class
+    fn is_php_type(&self) -> bool {
+        let keyword = match &self.current_token {
+            Token::ObjectDeclaration(keyword) => matches!(keyword.as_str(), "class" | "interface" | "enum"),
+            Token::Trait => true,
+            _ => false,
+        };
+        keyword && self.peek().name().is_some()
+    }
+
+    // Attributes and modifiers, eg #[Entity] abstract or public static
+    fn php_modifiers(&mut self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+
+        loop {
+            match self.current_token.clone() {
+                // #[Route("/users"), Auth]
+                Token::Other('#') if self.peek() == Token::Bracket('[') => {
+                    self.advance(); // skip #
+                    for attribute in self.parse_list(Token::Bracket('['), Token::Bracket(']')) {
+                        let end = attribute.iter().position(|token| *token == Token::Parenthesis('(')).unwrap_or(attribute.len());
+                        modifiers.attributes.push(tokens_to_string(&attribute[..end]));
+                    }
+                    continue;
+                },
+
+                Token::Publicity(public) => modifiers.access = Some(public.into()),
+                Token::Identifier(id) => match id.as_str() {
+                    "protected" => modifiers.access = Some(Access::Protected),
+                    "abstract" => modifiers.is_abstract = true,
+                    "static" => modifiers.is_static = true,
+                    "readonly" => modifiers.readonly = true,
+                    "final" | "var" => {},
+                    _ => break,
+                },
+
+                Token::Indent(_) | Token::Comment(_) => {},
+                _ => break,
+            }
+            self.advance();
+        }
+
+        modifiers
+    }
+
+    // class Name extends Base implements A, B { ... } and the like, the current token is the keyword. Interfaces
+    // extend several interfaces, an enum can have a backing type, enum Suit: string
+    fn php_type(&mut self, modifiers: Modifiers, start: (i64, usize)) -> ASTNode {
+        let kind = match &self.current_token {
+            Token::Trait => ObjectKind::Trait,
+            token => ObjectKind::from_keyword(&token.to_string()),
+        };
+        self.advance(); // skip class / interface / trait / enum
+
+        let identifier = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => return ASTNode::None,
+        };
+        self.advance();
+
+        let mut object = self.blank_object(identifier, kind, true, start);
+        object.is_abstract = modifiers.is_abstract;
+        object.attributes = modifiers.attributes;
+
+        if kind == ObjectKind::Enum && self.current_token == Token::Colon {
+            self.advance(); // skip :
+            self.code_until(|token| matches!(token, Token::BlockOpen(_) | Token::Semicolon) || *token == Token::Identifier(String::from("implements")));
+        }
+        loop {
+            let implements = match &self.current_token {
+                Token::Identifier(id) if id == "extends" => kind == ObjectKind::Interface,
+                Token::Identifier(id) if id == "implements" => true,
+                _ => break,
+            };
+            self.advance();
+
+            let clause = self.code_until(|token| matches!(token, Token::BlockOpen(_) | Token::Semicolon) || matches!(token, Token::Identifier(id) if id == "extends" || id == "implements"));
+            for entry in split_entries(&clause, &Token::Comma, false) {
+                let parent = tokens_to_string(&entry);
+                if implements {
+                    object.interfaces.push(parent.clone());
+                }
+                object.parents.push(parent);
+            }
+        }
+
+        self.php_type_body(&mut object);
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // The members between the braces of a type
+    fn php_type_body(&mut self, object: &mut Object) {
+        self.skip_layout();
+        if !matches!(self.current_token, Token::BlockOpen(_)) {
+            return
+        }
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        self.advance(); // skip {
+
+        loop {
+            match self.current_token.clone() {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+
+                // use Loggable, Cacheable { Loggable::log insteadof Cacheable; }
+                Token::Identifier(id) if id == "use" => {
+                    self.advance();
+                    let clause = self.code_until(|token| matches!(token, Token::BlockOpen(_) | Token::Semicolon));
+                    for entry in split_entries(&clause, &Token::Comma, false) {
+                        let trait_name = tokens_to_string(&entry);
+                        object.keywords.push((String::from("use"), trait_name.clone()));
+                        object.parents.push(trait_name);
+                    }
+                    if matches!(self.current_token, Token::BlockOpen(_)) {
+                        self.code_block();
+                    }
+                },
+                // case Hearts = 'H';
+                Token::Identifier(id) if id == "case" => {
+                    let start = self.position();
+                    self.advance();
+                    let identifier = self.current_token.to_string();
+                    self.advance();
+                    let mut value = None;
+                    if self.current_token == Token::Equals {
+                        self.advance(); // skip =
+                        value = Some(self.code_until(|token| *token == Token::Semicolon));
+                    }
+                    let access = object.public.into();
+                    object.variables.push(self.new_variable(identifier, VariableKind::Variant, access, &[], value, &object.identifier, self.span_from(start)));
+                },
+
+                _ => {
+                    let start = self.position();
+                    let modifiers = self.php_modifiers();
+
+                    match &self.current_token {
+                        Token::Identifier(id) if id == "function" => {
+                            let (function, promoted) = self.php_function(modifiers, object.kind, start);
+                            object.functions.extend(function);
+                            object.variables.extend(promoted);
+                        },
+                        // const int LIMIT = 10;
+                        Token::Identifier(id) if id == "const" => {
+                            self.advance();
+                            let tokens = self.code_until(|token| *token == Token::Semicolon);
+                            let equals = tokens.iter().position(|token| *token == Token::Equals).unwrap_or(tokens.len());
+                            if let Some((name, vtype)) = tokens[..equals].split_last() {
+                                let value = tokens.get(equals + 1..).map(|value| value.to_vec());
+                                let mut variable = self.new_variable(name.to_string(), VariableKind::ClassAttribute, modifiers.access.unwrap_or(Access::Public), vtype, value, &object.identifier, self.span_from(start));
+                                variable.readonly = true;
+                                object.variables.push(variable);
+                            }
+                        },
+                        _ if self.position() != start || php_variable(&self.current_token).is_some() => {
+                            let properties = self.php_properties(&modifiers, &object.identifier, start);
+                            object.variables.extend(properties);
+                        },
+                        _ => {
+                            self.php_statement();
+                            if self.position() == start {
+                                self.advance();
+                            }
+                        },
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        self.current_object = enclosing_object;
+    }
+
+    // ?int $count = 0, $total; after the property's modifiers. Hooks, { get => ... }, are skipped
+    fn php_properties(&mut self, modifiers: &Modifiers, parent: &str, start: (i64, usize)) -> Vec<Variable> {
+        let vtype = self.code_until(|token| *token == Token::Semicolon || php_variable(token).is_some());
+        let kind = if modifiers.is_static { VariableKind::ClassAttribute } else { VariableKind::Field };
+        let mut properties = Vec::new();
+
+        while let Some(identifier) = php_variable(&self.current_token).map(|name| name.to_string()) {
+            self.advance();
+
+            let mut value = None;
+            if self.current_token == Token::Equals {
+                self.advance(); // skip =
+                value = Some(self.code_until(|token| matches!(token, Token::BlockOpen(_) | Token::Comma | Token::Semicolon)));
+            }
+            if matches!(self.current_token, Token::BlockOpen(_)) {
+                self.code_block();
+            }
+
+            let mut variable = self.new_variable(identifier, kind, modifiers.access.unwrap_or(Access::Public), &vtype, value, parent, self.span_from(start));
+            variable.readonly = modifiers.readonly;
+            properties.push(variable);
+
+            if self.current_token != Token::Comma {
+                break
+            }
+            self.advance(); // skip ,
+            self.skip_layout();
+        }
+
+        properties
+    }
+
+    // function name(parameters): ReturnType { ... }, the current token is function. A constructor's parameters
+    // with a visibility or readonly are promoted to properties
+    fn php_function(&mut self, modifiers: Modifiers, kind: ObjectKind, start: (i64, usize)) -> (Option<Function>, Vec<Variable>) {
+        self.advance(); // skip function
+        // function &reference()
+        if self.current_token == Token::Other('&') {
+            self.advance();
+        }
+        let identifier = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => {
+                self.php_statement();
+                return (None, Vec::new())
+            },
+        };
+        self.advance();
+
+        let mut parameters = Vec::new();
+        let mut promoted = Vec::new();
+        for (tokens, span) in self.parse_spanned_list(Token::Parenthesis('('), Token::Parenthesis(')')) {
+            let mut rest = &tokens[..];
+            let mut access = None;
+            let mut readonly = false;
+            while let Some((first, tail)) = rest.split_first() {
+                match first {
+                    // #[SensitiveParameter]
+                    Token::Other('#') => {
+                        let close = rest.iter().position(|token| *token == Token::Bracket(']')).unwrap_or(rest.len() - 1);
+                        rest = &rest[close + 1..];
+                        continue;
+                    },
+                    Token::Publicity(public) => access = Some((*public).into()),
+                    Token::Identifier(id) if id == "protected" => access = Some(Access::Protected),
+                    Token::Identifier(id) if id == "readonly" => readonly = true,
+                    _ => break,
+                }
+                rest = tail;
+            }
+
+            // ?string ...$names = [], the type is what's before the variable
+            let Some(variable) = rest.iter().position(|token| php_variable(token).is_some()) else {
+                continue
+            };
+            let name = php_variable(&rest[variable]).unwrap_or_default().to_string();
+            let ptype: Vec<Token> = rest[..variable].iter().filter(|token| !matches!(token, Token::Other('&') | Token::Period)).cloned().collect();
+            let value = rest.iter().position(|token| *token == Token::Equals).map(|equals| rest[equals + 1..].to_vec());
+
+            if access.is_some() || readonly {
+                let mut property = self.new_variable(name.clone(), VariableKind::Field, access.unwrap_or(Access::Public), &ptype, value, &self.current_object, span);
+                property.readonly = readonly;
+                promoted.push(property);
+            }
+            parameters.push(Parameter {
+                identifier: name,
+                ptype: if ptype.is_empty() { None } else { Some(tokens_to_string(&ptype)) },
+            });
+        }
+
+        let mut return_type = Vec::new();
+        if self.current_token == Token::Colon {
+            self.advance(); // skip :
+            return_type = self.code_until(|token| matches!(token, Token::BlockOpen(_) | Token::Semicolon));
+        }
+        self.skip_layout();
+        let has_body = matches!(self.current_token, Token::BlockOpen(_));
+        if has_body {
+            self.code_block();
+        } else if self.current_token == Token::Semicolon {
+            self.advance();
+        }
+
+        let function = Function {
+            identifier,
+            parameters,
+            return_type: tokens_to_string(&return_type),
+            access: modifiers.access.unwrap_or(Access::Public),
+            is_abstract: modifiers.is_abstract || (kind == ObjectKind::Interface && !has_body),
+            is_static: modifiers.is_static,
+            is_class_method: false,
+            decorators: Vec::new(),
+            attributes: modifiers.attributes,
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        };
+        (Some(function), promoted)
+    }
+
+    // A statement up to its ;, or a block up to its closing brace, eg if ($debug) { ... }
+    fn php_statement(&mut self) {
+        let mut depth = 0;
+
+        loop {
+            match self.current_token {
+                Token::Eof => break,
+                Token::Semicolon if depth == 0 => {
+                    self.advance();
+                    break
+                },
+                Token::BlockClose if depth == 0 => break,
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_) => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') => depth -= 1,
+                Token::BlockClose => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance();
+                        break
+                    }
+                },
+                _ => {},
+            }
+            self.advance();
+        }
+    }
+}
+
+// $name -> name
+fn php_variable(token: &Token) -> Option<&str> {
+    match token {
+        Token::Identifier(id) => id.strip_prefix('$'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Access;
+    use crate::testing::{node, resolve};
+
+    const CONTRACT: &str = "<?php
+namespace App\\Contracts;
+
+interface HasName
+{
+    public function name(): string;
+}
+";
+
+    const USER: &str = "<?php
+namespace App\\Models;
+
+use App\\Contracts\\HasName;
+
+final class User extends Model implements HasName
+{
+    use SoftDeletes;
+
+    public function __construct(
+        private readonly string $name,
+    ) { }
+
+    public function name(): string { return $this->name; }
+}
+";
+
+    #[test]
+    fn parents_are_resolved_through_use_declarations() {
+        let hierarchy = resolve(&[("src/Contracts/HasName.php", CONTRACT), ("src/Models/User.php", USER)]);
+        let user = node(&hierarchy, "User");
+
+        assert_eq!(user.parents, [hierarchy.find("HasName").unwrap()]);
+        assert_eq!(user.unresolved_parents, ["Model", "SoftDeletes"]);
+        assert_eq!(hierarchy.mixin(hierarchy.find("User").unwrap(), "SoftDeletes"), Some("use"));
+        assert_eq!(user.object.namespace, "App\\Models");
+    }
+
+    #[test]
+    fn promoted_constructor_parameters_are_properties() {
+        let hierarchy = resolve(&[("src/Models/User.php", USER)]);
+        let name = &node(&hierarchy, "User").object.variables[0];
+
+        assert_eq!(name.identifier, "name");
+        assert_eq!(name.access, Access::Private);
+        assert!(name.readonly);
+    }
+
+    #[test]
+    fn strings_can_span_lines() {
+        let hierarchy = resolve(&[("src/Shape.php", "<?php\nclass Shape\n{\n    public function describe(): string\n    {\n        return \"a shape\n        with { a brace\";\n    }\n\n    public function area(): float { return 0; }\n}\n")]);
+        let functions: Vec<&str> = node(&hierarchy, "Shape").object.functions.iter().map(|function| function.identifier.as_str()).collect();
+
+        assert_eq!(functions, ["describe", "area"]);
+    }
+}
@@ -0,0 +1,494 @@
+use super::{split_entries, Parser};
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Object, ObjectKind, Parameter, Severity, Token, VariableKind};
+
+// Ruby. Classes and modules are objects, nested ones nested objects, and a class reopened elsewhere is merged
+// into one by the resolver. The modules a class mixes in are parents too, told apart from its superclass by
+// the keyword that mixes them in:
+//
+// class Square < Shape        Square, with the superclass Shape
+//   include Comparable        and the mixin ("include", "Comparable")
+//   attr_reader :side         the read only field side
+//   def self.unit = new(1)    unit, a class method
+// end
+impl Parser {
+    // A top level class or module. Anything else, eg require calls and methods outside of a class, is skipped
+    // along with the blocks it opens
+    pub(super) fn handle_ruby_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+
+        match self.current_token {
+            _ if self.is_ruby_type() => self.ruby_type(start).map_or(ASTNode::None, ASTNode::Object),
+            Token::FunctionDeclaration => {
+                self.ruby_method(Access::Private, false, start);
+                ASTNode::None
+            },
+            Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                self.advance();
+                ASTNode::None
+            },
+            _ => {
+                self.ruby_line();
+                if self.position() == start {
+                    self.advance();
+                }
+                ASTNode::None
+            },
+        }
+    }
+
+    // class or module followed by its name, rather than class << self
+    fn is_ruby_type(&self) -> bool {
+        matches!(&self.current_token, Token::ObjectDeclaration(keyword) if keyword == "class" || keyword == "module")
+            && self.ruby_keyword()
+            && self.peek().name().is_some()
+    }
+
+    // class Outer::Name < Base ... end or module Name ... end, the current token is the keyword
+    fn ruby_type(&mut self, start: (i64, usize)) -> Option<Object> {
+        let kind = ObjectKind::from_keyword(&self.current_token.to_string());
+        self.advance(); // skip class / module
+
+        // Outer::Name is Name declared in Outer
+        let mut path = vec![self.current_token.name()?.to_string()];
+        self.advance();
+        while self.current_token == Token::Connect {
+            self.advance(); // skip ::
+            path.push(self.current_token.name().unwrap_or_default().to_string());
+            self.advance();
+        }
+        let identifier = path.pop().unwrap_or_default();
+        let depth = self.namespace.len();
+        self.namespace.extend(path);
+
+        let mut object = self.blank_object(identifier, kind, true, start);
+        if kind == ObjectKind::Class && self.current_token == Token::AngleBracket('<') {
+            self.advance(); // skip <
+            let parent = self.ruby_line();
+            // Struct.new(:x, :y) is a Struct
+            let end = parent.iter().position(|token| *token == Token::Identifier(String::from("new"))).map_or(parent.len(), |new| new.saturating_sub(1));
+            let parent = tokens_to_string(&parent[..end]);
+            if !parent.is_empty() {
+                object.parents.push(parent);
+            }
+        }
+
+        self.namespace.push(object.identifier.clone());
+        self.ruby_body(&mut object, false);
+        self.namespace.truncate(depth);
+        object.span = self.span_from(start);
+
+        Some(object)
+    }
+
+    // The members of a class or module up to its end, and of a class << self section, whose methods belong to
+    // the class. private, protected and public on a line of their own set the visibility of the methods after them
+    fn ruby_body(&mut self, object: &mut Object, singleton: bool) {
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        let mut access = Access::Public;
+
+        loop {
+            match self.current_token.clone() {
+                Token::Identifier(id) if id == "end" && self.ruby_keyword() => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("expected `end`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+
+                // class << self
+                Token::ObjectDeclaration(keyword) if keyword == "class" && self.peek() == Token::AngleBracket('<') => {
+                    self.advance(); // skip class
+                    while self.current_token == Token::AngleBracket('<') {
+                        self.advance();
+                    }
+                    self.ruby_line(); // self
+                    self.ruby_body(object, true);
+                },
+                _ if self.is_ruby_type() => {
+                    let start = self.position();
+                    if let Some(nested) = self.ruby_type(start) {
+                        object.objects.push(nested);
+                    }
+                },
+                Token::FunctionDeclaration => {
+                    let start = self.position();
+                    object.functions.extend(self.ruby_method(access, singleton, start));
+                },
+
+                Token::Publicity(_) | Token::Identifier(_) if ruby_visibility(&self.current_token).is_some() => {
+                    let visibility = ruby_visibility(&self.current_token).unwrap_or(Access::Public);
+                    let line = self.position().0;
+                    let start = self.position();
+                    self.advance();
+
+                    if self.position().0 > line {
+                        access = visibility;
+                        continue;
+                    }
+                    match &self.current_token {
+                        // private def secret
+                        Token::FunctionDeclaration => object.functions.extend(self.ruby_method(visibility, singleton, start)),
+                        // private attr_reader :secret
+                        Token::Identifier(id) if id.starts_with("attr_") => self.ruby_attributes(object, visibility),
+                        // private :secret, :other
+                        _ => {
+                            let names = ruby_symbols(&self.ruby_line());
+                            for function in object.functions.iter_mut().filter(|function| names.contains(&function.identifier)) {
+                                function.access = visibility;
+                            }
+                            for variable in object.variables.iter_mut().filter(|variable| names.contains(&variable.identifier)) {
+                                variable.access = visibility;
+                            }
+                        },
+                    }
+                },
+                Token::Identifier(id) if matches!(id.as_str(), "attr_accessor" | "attr_reader" | "attr_writer") => self.ruby_attributes(object, access),
+                Token::Identifier(id) if matches!(id.as_str(), "include" | "extend" | "prepend") => {
+                    self.advance();
+                    for module in ruby_entries(&self.ruby_line()) {
+                        object.keywords.push((id.clone(), module.clone()));
+                        object.parents.push(module);
+                    }
+                },
+
+                // VERSION = "1.0" and @@count = 0
+                _ if self.ruby_class_variable().is_some() => {
+                    let start = self.position();
+                    let (identifier, constant) = self.ruby_class_variable().unwrap_or_default();
+                    while self.current_token != Token::Equals {
+                        self.advance();
+                    }
+                    self.advance(); // skip =
+                    let value = self.ruby_line();
+                    // class variables can't be read from outside of the class
+                    let access = if constant { access } else { Access::Private };
+                    let mut variable = self.new_variable(identifier, VariableKind::ClassAttribute, access, &[], Some(value), &object.identifier, self.span_from(start));
+                    variable.readonly = constant;
+                    object.variables.push(variable);
+                },
+
+                _ => {
+                    let start = self.position();
+                    self.ruby_line();
+                    if self.position() == start {
+                        self.advance();
+                    }
+                },
+            }
+        }
+        self.advance(); // skip end
+
+        self.current_object = enclosing_object;
+    }
+
+    // attr_accessor :name, :age, the current token is attr_accessor, attr_reader or attr_writer
+    fn ruby_attributes(&mut self, object: &mut Object, access: Access) {
+        let start = self.position();
+        let readonly = self.current_token == Token::Identifier(String::from("attr_reader"));
+        self.advance();
+
+        for name in ruby_symbols(&self.ruby_line()) {
+            let mut variable = self.new_variable(name, VariableKind::Field, access, &[], None, &object.identifier, self.span_from(start));
+            variable.readonly = readonly;
+            object.add_variable(variable);
+        }
+    }
+
+    // The name of a constant or class variable being assigned, and whether it's a constant
+    fn ruby_class_variable(&self) -> Option<(String, bool)> {
+        let rest = self.current_line.get(self.pos as usize..)?;
+        match rest {
+            [Token::Other('@'), Token::Other('@'), name, Token::Equals, next, ..] if *next != Token::Equals => Some((format!("@@{}", name.name()?), false)),
+            [name, Token::Equals, next, ..] if *next != Token::Equals && name.name()?.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                Some((name.name()?.to_string(), true))
+            },
+            _ => None,
+        }
+    }
+
+    // def self.name(parameters) ... end, def name = expression or an operator, def <=>(other), the current token
+    // is def. Methods of self, or in a class << self section, belong to the class
+    fn ruby_method(&mut self, access: Access, singleton: bool, start: (i64, usize)) -> Option<Function> {
+        let line = self.position().0;
+        self.advance(); // skip def
+
+        let mut is_class_method = singleton;
+        if matches!(&self.current_token, Token::SelfToken) && self.peek() == Token::Period {
+            self.advance(); // skip self
+            self.advance(); // skip .
+            is_class_method = true;
+        }
+
+        // valid?, save!, name= and operators, up to the parameters. The = of def name = expression isn't part of it
+        let mut name: Vec<Token> = Vec::new();
+        while self.position().0 == line && !matches!(self.current_token, Token::Parenthesis('(') | Token::Semicolon | Token::Comment(_) | Token::Eof) {
+            let word = self.current_token.name().is_some();
+            let endless = self.current_token == Token::Equals && self.peek() != Token::Parenthesis('(') && name.first().is_some_and(|first| first.name().is_some());
+            if !name.is_empty() && (word || endless) {
+                break
+            }
+            name.push(self.current_token.clone());
+            self.advance();
+            // a name is done unless ? ! or = follow it
+            if word && !matches!(self.current_token, Token::Other('?' | '!') | Token::Equals) {
+                break
+            }
+        }
+        let identifier = tokens_to_string(&name);
+
+        let parameters = if self.current_token == Token::Parenthesis('(') {
+            ruby_parameters(self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')')))
+        } else if self.position().0 == line && !matches!(self.current_token, Token::Equals | Token::Semicolon) {
+            // def greet name, greeting = "hi"
+            let tokens = self.ruby_line();
+            ruby_parameters(split_entries(&tokens, &Token::Comma, false))
+        } else {
+            Vec::new()
+        };
+
+        if self.current_token == Token::Equals {
+            self.advance(); // skip =
+            self.ruby_line();
+        } else {
+            self.ruby_end(1);
+        }
+
+        if identifier.is_empty() {
+            return None
+        }
+        Some(Function {
+            identifier,
+            parameters,
+            return_type: String::new(),
+            access,
+            is_abstract: false,
+            is_static: false,
+            is_class_method,
+            decorators: Vec::new(),
+            attributes: Vec::new(),
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        })
+    }
+
+    // A statement up to the end of its line, or of the last line it carries on to with an open bracket or a
+    // trailing comma, skipping the blocks it opens, eg has_many :posts do ... end
+    fn ruby_line(&mut self) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut depth = 0;
+        let mut line = self.position().0;
+
+        loop {
+            self.skip_layout();
+            if depth == 0 && !tokens.is_empty() && self.position().0 > line && !matches!(tokens.last(), Some(Token::Comma | Token::Period | Token::Other('\\'))) {
+                break
+            }
+
+            match self.current_token {
+                Token::Eof => break,
+                Token::Semicolon if depth == 0 => break,
+                Token::Identifier(ref id) if id == "end" && depth == 0 && self.ruby_keyword() => break,
+                _ if self.ruby_opens_block() => {
+                    self.ruby_end(0);
+                    line = self.previous_end.0;
+                    continue;
+                },
+
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_) => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose if depth > 0 => depth -= 1,
+                _ => {},
+            }
+
+            line = self.position().0;
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // Skips past the end closing the blocks already open, and those opened along the way. With none open it
+    // skips the block the current token opens
+    fn ruby_end(&mut self, mut depth: usize) {
+        let start = self.position();
+        // while x do ... end has one end
+        let mut loop_line = None;
+
+        loop {
+            match &self.current_token {
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, start, String::from("expected `end`"));
+                    return
+                },
+                Token::Identifier(id) if id == "end" && self.ruby_keyword() => {
+                    if depth == 0 {
+                        return
+                    }
+                    depth -= 1;
+                },
+                Token::Identifier(id) if id == "do" && loop_line == Some(self.position().0) => {},
+                token if self.ruby_opens_block() => {
+                    if matches!(token, Token::Identifier(id) if matches!(id.as_str(), "while" | "until" | "for")) {
+                        loop_line = Some(self.position().0);
+                    }
+                    depth += 1;
+                },
+                _ => {},
+            }
+
+            self.advance();
+            if depth == 0 {
+                return
+            }
+        }
+    }
+
+    // Whether the current token opens a block that an end closes. if, unless, while, until and for only do at
+    // the start of a statement, after it they're modifiers, eg return if done
+    fn ruby_opens_block(&self) -> bool {
+        if !self.ruby_keyword() {
+            return false
+        }
+
+        match &self.current_token {
+            Token::ObjectDeclaration(keyword) => keyword == "class" || keyword == "module",
+            Token::FunctionDeclaration => true,
+            Token::Identifier(id) => match id.as_str() {
+                "begin" | "case" | "do" => true,
+                "if" | "unless" | "while" | "until" | "for" => match self.previous_token() {
+                    None => true,
+                    Some(Token::Other(symbol)) => !matches!(symbol, '?' | '!'),
+                    Some(Token::Identifier(word)) => matches!(word.as_str(), "then" | "else" | "do" | "begin" | "and" | "or" | "not"),
+                    Some(token) => matches!(token, Token::Equals | Token::Semicolon | Token::Comma | Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_)),
+                },
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    // Whether the current token is used as a keyword, rather than as a method name, a.class, a symbol, :end, or a
+    // hash key, class: "wide"
+    fn ruby_keyword(&self) -> bool {
+        !matches!(self.previous_token(), Some(Token::Period | Token::Colon | Token::Connect)) && self.peek() != Token::Colon
+    }
+
+    // The token before the current one on its line
+    fn previous_token(&self) -> Option<&Token> {
+        self.current_line
+            .get(..self.pos.max(0) as usize)?
+            .iter()
+            .rev()
+            .find(|token| !matches!(token, Token::Indent(_) | Token::Comment(_)))
+    }
+}
+
+// private, protected or public, and the visibility it gives. private_class_method :build is private too
+fn ruby_visibility(token: &Token) -> Option<Access> {
+    match token {
+        Token::Publicity(public) => Some((*public).into()),
+        Token::Identifier(id) if id == "protected" => Some(Access::Protected),
+        Token::Identifier(id) if id == "private_class_method" => Some(Access::Private),
+        Token::Identifier(id) if id == "public_class_method" => Some(Access::Public),
+        _ => None,
+    }
+}
+
+// :name, :age -> ["name", "age"]
+fn ruby_symbols(tokens: &[Token]) -> Vec<String> {
+    tokens
+        .windows(2)
+        .filter(|pair| pair[0] == Token::Colon)
+        .filter_map(|pair| pair[1].name().map(|name| name.to_string()))
+        .collect()
+}
+
+// Comparable, Enumerable -> ["Comparable", "Enumerable"]
+fn ruby_entries(tokens: &[Token]) -> Vec<String> {
+    split_entries(tokens, &Token::Comma, false)
+        .iter()
+        .map(|entry| tokens_to_string(entry))
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+// name, greeting = "hi", *rest, key:, **options, &block -> name, greeting, *rest, key, **options, &block
+fn ruby_parameters(entries: Vec<Vec<Token>>) -> Vec<Parameter> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let end = entry.iter().position(|token| matches!(token, Token::Colon | Token::Equals)).unwrap_or(entry.len());
+            let identifier = tokens_to_string(&entry[..end]);
+            if identifier.is_empty() {
+                return None
+            }
+
+            Some(Parameter {
+                identifier,
+                ptype: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Access;
+    use crate::testing::{methods, node, resolve};
+
+    const SHAPES: &str = "class Shape
+end
+
+class Square < Shape
+  include Comparable
+  attr_reader :side
+
+  def self.unit = new(1)
+
+  def area
+    side * side
+  end
+
+  protected
+
+  def scale(factor)
+    Square.new(side * factor)
+  end
+end
+";
+
+    #[test]
+    fn mixins_are_kept_apart_from_the_superclass() {
+        let hierarchy = resolve(&[("lib/square.rb", SHAPES)]);
+        let square = node(&hierarchy, "Square");
+
+        assert_eq!(square.parents, [hierarchy.find("Shape").unwrap()]);
+        assert_eq!(square.unresolved_parents, ["Comparable"]);
+        assert_eq!(hierarchy.mixin(hierarchy.find("Square").unwrap(), "Comparable"), Some("include"));
+    }
+
+    #[test]
+    fn reopened_classes_are_merged() {
+        let hierarchy = resolve(&[
+            ("lib/square.rb", SHAPES),
+            ("lib/square/sides.rb", "class Square\n  def sides = 4\nend\n"),
+        ]);
+        let square = &node(&hierarchy, "Square").object;
+
+        assert_eq!(methods(&hierarchy, "Square"), ["unit", "area", "scale", "sides"]);
+        assert!(square.functions[0].is_class_method);
+        assert_eq!(square.functions[2].access, Access::Protected);
+        assert!(square.variables[0].readonly);
+    }
+
+    #[test]
+    fn strings_can_span_lines() {
+        let hierarchy = resolve(&[("lib/shape.rb", "class Shape\n  def describe\n    \"a shape\n    end \\\" }\"\n  end\n\n  def area\n    0\n  end\nend\n")]);
+
+        assert_eq!(methods(&hierarchy, "Shape"), ["describe", "area"]);
+    }
+}
@@ -0,0 +1,601 @@
+use super::{split_top_level, Parser};
+use crate::structs::{tokens_to_string, ASTNode, Access, Import, ModuleDeclaration, ObjectKind, Span, Token, Variable, VariableKind};
+
+// Rust items. Structs, enums, unions and traits are objects, an impl block is parsed as an object of
+// kind Impl named after the type it's for, which the resolver merges into that type:
+//
+// trait Shape: Debug { fn area(&self) -> f64; }     Shape, a trait with Debug as a parent
+// struct Square(f64);                               Square, with the field 0
+// impl Shape for Square { fn area(&self) ... }      area and the parent Shape added to Square
+impl Parser {
+    // struct, enum, union, trait or impl. Other keywords ClassTree knows, eg object or module, are plain names in rust
+    pub(super) fn handle_rust_item(&mut self, public: bool) -> ASTNode {
+        let start = self.position();
+        let keyword = self.current_token.to_string();
+        let attributes = std::mem::take(&mut self.attributes);
+
+        match keyword.as_str() {
+            "struct" | "enum" | "union" | "trait" => self.handle_rust_type(&keyword, public, attributes, start),
+            "impl" => self.handle_rust_impl(attributes, start),
+
+            // type Alias = Type; and associated types
+            "type" => {
+                while !matches!(self.current_token, Token::Semicolon | Token::BlockClose | Token::Eof) {
+                    self.advance();
+                }
+                ASTNode::None
+            },
+
+            _ => {
+                self.advance();
+                ASTNode::None
+            },
+        }
+    }
+
+    // #[derive(Debug)] before an item, #![allow(dead_code)] inside the module it's about
+    pub(super) fn handle_attribute(&mut self) -> ASTNode {
+        self.advance(); // skip #
+        let inner = self.current_token == Token::Other('!');
+        if inner {
+            self.advance();
+        }
+
+        let tokens = self.parse_list(Token::Bracket('['), Token::Bracket(']')).concat();
+        if !inner && !tokens.is_empty() {
+            self.attributes.push(tokens_to_string(&tokens));
+        }
+
+        ASTNode::None
+    }
+
+    // use crate::structs::{self, Object, Token as T}; each path it brings in is an import
+    pub(super) fn handle_use(&mut self) -> ASTNode {
+        let start = self.position();
+        self.attributes.clear();
+        self.advance(); // skip use
+
+        let mut tokens = Vec::new();
+        while !matches!(self.current_token, Token::Semicolon | Token::Eof) {
+            if !matches!(self.current_token, Token::Indent(_) | Token::Comment(_)) {
+                tokens.push(self.current_token.clone());
+            }
+            self.advance();
+        }
+        let span = self.span_from(start);
+        self.advance(); // skip ;
+
+        let mut paths = Vec::new();
+        use_tree(&[], &tokens, &mut paths);
+
+        for (mut path, alias) in paths {
+            // use a::b::C -> a::b, C, use clap -> clap
+            let name = path.pop();
+            let (module, name) = match name {
+                Some(name) if path.is_empty() => (name, None),
+                Some(name) => (path.join("::"), Some(name)),
+                None => continue,
+            };
+
+            self.imports.push(Import {
+                module,
+                name,
+                alias,
+                namespace: self.namespace(),
+                filepath: self.current_file.filepath.clone(),
+                span,
+            });
+        }
+
+        ASTNode::None
+    }
+
+    // mod name; says the module's items are in another file, the items of mod name { } are declared
+    // in the namespace name
+    pub(super) fn handle_module(&mut self) -> ASTNode {
+        let start = self.position();
+        let attributes = std::mem::take(&mut self.attributes);
+        self.advance(); // skip mod
+
+        let name = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => return ASTNode::None,
+        };
+        self.advance();
+
+        if !matches!(self.current_token, Token::BlockOpen(_)) {
+            self.modules.push(ModuleDeclaration {
+                name,
+                // #[path = "other.rs"]
+                path: attributes.iter().find_map(|attribute| attribute.strip_prefix("path=")).map(|path| path.to_string()),
+                namespace: self.namespace(),
+                filepath: self.current_file.filepath.clone(),
+            });
+            return ASTNode::None
+        }
+
+        self.namespace.push(name.clone());
+        let block = self.handle_block(start.0);
+        self.namespace.pop();
+
+        let mut module = self.blank_object(name, ObjectKind::Module, false, start);
+        module.attributes = attributes;
+        for node in block {
+            match node {
+                ASTNode::Object(object) => module.objects.push(object),
+                ASTNode::Function(function) => module.functions.push(function),
+                ASTNode::Variable(variable) => module.variables.push(variable),
+                _ => {},
+            }
+        }
+
+        ASTNode::Object(module)
+    }
+
+    // pub const fn, pub unsafe extern "C" fn
+    pub(super) fn skip_rust_qualifiers(&mut self) {
+        loop {
+            match &self.current_token {
+                Token::Identifier(id) if matches!(id.as_str(), "async" | "const" | "unsafe" | "extern" | "default") => {},
+                Token::StringLiteral(_) => {}, // the ABI
+                _ => break,
+            }
+            self.advance();
+        }
+    }
+
+    fn handle_rust_type(&mut self, keyword: &str, public: bool, attributes: Vec<String>, start: (i64, usize)) -> ASTNode {
+        self.advance(); // skip struct / enum / union / trait
+
+        // union is only a keyword before a name
+        let identifier = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => return ASTNode::None,
+        };
+        self.advance();
+        let (generics, mut where_clauses) = self.rust_generics();
+
+        // trait Shape: Debug + Clone + 'static, lifetimes and ?Sized aren't traits
+        let mut parents = Vec::new();
+        if keyword == "trait" && self.current_token == Token::Colon {
+            self.advance(); // skip :
+            parents = split_top_level(&self.rust_clause(), &Token::Other('+'))
+                .into_iter()
+                .filter(|bound| !bound.starts_with(['\'', '?']))
+                .collect();
+        }
+        parents.extend(derived_traits(&attributes));
+
+        // struct Pair(pub i32, i32);
+        let mut variables = Vec::new();
+        if self.current_token == Token::Parenthesis('(') {
+            let fields = self.parse_spanned_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+            for (position, (tokens, span)) in fields.iter().enumerate() {
+                let (field_public, vtype) = rust_visibility(strip_attributes(tokens));
+                variables.push(self.new_variable(position.to_string(), VariableKind::Field, field_public.into(), vtype, None, &identifier, *span));
+            }
+        }
+        where_clauses.extend(self.parse_where_clause());
+
+        let enclosing_object = std::mem::replace(&mut self.current_object, identifier.clone());
+        let mut block = Vec::new();
+        match self.current_token {
+            Token::BlockOpen(_) if keyword == "trait" => block = self.handle_block(start.0),
+
+            Token::BlockOpen(_) => {
+                for (tokens, span) in self.parse_spanned_list(Token::BlockOpen('{'), Token::BlockClose) {
+                    let variable = if keyword == "enum" {
+                        self.rust_variant(&tokens, public, &identifier, span)
+                    } else {
+                        self.rust_field(&tokens, &identifier, span)
+                    };
+                    variables.extend(variable);
+                }
+            },
+
+            _ => {}, // struct Unit;
+        }
+        self.current_object = enclosing_object;
+
+        let mut object = self.blank_object(identifier, ObjectKind::from_keyword(keyword), public, start);
+        object.attributes = attributes;
+        object.generics = generics;
+        object.where_clauses = where_clauses;
+        object.variables = variables;
+        object.interfaces = parents.clone();
+        object.parents = parents;
+
+        for node in block {
+            match node {
+                // a trait's methods are as visible as the trait
+                ASTNode::Function(mut function) => {
+                    function.access = public.into();
+                    object.functions.push(function);
+                },
+                ASTNode::Object(nested) => object.objects.push(nested),
+                _ => {},
+            }
+        }
+
+        ASTNode::Object(object)
+    }
+
+    // impl<T> Trait for Type<T> where T: Bound { ... } | impl Type { ... }
+    fn handle_rust_impl(&mut self, attributes: Vec<String>, start: (i64, usize)) -> ASTNode {
+        self.advance(); // skip impl
+        let (generics, mut where_clauses) = self.rust_generics();
+
+        let mut trait_path = Vec::new();
+        let mut type_path = Vec::new();
+        let mut depth = 0;
+        loop {
+            match self.current_token {
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::AngleBracket('>') => depth -= 1,
+
+                Token::BlockOpen(_) | Token::Semicolon if depth == 0 => break,
+                Token::Identifier(ref id) if depth == 0 && id == "where" => break,
+                Token::Identifier(ref id) if depth == 0 && id == "for" => {
+                    trait_path = std::mem::take(&mut type_path);
+                    self.advance();
+                    continue;
+                },
+
+                Token::Eof => break,
+                _ => {},
+            }
+
+            type_path.push(self.current_token.clone());
+            self.advance();
+        }
+        where_clauses.extend(self.parse_where_clause());
+
+        if !matches!(self.current_token, Token::BlockOpen(_)) {
+            return ASTNode::None
+        }
+
+        // named by the path of its type until the resolver merges it into that type
+        let identifier = rust_type_path(&type_path);
+        let enclosing_object = std::mem::replace(&mut self.current_object, identifier.clone());
+        let block = self.handle_block(start.0);
+        self.current_object = enclosing_object;
+
+        // impl !Send for Type says the trait isn't implemented
+        let parents = match trait_path.first() {
+            None | Some(Token::Other('!')) => Vec::new(),
+            Some(_) => vec![tokens_to_string(&trait_path)],
+        };
+
+        let mut object = self.blank_object(identifier, ObjectKind::Impl, false, start);
+        object.attributes = attributes;
+        object.generics = generics;
+        object.where_clauses = where_clauses;
+        object.interfaces = parents.clone();
+        object.parents = parents;
+
+        for node in block {
+            match node {
+                // trait methods are as visible as the trait
+                ASTNode::Function(mut function) => {
+                    if !trait_path.is_empty() {
+                        function.access = Access::Public;
+                    }
+                    object.functions.push(function);
+                },
+                ASTNode::Object(nested) => object.objects.push(nested),
+                _ => {},
+            }
+        }
+
+        ASTNode::Object(object)
+    }
+
+    // <'a, T: Display + 'a, const N: usize> -> ["'a", "T", "N"], ["T: Display + 'a"]
+    fn rust_generics(&mut self) -> (Vec<String>, Vec<String>) {
+        let mut generics = Vec::new();
+        let mut where_clauses = Vec::new();
+
+        for tokens in self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>')) {
+            let tokens = strip_attributes(&tokens);
+            let (constant, tokens) = match tokens {
+                [Token::Identifier(id), rest @ ..] if id == "const" => (true, rest),
+                _ => (false, tokens),
+            };
+            let name = match tokens.first().and_then(|token| token.name()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            // T: Bound = Default
+            let end = tokens.iter().position(|token| *token == Token::Equals).unwrap_or(tokens.len());
+            if let Some(colon) = tokens[..end].iter().position(|token| *token == Token::Colon)
+                && !constant {
+                where_clauses.push(format!("{}: {}", name, split_top_level(&tokens[colon + 1..end], &Token::Other('+')).join(" + ")));
+            }
+            generics.push(name);
+        }
+
+        (generics, where_clauses)
+    }
+
+    // where T: Display, Vec<T>: Debug -> ["T: Display", "Vec<T>: Debug"]
+    fn parse_where_clause(&mut self) -> Vec<String> {
+        let mut where_clauses = Vec::new();
+        if self.current_token != Token::Identifier(String::from("where")) {
+            return where_clauses
+        }
+        self.advance(); // skip where
+
+        loop {
+            let predicate = self.rust_clause();
+            if let Some(colon) = predicate.iter().position(|token| *token == Token::Colon) {
+                where_clauses.push(format!("{}: {}", tokens_to_string(&predicate[..colon]), split_top_level(&predicate[colon + 1..], &Token::Other('+')).join(" + ")));
+            }
+
+            if self.current_token != Token::Comma {
+                break
+            }
+            self.advance(); // skip ,
+        }
+
+        where_clauses
+    }
+
+    // Tokens up to the next top level comma, where clause or the body of the item
+    fn rust_clause(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            match self.current_token {
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::AngleBracket('>') => depth -= 1,
+
+                Token::Comma | Token::BlockOpen(_) | Token::Semicolon if depth == 0 => break,
+                Token::Identifier(ref id) if depth == 0 && id == "where" => break,
+
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                Token::Eof => break,
+                _ => {},
+            }
+
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // pub name: Type
+    fn rust_field(&self, tokens: &[Token], parent: &str, span: Span) -> Option<Variable> {
+        let (public, tokens) = rust_visibility(strip_attributes(tokens));
+        let colon = tokens.iter().position(|token| *token == Token::Colon)?;
+        let identifier = tokens[..colon].last()?.name()?.to_string();
+
+        Some(self.new_variable(identifier, VariableKind::Field, public.into(), &tokens[colon + 1..], None, parent, span))
+    }
+
+    // Name, Name(Type, Type), Name { field: Type } or Name = discriminant
+    fn rust_variant(&self, tokens: &[Token], public: bool, parent: &str, span: Span) -> Option<Variable> {
+        let tokens = strip_attributes(tokens);
+        let identifier = tokens.first()?.name()?.to_string();
+        let rest = &tokens[1..];
+
+        let (payload, value) = match rest.iter().position(|token| *token == Token::Equals) {
+            Some(equals) => (&rest[..equals], Some(rest[equals + 1..].to_vec())),
+            None => (rest, None),
+        };
+
+        Some(self.new_variable(identifier, VariableKind::Variant, public.into(), payload, value, parent, span))
+    }
+}
+
+// Expands a use tree into the paths it brings in, with their aliases:
+// a::{b, c::{self, d as e}, f::*} -> [a, b], [a, c], [a, c, d] as e, [a, f, *]
+fn use_tree(prefix: &[String], tokens: &[Token], paths: &mut Vec<(Vec<String>, Option<String>)>) {
+    let mut path = prefix.to_vec();
+
+    for (position, token) in tokens.iter().enumerate() {
+        match token {
+            Token::BlockOpen(_) => {
+                for item in split_items(&tokens[position + 1..]) {
+                    use_tree(&path, item, paths);
+                }
+                return
+            },
+            Token::Identifier(id) if id == "as" => {
+                paths.push((path, tokens.get(position + 1).map(|alias| alias.to_string())));
+                return
+            },
+
+            Token::Connect => {},
+            // {self, ...} is the module the braces are in
+            Token::SelfToken if !prefix.is_empty() && path.len() == prefix.len() => {},
+            token => path.push(token.to_string()),
+        }
+    }
+
+    if !path.is_empty() {
+        paths.push((path, None));
+    }
+}
+
+// The comma separated items between a pair of braces, the tokens start just inside the opening one
+fn split_items(tokens: &[Token]) -> Vec<&[Token]> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+
+    for (position, token) in tokens.iter().enumerate() {
+        match token {
+            Token::BlockOpen(_) => depth += 1,
+            Token::BlockClose if depth == 0 => {
+                items.push(&tokens[start..position]);
+                return items
+            },
+            Token::BlockClose => depth -= 1,
+            Token::Comma if depth == 0 => {
+                items.push(&tokens[start..position]);
+                start = position + 1;
+            },
+            _ => {},
+        }
+    }
+
+    items.push(&tokens[start..]);
+    items
+}
+
+// #[serde(default)] pub name: Type -> pub name: Type
+fn strip_attributes(tokens: &[Token]) -> &[Token] {
+    let mut tokens = tokens;
+
+    while let [Token::Other('#'), Token::Bracket('['), rest @ ..] = tokens {
+        let mut depth = 0;
+        let close = rest.iter().position(|token| {
+            match token {
+                Token::Bracket('[') => depth += 1,
+                Token::Bracket(']') if depth == 0 => return true,
+                Token::Bracket(']') => depth -= 1,
+                _ => {},
+            }
+            false
+        });
+
+        tokens = match close {
+            Some(close) => &rest[close + 1..],
+            None => &[],
+        };
+    }
+
+    tokens
+}
+
+// #[derive(Debug, serde::Serialize)] -> ["Debug", "serde::Serialize"]
+fn derived_traits(attributes: &[String]) -> Vec<String> {
+    attributes
+        .iter()
+        .filter_map(|attribute| attribute.strip_prefix("derive(")?.strip_suffix(')'))
+        .flat_map(|traits| traits.split(','))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+// pub, pub(crate) -> (true, the tokens after it)
+fn rust_visibility(tokens: &[Token]) -> (bool, &[Token]) {
+    match tokens {
+        [Token::Publicity(public), Token::Parenthesis('('), rest @ ..] => {
+            let close = rest.iter().position(|token| *token == Token::Parenthesis(')')).map_or(0, |close| close + 1);
+            (*public, &rest[close..])
+        },
+        [Token::Publicity(public), rest @ ..] => (*public, rest),
+        _ => (false, tokens),
+    }
+}
+
+// The path of the type an impl is for, &'a mut crate::structs::Object<T> -> crate::structs::Object.
+// Tuples, slices and the like are kept as they are
+fn rust_type_path(tokens: &[Token]) -> String {
+    let end = tokens.iter().position(|token| *token == Token::AngleBracket('<')).unwrap_or(tokens.len());
+    let path: Vec<Token> = tokens[..end]
+        .iter()
+        .filter(|token| match token.name() {
+            Some(name) => !name.starts_with('\'') && name != "mut" && name != "dyn",
+            None => **token == Token::Connect,
+        })
+        .cloned()
+        .collect();
+
+    if path.is_empty() || matches!(tokens.first(), Some(Token::Parenthesis(_) | Token::Bracket(_))) {
+        tokens_to_string(tokens)
+    } else {
+        tokens_to_string(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{methods, node, parse, resolve};
+
+    #[test]
+    fn block_comments_spanning_lines_are_not_read() {
+        let hierarchy = resolve(&[("src/lib.rs", "/*\nstruct Fake {}\n*/\npub struct Real {}\n")]);
+
+        assert!(hierarchy.find("Fake").is_none());
+        assert!(hierarchy.find("Real").is_some());
+    }
+
+    #[test]
+    fn paths_ending_in_public_or_private_are_not_modifiers() {
+        let source = "pub enum Access {\n    Public,\n    Private,\n}\n\nimpl From<bool> for Access {\n    fn from(public: bool) -> Self {\n        if public { Access::Public } else { Access::Private }\n    }\n}\n\npub struct Shape {}\n";
+        let model = parse(&[("src/lib.rs", source)]);
+        let hierarchy = resolve(&[("src/lib.rs", source)]);
+        let variants: Vec<&str> = node(&hierarchy, "Access").object.variables.iter().map(|variable| variable.identifier.as_str()).collect();
+
+        assert!(model.diagnostics.is_empty(), "{:?}", model.diagnostics);
+        assert_eq!(variants, ["Public", "Private"]);
+        assert_eq!(methods(&hierarchy, "Access"), ["from"]);
+        assert!(hierarchy.find("Shape").is_some());
+    }
+
+    #[test]
+    fn derives_generics_and_where_clauses_are_kept() {
+        let source = "\
+pub trait Shape {}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Wrapper<T: Display, U>
+where
+    U: Clone,
+{
+    inner: T,
+    other: U,
+}
+
+#[inline]
+impl<T: Display, U> Shape for Wrapper<T, U> where U: Clone + Default {}
+";
+        let hierarchy = resolve(&[("src/lib.rs", source)]);
+        let wrapper = node(&hierarchy, "Wrapper");
+        let implementation = &wrapper.object.implementations[0];
+
+        assert_eq!(wrapper.object.attributes, ["derive(Debug, Clone, serde::Serialize)"]);
+        assert_eq!(wrapper.object.generics, ["T", "U"]);
+        assert_eq!(wrapper.object.where_clauses, ["T: Display", "U: Clone"]);
+        // derived traits are implemented, though only Shape was read
+        assert_eq!(wrapper.parents, [hierarchy.find("Shape").unwrap()]);
+        assert_eq!(wrapper.unresolved_parents, ["Debug", "Clone", "serde::Serialize"]);
+
+        assert_eq!(implementation.trait_name.as_deref(), Some("Shape"));
+        assert_eq!(implementation.attributes, ["inline"]);
+        assert_eq!(implementation.generics, ["T", "U"]);
+        assert_eq!(implementation.where_clauses, ["T: Display", "U: Clone + Default"]);
+    }
+
+    #[test]
+    fn strings_can_span_lines() {
+        let source = "pub struct Shape {}\nimpl Shape {\n    fn describe(&self) -> &str {\n        \"a shape {\n        \\\"quoted\\\" }\"\n    }\n\n    fn area(&self) -> f64 { 0.0 }\n}\n";
+        let model = parse(&[("src/lib.rs", source)]);
+        let hierarchy = resolve(&[("src/lib.rs", source)]);
+
+        assert!(model.diagnostics.is_empty(), "{:?}", model.diagnostics);
+        assert_eq!(methods(&hierarchy, "Shape"), ["describe", "area"]);
+    }
+
+    #[test]
+    fn inline_block_comments_end_at_their_close() {
+        let source = "pub struct Shape {}\nimpl Shape {\n    fn ok(&self) /* inline */ -> i32 { 0 }\n    fn next(&self) {}\n}\n";
+        let model = parse(&[("src/lib.rs", source)]);
+        let hierarchy = resolve(&[("src/lib.rs", source)]);
+
+        assert!(model.diagnostics.is_empty(), "{:?}", model.diagnostics);
+        assert_eq!(methods(&hierarchy, "Shape"), ["ok", "next"]);
+        let ok = &hierarchy.nodes[hierarchy.find("Shape").unwrap()].object.functions[0];
+        assert!(!ok.is_abstract);
+        assert_eq!(ok.return_type, "i32");
+    }
+}
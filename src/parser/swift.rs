@@ -0,0 +1,544 @@
+use super::{line_continues, split_entries, Modifiers, Parser};
+use crate::resolver::base_name;
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Object, ObjectKind, Severity, Token, Variable, VariableKind};
+
+// Swift. Classes, structs, enums, protocols and actors are objects, nested types nested objects, and
+// extensions are merged into the type they extend by the resolver, wherever they're written:
+//
+// final class Square: Shape, Drawable { }      Square, with the base class Shape and the protocol Drawable
+// var area: Double { side * side }             a computed, read only property of Square
+// extension Square: Codable { func encode() }  the protocol Codable and the method encode, added to Square
+impl Parser {
+    // A top level type or extension along with its modifiers. Anything else, eg import declarations, is skipped
+    pub(super) fn handle_swift_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+        let modifiers = self.swift_modifiers();
+        // types are internal unless they say otherwise
+        let public = modifiers.access.is_some_and(|access| access.is_public());
+
+        match &self.current_token {
+            _ if self.is_swift_type() => self.swift_type(modifiers, public, start),
+            Token::Identifier(id) if id == "extension" => self.swift_extension(modifiers, public, start),
+            // functions and variables outside of a type, along with their bodies and values
+            Token::Identifier(id) if id == "func" => {
+                self.swift_function(modifiers, Access::Private, ObjectKind::Module, start);
+                ASTNode::None
+            },
+            Token::Identifier(id) if id == "var" || id == "let" => {
+                self.swift_properties(modifiers, Access::Private, ObjectKind::Module, start);
+                ASTNode::None
+            },
+            _ => {
+                if self.position() == start {
+                    self.advance();
+                }
+                ASTNode::None
+            },
+        }
+    }
+
+    // class, struct, enum, protocol or actor followed by its name, rather than class func or class var
+    fn is_swift_type(&self) -> bool {
+        let keyword = match &self.current_token {
+            Token::ObjectDeclaration(keyword) => matches!(keyword.as_str(), "class" | "struct" | "enum" | "protocol"),
+            Token::Identifier(keyword) => keyword == "actor",
+            _ => false,
+        };
+        let member = matches!(self.peek(), Token::Identifier(id) if matches!(id.as_str(), "func" | "var" | "let" | "subscript" | "override" | "final"));
+        keyword && !member && self.peek().name().is_some()
+    }
+
+    // Attributes and modifiers, eg @MainActor public final
+    fn swift_modifiers(&mut self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+
+        loop {
+            match self.current_token.clone() {
+                Token::Other('@') => {
+                    self.advance(); // skip @
+                    if let Some(name) = self.current_token.name() {
+                        modifiers.decorators.push(name.to_string());
+                    }
+                    self.advance();
+                    // @available(iOS 13, *)
+                    if self.current_token == Token::Parenthesis('(') {
+                        self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                    }
+                    continue;
+                },
+
+                // class func and class var
+                Token::ObjectDeclaration(keyword) if keyword == "class" && !self.is_swift_type() => modifiers.is_class = true,
+
+                Token::Publicity(public) => {
+                    self.advance();
+                    // private(set) is about the setter
+                    if self.current_token == Token::Parenthesis('(') {
+                        self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                        modifiers.private_setter = true;
+                    } else {
+                        modifiers.access = Some(public.into());
+                    }
+                    continue;
+                },
+                Token::Identifier(id) => match id.as_str() {
+                    "open" | "internal" | "package" | "fileprivate" => {
+                        self.advance();
+                        if self.current_token == Token::Parenthesis('(') {
+                            self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                            modifiers.private_setter = id != "open";
+                        } else {
+                            modifiers.access = Some(match id.as_str() {
+                                "open" => Access::Public,
+                                "fileprivate" => Access::Private,
+                                _ => Access::Package,
+                            });
+                        }
+                        continue;
+                    },
+                    "static" => modifiers.is_static = true,
+                    "final" | "override" | "mutating" | "nonmutating" | "lazy" | "weak" | "unowned" | "required" | "convenience"
+                    | "dynamic" | "indirect" | "optional" | "nonisolated" | "distributed" => {},
+                    _ => break,
+                },
+
+                Token::Indent(_) | Token::Comment(_) => {},
+                _ => break,
+            }
+            self.advance();
+        }
+
+        modifiers
+    }
+
+    // class Name<T>: Base, Protocol where T: Equatable { ... } and the like, the current token is the keyword
+    fn swift_type(&mut self, modifiers: Modifiers, public: bool, start: (i64, usize)) -> ASTNode {
+        let keyword = match self.current_token.to_string().as_str() {
+            "actor" => String::from("class"),
+            keyword => keyword.to_string(),
+        };
+        self.advance(); // skip class / struct / enum / protocol / actor
+
+        let identifier = match self.current_token.name() {
+            Some(name) => name.to_string(),
+            None => return ASTNode::None,
+        };
+        self.advance();
+
+        let kind = ObjectKind::from_keyword(&keyword);
+        let mut object = self.blank_object(identifier, kind, public, start);
+        object.decorators = modifiers.decorators;
+        let (generics, where_clauses) = self.swift_generics();
+        object.generics = generics;
+        object.where_clauses = where_clauses;
+
+        self.swift_inheritance(&mut object);
+        self.swift_type_body(&mut object);
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // extension Name: Protocol where T: Equatable { ... }, a piece of Name for the resolver to merge into it
+    fn swift_extension(&mut self, modifiers: Modifiers, public: bool, start: (i64, usize)) -> ASTNode {
+        self.advance(); // skip extension
+
+        // Outer.Inner or Array<Int>
+        let name = self.until(Some(line_continues), true, |token| matches!(token, Token::Colon | Token::BlockOpen(_)) || *token == Token::Identifier(String::from("where")));
+        let end = name.iter().position(|token| *token == Token::AngleBracket('<')).unwrap_or(name.len());
+        let identifier = base_name(&tokens_to_string(&name[..end])).to_string();
+        if identifier.is_empty() {
+            return ASTNode::None
+        }
+
+        let mut object = self.blank_object(identifier, ObjectKind::Impl, public, start);
+        object.decorators = modifiers.decorators;
+        self.swift_inheritance(&mut object);
+        self.swift_type_body(&mut object);
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // <T: Equatable, U> -> ["T", "U"], ["T: Equatable"]
+    fn swift_generics(&mut self) -> (Vec<String>, Vec<String>) {
+        let mut generics = Vec::new();
+        let mut where_clauses = Vec::new();
+
+        for tokens in self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>')) {
+            let colon = tokens.iter().position(|token| *token == Token::Colon).unwrap_or(tokens.len());
+            let name = match tokens[..colon].iter().rev().find_map(|token| token.name()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            if colon < tokens.len() {
+                where_clauses.push(format!("{}: {}", name, tokens_to_string(&tokens[colon + 1..])));
+            }
+            generics.push(name);
+        }
+
+        (generics, where_clauses)
+    }
+
+    // : Base, Protocol where T: Equatable, up to the body. A class's list may start with its base class, everything
+    // else only conforms to protocols. The resolver corrects this for the bases it finds
+    fn swift_inheritance(&mut self, object: &mut Object) {
+        if self.next_is(Token::Colon) {
+            self.advance(); // skip :
+            let clause = self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_)) || *token == Token::Identifier(String::from("where")));
+
+            for (index, entry) in split_entries(&clause, &Token::Comma, true).iter().enumerate() {
+                let parent = tokens_to_string(entry);
+                // enum Planet: Int gives the type of its raw values
+                if object.kind == ObjectKind::Enum && index == 0 && swift_raw_type(&parent) {
+                    continue;
+                }
+
+                if object.kind != ObjectKind::Class || index > 0 {
+                    object.interfaces.push(parent.clone());
+                }
+                object.parents.push(parent);
+            }
+        }
+
+        if self.next_is(Token::Identifier(String::from("where"))) {
+            self.advance(); // skip where
+            let clause = self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_)));
+            for entry in split_entries(&clause, &Token::Comma, true) {
+                if let Some(colon) = entry.iter().position(|token| *token == Token::Colon) {
+                    object.where_clauses.push(format!("{}: {}", tokens_to_string(&entry[..colon]), tokens_to_string(&entry[colon + 1..])));
+                } else {
+                    // where Element == Int
+                    object.where_clauses.push(tokens_to_string(&entry));
+                }
+            }
+        }
+    }
+
+    // The members between the braces of a type or extension, and the cases of an enum
+    fn swift_type_body(&mut self, object: &mut Object) {
+        if !self.next_is(Token::BlockOpen('{')) {
+            return
+        }
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        self.advance(); // skip {
+
+        loop {
+            match self.current_token {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon => {
+                    self.advance();
+                },
+
+                _ => {
+                    let start = self.position();
+                    let modifiers = self.swift_modifiers();
+                    // members are internal unless they say otherwise, and the requirements of a protocol are as
+                    // visible as the protocol
+                    let access = match (modifiers.access, object.kind) {
+                        (Some(access), _) => access,
+                        (None, ObjectKind::Interface) if object.public => Access::Public,
+                        (None, _) => Access::Package,
+                    };
+
+                    match self.current_token.clone() {
+                        _ if self.is_swift_type() => {
+                            if let ASTNode::Object(nested) = self.swift_type(modifiers, access.is_public(), start) {
+                                object.objects.push(nested);
+                            }
+                        },
+                        Token::Identifier(id) => match id.as_str() {
+                            "case" => self.swift_cases(object),
+                            "func" | "init" | "deinit" => object.functions.extend(self.swift_function(modifiers, access, object.kind, start)),
+                            "var" | "let" => object.variables.extend(self.swift_properties(modifiers, access, object.kind, start)),
+                            // subscript(index: Int) -> T { ... }
+                            "subscript" => {
+                                self.advance();
+                                self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+                                self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_)));
+                                if self.next_is(Token::BlockOpen('{')) {
+                                    self.code_block();
+                                }
+                            },
+                            // typealias Id = Int and associatedtype Element
+                            "typealias" | "associatedtype" => {
+                                self.advance();
+                                self.until(Some(line_continues), true, |_| false);
+                            },
+                            _ => {
+                                if self.position() == start {
+                                    self.advance();
+                                }
+                            },
+                        },
+                        _ => {
+                            if self.position() == start {
+                                self.advance();
+                            }
+                        },
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        self.current_object = enclosing_object;
+    }
+
+    // case red, green = "g", custom(Int), the current token is case
+    fn swift_cases(&mut self, object: &mut Object) {
+        self.advance(); // skip case
+
+        loop {
+            let start = self.position();
+            let identifier = match self.current_token.name() {
+                Some(name) => name.to_string(),
+                None => break,
+            };
+            self.advance();
+
+            // associated values, custom(Int)
+            if self.current_token == Token::Parenthesis('(') {
+                self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+            }
+            let mut value = None;
+            if self.current_token == Token::Equals {
+                self.advance(); // skip =
+                value = Some(self.until(Some(line_continues), false, |token| *token == Token::Comma));
+            }
+            // cases are as visible as their enum
+            let access = if object.public { Access::Public } else { Access::Package };
+            object.variables.push(self.new_variable(identifier, VariableKind::Variant, access, &[], value, &object.identifier, self.span_from(start)));
+
+            if self.current_token != Token::Comma {
+                break
+            }
+            self.advance(); // skip ,
+            self.skip_layout();
+        }
+    }
+
+    // func name<T>(parameters) async throws -> ReturnType where T: Equatable { ... }, or init and deinit,
+    // the current token is func, init or deinit
+    fn swift_function(&mut self, modifiers: Modifiers, access: Access, kind: ObjectKind, start: (i64, usize)) -> Option<Function> {
+        let mut identifier = match self.current_token.to_string().as_str() {
+            "func" => None,
+            initialiser => Some(initialiser.to_string()),
+        };
+        self.advance();
+
+        if identifier.is_none() {
+            // operators have symbols for names, static func == (lhs: Self, rhs: Self) -> Bool
+            identifier = self.current_token.name().map(|name| name.to_string());
+            while !matches!(self.current_token, Token::Parenthesis('(') | Token::AngleBracket('<') | Token::BlockOpen(_) | Token::Eof) {
+                self.advance();
+            }
+        } else {
+            // init? and init!
+            while matches!(self.current_token, Token::Other('?') | Token::Other('!')) {
+                self.advance();
+            }
+        }
+        if self.current_token == Token::AngleBracket('<') {
+            self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'));
+        }
+
+        let parameters = self.parse_parenthesis(Token::Parenthesis('('), Token::Parenthesis(')'));
+        // async, throws, rethrows or throws(ParseError)
+        while matches!(&self.current_token, Token::Identifier(id) if matches!(id.as_str(), "async" | "throws" | "rethrows")) {
+            self.advance();
+            if self.current_token == Token::Parenthesis('(') {
+                self.parse_list(Token::Parenthesis('('), Token::Parenthesis(')'));
+            }
+        }
+        let mut return_type = Vec::new();
+        if self.current_token == Token::Arrow {
+            self.advance(); // skip ->
+            return_type = self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_)) || *token == Token::Identifier(String::from("where")));
+        }
+        if self.next_is(Token::Identifier(String::from("where"))) {
+            self.until(Some(line_continues), true, |token| matches!(token, Token::BlockOpen(_)));
+        }
+
+        let has_body = self.next_is(Token::BlockOpen('{'));
+        if has_body {
+            self.code_block();
+        }
+
+        Some(Function {
+            identifier: identifier?,
+            parameters,
+            return_type: tokens_to_string(&return_type),
+            access,
+            is_abstract: kind == ObjectKind::Interface && !has_body,
+            is_static: modifiers.is_static,
+            is_class_method: modifiers.is_class,
+            decorators: modifiers.decorators,
+            attributes: Vec::new(),
+            throws: Vec::new(),
+            filepath: self.current_file.filepath.clone(),
+            span: self.span_from(start),
+        })
+    }
+
+    // var a: Int = 1, b = 2 or var area: Double { get set }, the current token is var or let. Properties with
+    // a getter are computed, those that only observe changes with willSet and didSet are stored
+    fn swift_properties(&mut self, modifiers: Modifiers, access: Access, kind: ObjectKind, start: (i64, usize)) -> Vec<Variable> {
+        let constant = self.current_token == Token::Identifier(String::from("let"));
+        self.advance(); // skip var / let
+        let mut properties = Vec::new();
+
+        loop {
+            // let (a, b) = pair
+            let identifier = self.current_token.name().map(|name| name.to_string());
+            self.advance();
+
+            let mut vtype = Vec::new();
+            if self.current_token == Token::Colon {
+                self.advance(); // skip :
+                vtype = self.until(Some(line_continues), true, |token| matches!(token, Token::Equals | Token::BlockOpen(_) | Token::Comma));
+            }
+            let mut value = None;
+            let mut observed = false;
+            if self.current_token == Token::Equals {
+                self.advance(); // skip =
+                let mut tokens = self.until(Some(line_continues), false, |token| *token == Token::Comma);
+                // var layer = 0 { didSet { } }, the observers aren't part of the value
+                if let Some(open) = swift_observers(&tokens) {
+                    tokens.truncate(open);
+                    observed = true;
+                }
+                value = Some(tokens);
+            }
+
+            // { get set } in a protocol, a getter's body, or willSet and didSet
+            let mut computed = kind == ObjectKind::Interface;
+            let mut settable = !constant;
+            if matches!(self.current_token, Token::BlockOpen(_)) && !observed {
+                let accessors = self.swift_accessors();
+                computed = !accessors.contains(&String::from("willSet")) && !accessors.contains(&String::from("didSet"));
+                if computed {
+                    settable = accessors.contains(&String::from("set"));
+                }
+            }
+
+            if let Some(identifier) = identifier {
+                let variable_kind = match (modifiers.is_static || modifiers.is_class, computed) {
+                    (true, _) => VariableKind::ClassAttribute,
+                    (false, true) => VariableKind::Property,
+                    (false, false) => VariableKind::Field,
+                };
+                let mut variable = self.new_variable(identifier, variable_kind, access, &vtype, value, &self.current_object, self.span_from(start));
+                variable.readonly = !settable || modifiers.private_setter;
+                properties.push(variable);
+            }
+
+            if self.current_token != Token::Comma {
+                break
+            }
+            self.advance(); // skip ,
+            self.skip_layout();
+        }
+
+        // var x, y: Int, x is an Int too
+        for index in (1..properties.len()).rev() {
+            if properties[index - 1].vtype.is_none() && properties[index - 1].value.is_none() {
+                properties[index - 1].vtype = properties[index].vtype.clone();
+            }
+        }
+
+        properties
+    }
+
+    // The braces after a property, along with the names of the accessors in them. A getter can leave out get
+    fn swift_accessors(&mut self) -> Vec<String> {
+        let mut accessors = Vec::new();
+        let mut depth = 0;
+
+        self.advance(); // skip {
+        loop {
+            match &self.current_token {
+                Token::BlockClose if depth == 0 => break,
+                Token::Eof => break,
+                Token::BlockOpen(_) | Token::Parenthesis('(') | Token::Bracket('[') => depth += 1,
+                Token::BlockClose | Token::Parenthesis(')') | Token::Bracket(']') => depth -= 1,
+                Token::Identifier(id) if depth == 0 && matches!(id.as_str(), "get" | "set" | "willSet" | "didSet") => accessors.push(id.clone()),
+                _ => {},
+            }
+            self.advance();
+        }
+        self.advance(); // skip }
+
+        accessors
+    }
+}
+
+// Where the observers of a stored property start in what was read as its value, if they're in it
+fn swift_observers(tokens: &[Token]) -> Option<usize> {
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            Token::BlockOpen(_) if depth == 0 && matches!(tokens.get(index + 1), Some(Token::Identifier(id)) if id == "willSet" || id == "didSet") => return Some(index),
+            Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_) => depth += 1,
+            Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose => depth -= 1,
+            _ => {},
+        }
+    }
+
+    None
+}
+
+// The types an enum's raw values can have
+fn swift_raw_type(name: &str) -> bool {
+    matches!(name, "String" | "Character" | "Int" | "Int8" | "Int16" | "Int32" | "Int64" | "UInt" | "UInt8" | "UInt16" | "UInt32" | "UInt64" | "Double" | "Float")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Access;
+    use crate::testing::{methods, node, resolve};
+
+    #[test]
+    fn members_without_a_modifier_are_internal() {
+        let hierarchy = resolve(&[("Shapes/Square.swift", "public final class Square {
+    public var side: Double = 0
+    internal var name = \"\"
+    package var layer = 0
+    fileprivate var cache: [Int] = []
+    private var scale = 1.0
+    func area() -> Double { side * side }
+}
+")]);
+        let square = &node(&hierarchy, "Square").object;
+        let access: Vec<(&str, Access)> = square.variables.iter().map(|variable| (variable.identifier.as_str(), variable.access)).collect();
+
+        assert_eq!(access, [
+            ("side", Access::Public),
+            ("name", Access::Package),
+            ("layer", Access::Package),
+            ("cache", Access::Private),
+            ("scale", Access::Private),
+        ]);
+        assert_eq!(square.functions[0].access, Access::Package);
+    }
+
+    #[test]
+    fn strings_within_interpolations_dont_close_the_class_body() {
+        let hierarchy = resolve(&[("Shapes/Shape.swift", "final class Shape {
+    var sides: [Int] = []
+    var label: String { \"\\(sides.map { \"}\" })\" }
+
+    func area() -> Double { 0 }
+}
+")]);
+
+        assert_eq!(methods(&hierarchy, "Shape"), ["area"]);
+    }
+}
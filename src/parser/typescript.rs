@@ -0,0 +1,794 @@
+use super::{line_continues, split_top_level, Modifiers, Parser};
+use crate::structs::{tokens_to_string, ASTNode, Access, Function, Import, Object, ObjectKind, Severity, Token, VariableKind};
+
+// Typescript and javascript. Classes, interfaces, enums and type aliases of object shapes are objects,
+// and imports and re-exports are kept so the resolver can follow them to other files:
+//
+// import { Base } from './base';                          Base is whatever ./base exports as Base
+// export class Circle extends Base implements Shape { }  Circle, with the parents Base and Shape
+// constructor(private radius: number) { }                 the private field radius of Circle
+// type Point = Named & { x: number; y: number };          Point, an interface with the parent Named
+impl Parser {
+    // A top level declaration along with its modifiers, or an import or re-export. Anything else is skipped
+    pub(super) fn handle_typescript_declaration(&mut self) -> ASTNode {
+        let start = self.position();
+        match &self.current_token {
+            // import('./lazy') and import.meta are expressions
+            Token::Identifier(id) if id == "import" && !matches!(self.peek(), Token::Parenthesis('(') | Token::Period) => {
+                return self.typescript_import()
+            },
+            Token::Identifier(id) if id == "export" && matches!(self.peek(), Token::BlockOpen(_) | Token::Other('*')) => {
+                return self.typescript_export()
+            },
+            _ => {},
+        }
+
+        let modifiers = self.typescript_modifiers();
+        // without export, a declaration can only be used within its file
+        let public = modifiers.exported;
+        let default_export = modifiers.default_export;
+
+        let node = match &self.current_token {
+            // namespace Shapes { } and declare module 'lib' { }
+            Token::Identifier(id) if id == "namespace" && self.peek().name().is_some() => return self.handle_namespace(),
+            Token::ObjectDeclaration(id) if id == "module" && matches!(self.peek(), Token::Identifier(_) | Token::StringLiteral(_)) => {
+                return self.handle_namespace()
+            },
+            Token::ObjectDeclaration(id) if id == "type" && self.peek().name().is_some() => self.typescript_alias(modifiers, public, start),
+
+            // const Shape = class extends Base { }
+            Token::Identifier(id) if matches!(id.as_str(), "const" | "let" | "var") && self.peek().name().is_some() => {
+                self.advance();
+                let binding = self.current_token.to_string();
+                self.advance();
+                if self.current_token == Token::Equals
+                    && self.peek() == Token::ObjectDeclaration(String::from("class")) {
+                    self.advance();
+                    return self.typescript_type(modifiers, public, Some(binding), start)
+                }
+                return ASTNode::None
+            },
+
+            // export default Square;
+            Token::Identifier(id) if default_export
+                && (matches!(self.peek(), Token::Semicolon | Token::Eof) || self.pos + 1 >= self.current_line.len() as i64) => {
+                let name = id.clone();
+                self.advance();
+                self.typescript_imports(String::new(), vec![(name, Some(String::from("default")))], start);
+                return ASTNode::None
+            },
+
+            _ if self.is_typescript_type() => self.typescript_type(modifiers, public, None, start),
+            _ => {
+                if self.position() == start {
+                    self.advance();
+                }
+                return ASTNode::None
+            },
+        };
+
+        // export default class Square { } is imported as default
+        if default_export
+            && let ASTNode::Object(object) = &node {
+            self.typescript_imports(String::new(), vec![(object.identifier.clone(), Some(String::from("default")))], start);
+        }
+        node
+    }
+
+    // class, interface or enum followed by its name
+    fn is_typescript_type(&self) -> bool {
+        let keyword = matches!(&self.current_token, Token::ObjectDeclaration(keyword) if matches!(keyword.as_str(), "class" | "interface" | "enum"));
+        keyword && typescript_type_name(&self.peek())
+    }
+
+    // Decorators and modifiers, eg @Input() export abstract
+    fn typescript_modifiers(&mut self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+
+        loop {
+            match self.current_token.clone() {
+                Token::Other('@') => {
+                    self.advance(); // skip @
+
+                    modifiers.decorators.push(self.annotation_name());
+                    continue;
+                },
+
+                // a modifier followed by these is the name of a member, eg a method called get()
+                Token::Identifier(_) | Token::Publicity(_)
+                    if matches!(self.peek(), Token::Parenthesis('(') | Token::Colon | Token::Equals | Token::Semicolon
+                        | Token::Other('?') | Token::Other('!') | Token::AngleBracket('<')) => break,
+
+                Token::Publicity(public) => modifiers.access = Some(public.into()),
+                Token::Identifier(id) => match id.as_str() {
+                    "protected" => modifiers.access = Some(Access::Protected),
+                    "export" => modifiers.exported = true,
+                    "default" if modifiers.exported => modifiers.default_export = true,
+                    "static" => modifiers.is_static = true,
+                    "abstract" => modifiers.is_abstract = true,
+                    "readonly" => modifiers.readonly = true,
+                    // const enum
+                    "const" if self.peek() == Token::ObjectDeclaration(String::from("enum")) => {},
+                    "declare" | "override" | "async" | "accessor" => {},
+                    _ => break,
+                },
+
+                Token::Indent(_) | Token::Comment(_) => {},
+                _ => break,
+            }
+            self.advance();
+        }
+
+        modifiers
+    }
+
+    // class Name<T> extends Base implements A, B { ... }, interface Name extends A, B { ... } or enum Name { ... },
+    // the current token is the keyword. A class expression takes the name of what it's assigned to
+    fn typescript_type(&mut self, modifiers: Modifiers, public: bool, binding: Option<String>, start: (i64, usize)) -> ASTNode {
+        let keyword = self.current_token.to_string();
+        self.advance(); // skip class / interface / enum
+
+        let mut identifier = binding;
+        if typescript_type_name(&self.current_token) {
+            identifier = identifier.or(self.current_token.name().map(|name| name.to_string()));
+            self.advance();
+        }
+        let identifier = match identifier {
+            Some(identifier) => identifier,
+            None => return ASTNode::None,
+        };
+
+        let kind = ObjectKind::from_keyword(&keyword);
+        let mut object = self.blank_object(identifier, kind, public, start);
+        object.is_abstract = modifiers.is_abstract;
+        object.decorators = modifiers.decorators;
+        (object.generics, object.where_clauses) = self.typescript_generics();
+
+        loop {
+            match &self.current_token {
+                Token::Identifier(id) if id == "extends" || id == "implements" => {
+                    // an interface extends other interfaces
+                    let interfaces = id == "implements" || kind == ObjectKind::Interface;
+                    self.advance();
+                    let parents = split_top_level(&self.typescript_clause(), &Token::Comma);
+                    if interfaces {
+                        object.interfaces.extend(parents.iter().cloned());
+                    }
+                    object.parents.extend(parents);
+                },
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                },
+                _ => break,
+            }
+        }
+
+        if kind == ObjectKind::Enum {
+            for (tokens, span) in self.parse_spanned_list(Token::BlockOpen('{'), Token::BlockClose) {
+                if let Some((identifier, _, value)) = typescript_binding(&tokens) {
+                    object.variables.push(self.new_variable(identifier, VariableKind::Variant, public.into(), &[], value, &object.identifier, span));
+                }
+            }
+        } else {
+            self.typescript_type_body(&mut object);
+        }
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // type Name<T> = { ... }, or one intersected with other types, type Name = Base & { ... }. Aliases of
+    // anything else, eg unions, aren't objects
+    fn typescript_alias(&mut self, modifiers: Modifiers, public: bool, start: (i64, usize)) -> ASTNode {
+        self.advance(); // skip type
+        let identifier = self.current_token.to_string();
+        self.advance();
+
+        let mut object = self.blank_object(identifier, ObjectKind::Interface, public, start);
+        object.decorators = modifiers.decorators;
+        (object.generics, object.where_clauses) = self.typescript_generics();
+        if self.current_token != Token::Equals {
+            return ASTNode::None
+        }
+        self.advance(); // skip =
+
+        let mut shape = false;
+        let mut union = false;
+        self.skip_layout();
+        // type Shape =
+        //     | Circle
+        //     | Square
+        if matches!(self.current_token, Token::Other('|') | Token::Other('&')) {
+            union = self.current_token == Token::Other('|');
+            self.advance();
+        }
+
+        loop {
+            self.skip_layout();
+            if matches!(self.current_token, Token::BlockOpen(_)) {
+                self.typescript_type_body(&mut object);
+                shape = true;
+            } else {
+                let part = self.typescript_annotation(true);
+                if part.is_empty() {
+                    break
+                }
+                object.parents.push(tokens_to_string(&part));
+            }
+
+            self.skip_layout();
+            match self.current_token {
+                Token::Other('&') => {
+                    self.advance();
+                },
+                Token::Other('|') => {
+                    union = true;
+                    self.advance();
+                },
+                Token::Semicolon => {
+                    self.advance();
+                    break
+                },
+                _ => break,
+            }
+        }
+
+        if union || !shape {
+            return ASTNode::None
+        }
+        object.interfaces = object.parents.clone();
+        object.span = self.span_from(start);
+
+        ASTNode::Object(object)
+    }
+
+    // <T extends Shape = Circle, U> -> ["T", "U"], ["T extends Shape"]
+    fn typescript_generics(&mut self) -> (Vec<String>, Vec<String>) {
+        let mut generics = Vec::new();
+        let mut where_clauses = Vec::new();
+
+        for tokens in self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>')) {
+            // const T, and the variance of in T and out T
+            let skip = tokens.iter().take_while(|token| matches!(token, Token::Identifier(id) if matches!(id.as_str(), "const" | "in" | "out"))).count();
+            let tokens = if skip < tokens.len() { &tokens[skip..] } else { &tokens[..] };
+            let name = match tokens.first().and_then(|token| token.name()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            let default = tokens.iter().position(|token| *token == Token::Equals).unwrap_or(tokens.len());
+            if let Some(extends) = tokens[..default].iter().position(|token| *token == Token::Identifier(String::from("extends"))) {
+                where_clauses.push(format!("{} extends {}", name, tokens_to_string(&tokens[extends + 1..default])));
+            }
+            generics.push(name);
+        }
+
+        (generics, where_clauses)
+    }
+
+    // Tokens up to the body or the next clause of a declaration, eg the list after implements
+    fn typescript_clause(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            match &self.current_token {
+                Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                    continue;
+                },
+                // => is an arrow rather than a closing >
+                Token::Equals if self.peek() == Token::AngleBracket('>') => {
+                    tokens.push(self.current_token.clone());
+                    tokens.push(self.advance());
+                    self.advance();
+                    continue;
+                },
+
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::AngleBracket('>') => depth -= 1,
+                Token::BlockOpen(_) | Token::Semicolon if depth == 0 => break,
+                Token::Identifier(id) if depth == 0 && (id == "extends" || id == "implements") => break,
+                Token::BlockClose | Token::Eof => break,
+                _ => {},
+            }
+
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // The members between the braces of a class, interface or object type. Classes declared in the
+    // bodies of its methods are nested in it
+    fn typescript_type_body(&mut self, object: &mut Object) {
+        if !matches!(self.current_token, Token::BlockOpen(_)) {
+            return
+        }
+        let open = self.position();
+        let enclosing_object = std::mem::replace(&mut self.current_object, object.identifier.clone());
+        let enclosing_locals = std::mem::take(&mut self.local_objects);
+        self.advance(); // skip {
+
+        loop {
+            match &self.current_token {
+                Token::BlockClose => break,
+                Token::Eof => {
+                    self.diagnostic(Severity::Error, open, String::from("unclosed `{`"));
+                    break
+                },
+                Token::Indent(_) | Token::Comment(_) | Token::Semicolon | Token::Comma => {
+                    self.advance();
+                },
+
+                _ => {
+                    for node in self.typescript_member(object.kind) {
+                        match node {
+                            ASTNode::Variable(variable) => {
+                                // the getter and setter of a property are declared separately
+                                match object.variables.iter_mut().find(|existing| existing.identifier == variable.identifier && existing.kind == VariableKind::Property) {
+                                    Some(existing) if variable.kind == VariableKind::Property => {
+                                        existing.readonly = existing.readonly && variable.readonly;
+                                        existing.vtype = existing.vtype.take().or(variable.vtype);
+                                    },
+                                    _ => object.variables.push(variable),
+                                }
+                            },
+                            ASTNode::Function(function) => {
+                                // overload signatures come before the method that implements them
+                                match object.functions.iter().position(|existing| existing.identifier == function.identifier && existing.is_static == function.is_static) {
+                                    Some(overload) => object.functions[overload] = function,
+                                    None => object.functions.push(function),
+                                }
+                            },
+                            ASTNode::Object(nested) => object.objects.push(nested),
+                            _ => {},
+                        }
+                    }
+                },
+            }
+        }
+        self.advance(); // skip }
+
+        object.objects.append(&mut self.local_objects);
+        self.local_objects = enclosing_locals;
+        self.current_object = enclosing_object;
+    }
+
+    // A field, method, accessor or signature
+    fn typescript_member(&mut self, kind: ObjectKind) -> Vec<ASTNode> {
+        let start = self.position();
+        let modifiers = self.typescript_modifiers();
+        // members are public unless they say otherwise
+        let mut access = modifiers.access.unwrap_or(Access::Public);
+
+        // static { } initialisers
+        if matches!(self.current_token, Token::BlockOpen(_)) {
+            self.code_block();
+            return Vec::new()
+        }
+
+        // get area() and set area(value) declare the property area
+        let accessor = match &self.current_token {
+            Token::Identifier(id) if (id == "get" || id == "set") && typescript_member_name(&self.peek()) => Some(id == "set"),
+            _ => None,
+        };
+        if accessor.is_some() {
+            self.advance();
+        }
+        // *entries() is a generator
+        if self.current_token == Token::Other('*') {
+            self.advance();
+        }
+        // #count is private to the class
+        if self.current_token == Token::Other('#') {
+            access = Access::Private;
+            self.advance();
+        }
+
+        let identifier = match self.current_token.clone() {
+            // [key: string]: T and [Symbol.iterator]() have no name of their own
+            Token::Bracket('[') => {
+                self.parse_list(Token::Bracket('['), Token::Bracket(']'));
+                None
+            },
+            // the call signature of an interface, (x: number): string
+            Token::Parenthesis('(') => None,
+            Token::StringLiteral(name) | Token::Number(name) => {
+                self.advance();
+                Some(name)
+            },
+            Token::BlockClose | Token::Eof => return Vec::new(),
+            token => {
+                self.advance();
+                match token.name() {
+                    Some(name) => Some(name.to_string()),
+                    None => return Vec::new(),
+                }
+            },
+        };
+
+        // name? is optional, name! is assigned somewhere typescript can't see
+        while matches!(self.current_token, Token::Other('?') | Token::Other('!')) {
+            self.advance();
+        }
+        if self.current_token == Token::AngleBracket('<') {
+            self.parse_list(Token::AngleBracket('<'), Token::AngleBracket('>'));
+        }
+
+        if self.current_token == Token::Parenthesis('(') {
+            return self.typescript_method(identifier, accessor, modifiers, access, kind, start)
+        }
+
+        let mut vtype = Vec::new();
+        if self.current_token == Token::Colon {
+            self.advance(); // skip :
+            vtype = self.typescript_annotation(false);
+        }
+        let mut value = None;
+        if self.current_token == Token::Equals {
+            self.advance(); // skip =
+            value = Some(self.until(Some(line_continues), false, |token| *token == Token::Comma));
+        }
+        if matches!(self.current_token, Token::Semicolon | Token::Comma) {
+            self.advance();
+        }
+
+        let identifier = match identifier {
+            Some(identifier) => identifier,
+            None => return Vec::new(),
+        };
+        let variable_kind = if modifiers.is_static { VariableKind::ClassAttribute } else { VariableKind::Field };
+        let mut variable = self.new_variable(identifier, variable_kind, access, &vtype, value, &self.current_object, self.span_from(start));
+        variable.readonly = modifiers.readonly;
+
+        vec![ASTNode::Variable(variable)]
+    }
+
+    // name(parameters): ReturnType { ... }, or ; for a signature, the current token is the (
+    fn typescript_method(&mut self, identifier: Option<String>, accessor: Option<bool>, modifiers: Modifiers, access: Access, kind: ObjectKind, start: (i64, usize)) -> Vec<ASTNode> {
+        let constructor = identifier.as_deref() == Some("constructor");
+        let mut nodes = Vec::new();
+        let mut parameters = Vec::new();
+        let mut first_type = Vec::new();
+
+        for (index, (tokens, span)) in self.parse_spanned_list(Token::Parenthesis('('), Token::Parenthesis(')')).into_iter().enumerate() {
+            let (tokens, accessibility, readonly) = typescript_parameter(&tokens);
+            let binding = typescript_binding(&tokens);
+
+            // constructor(private readonly store: Store) declares the field store
+            if constructor
+                && (accessibility.is_some() || readonly)
+                && let Some((name, vtype, value)) = &binding {
+                let mut variable = self.new_variable(name.clone(), VariableKind::Field, accessibility.unwrap_or(Access::Public), vtype, value.clone(), &self.current_object, span);
+                variable.readonly = readonly;
+                nodes.push(ASTNode::Variable(variable));
+            }
+            if index == 0
+                && let Some((_, vtype, _)) = binding {
+                first_type = vtype;
+            }
+            parameters.extend(self.parse_parameter(&tokens));
+        }
+
+        let mut return_type = Vec::new();
+        if self.current_token == Token::Colon {
+            self.advance(); // skip :
+            return_type = self.typescript_annotation(false);
+        }
+
+        let has_body = matches!(self.current_token, Token::BlockOpen(_));
+        if has_body {
+            self.code_block();
+        } else if matches!(self.current_token, Token::Semicolon | Token::Comma) {
+            self.advance();
+        }
+
+        let identifier = match identifier {
+            Some(identifier) => identifier,
+            None => return nodes,
+        };
+
+        match accessor {
+            // a getter gives the property's type, a setter its parameter's
+            Some(settable) => {
+                let vtype = if settable { first_type } else { return_type };
+                let mut variable = self.new_variable(identifier, VariableKind::Property, access, &vtype, None, &self.current_object, self.span_from(start));
+                variable.readonly = !settable;
+                nodes.push(ASTNode::Variable(variable));
+            },
+            None => nodes.insert(0, ASTNode::Function(Function {
+                identifier,
+                parameters,
+                return_type: tokens_to_string(&return_type),
+                access,
+                is_abstract: modifiers.is_abstract || (kind == ObjectKind::Interface && !has_body),
+                is_static: modifiers.is_static,
+                is_class_method: false,
+                decorators: modifiers.decorators,
+                attributes: Vec::new(),
+                throws: Vec::new(),
+                filepath: self.current_file.filepath.clone(),
+                span: self.span_from(start),
+            })),
+        }
+
+        nodes
+    }
+
+    // A type after a colon, up to what follows it: a ; , = ) or }, a method's body, or the next statement
+    // when semicolons are left out. With operators it also stops at the & and | between the parts of a type
+    fn typescript_annotation(&mut self, operators: bool) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut depth = 0;
+        let mut line = self.position().0;
+
+        loop {
+            self.skip_layout();
+            if depth == 0 && !tokens.is_empty() && self.position().0 > line && !line_continues(&tokens, &self.current_token) {
+                break
+            }
+
+            match self.current_token {
+                // => is an arrow rather than a closing >
+                Token::Equals if self.peek() == Token::AngleBracket('>') => {
+                    tokens.push(self.current_token.clone());
+                    tokens.push(self.advance());
+                    line = self.position().0;
+                    self.advance();
+                    continue;
+                },
+
+                Token::Semicolon | Token::Comma | Token::Equals | Token::Eof if depth == 0 => break,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose | Token::AngleBracket('>') if depth == 0 => break,
+                Token::Other('&') | Token::Other('|') if depth == 0 && operators && !tokens.is_empty() => break,
+                // { a: number } is a type, the { after one is a body
+                Token::BlockOpen(_) if depth == 0 && (operators || !typescript_type_starts(&tokens)) => break,
+
+                Token::Parenthesis('(') | Token::Bracket('[') | Token::BlockOpen(_) | Token::AngleBracket('<') => depth += 1,
+                Token::Parenthesis(')') | Token::Bracket(']') | Token::BlockClose | Token::AngleBracket('>') => depth -= 1,
+                Token::Eof => break,
+                _ => {},
+            }
+
+            line = self.position().0;
+            tokens.push(self.current_token.clone());
+            self.advance();
+        }
+
+        tokens
+    }
+
+    // import Base, { Shape, Circle as C } from './shapes' | import * as shapes from './shapes' | import './polyfill'
+    fn typescript_import(&mut self) -> ASTNode {
+        let start = self.position();
+        self.advance(); // skip import
+
+        // (name, alias)
+        let mut items = Vec::new();
+        loop {
+            match self.current_token.clone() {
+                Token::StringLiteral(module) => {
+                    self.advance();
+                    self.typescript_imports(module, items, start);
+                    break
+                },
+
+                Token::BlockOpen(_) => items.extend(self.typescript_import_list()),
+                Token::Other('*') => {
+                    self.advance(); // skip *
+                    self.advance(); // skip as
+                    items.push((String::from("*"), Some(self.current_token.to_string())));
+                    self.advance();
+                },
+                Token::Identifier(ref id) if id == "from" => {
+                    self.advance();
+                },
+                // import type { Shape } from './shapes'
+                Token::ObjectDeclaration(ref id) if id == "type" && self.peek() != Token::Identifier(String::from("from")) => {
+                    self.advance();
+                },
+                Token::Comma | Token::Indent(_) | Token::Comment(_) => {
+                    self.advance();
+                },
+
+                // the default export
+                ref token if token.name().is_some() => {
+                    items.push((String::from("default"), Some(token.to_string())));
+                    self.advance();
+                },
+                _ => break,
+            }
+        }
+
+        if self.current_token == Token::Semicolon {
+            self.advance();
+        }
+        ASTNode::None
+    }
+
+    // export { Shape, Circle as C } from './shapes' | export * from './shapes' | export * as shapes from './shapes'
+    fn typescript_export(&mut self) -> ASTNode {
+        let start = self.position();
+        self.advance(); // skip export
+
+        let mut items = Vec::new();
+        if self.current_token == Token::Other('*') {
+            self.advance(); // skip *
+            let mut alias = None;
+            if self.current_token == Token::Identifier(String::from("as")) {
+                self.advance();
+                alias = Some(self.current_token.to_string());
+                self.advance();
+            }
+            items.push((String::from("*"), alias));
+        } else {
+            items = self.typescript_import_list();
+        }
+
+        self.skip_layout();
+        if self.current_token == Token::Identifier(String::from("from")) {
+            self.advance();
+            if let Token::StringLiteral(module) = self.current_token.clone() {
+                self.advance();
+                self.typescript_imports(module, items, start);
+            }
+        } else {
+            // export { Square as default } exports the file's own names
+            self.typescript_imports(String::new(), items, start);
+        }
+
+        if self.current_token == Token::Semicolon {
+            self.advance();
+        }
+        ASTNode::None
+    }
+
+    // { Shape, type Circle, default as Square } -> [("Shape", None), ("Circle", None), ("default", Some("Square"))]
+    fn typescript_import_list(&mut self) -> Vec<(String, Option<String>)> {
+        self.parse_list(Token::BlockOpen('{'), Token::BlockClose)
+            .into_iter()
+            .filter_map(|tokens| {
+                let tokens = match tokens.as_slice() {
+                    [Token::ObjectDeclaration(id), rest @ ..] if id == "type" && !rest.is_empty() => rest,
+                    tokens => tokens,
+                };
+                match tokens {
+                    [name, Token::Identifier(id), alias] if id == "as" => Some((name.to_string(), Some(alias.to_string()))),
+                    [name] => Some((name.to_string(), None)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn typescript_imports(&mut self, module: String, items: Vec<(String, Option<String>)>, start: (i64, usize)) {
+        for (name, alias) in items {
+            self.imports.push(Import {
+                module: module.clone(),
+                name: Some(name),
+                alias,
+                namespace: self.namespace(),
+                filepath: self.current_file.filepath.clone(),
+                span: self.span_from(start),
+            });
+        }
+    }
+
+    // class Local { } in a method body or field value, in which case the tokens moved over
+    pub(super) fn typescript_local_declaration(&mut self) -> Option<Vec<Token>> {
+        if !self.is_typescript_type() {
+            return None
+        }
+
+        let start = self.position();
+        if let ASTNode::Object(object) = self.typescript_type(Modifiers::default(), false, None, start) {
+            self.local_objects.push(object);
+        }
+        Some(Vec::new())
+    }
+}
+
+// The name after class or interface, class extends Base { } has none
+fn typescript_type_name(token: &Token) -> bool {
+    token.name().is_some() && !matches!(token, Token::Identifier(id) if id == "extends" || id == "implements")
+}
+
+// What can follow get and set when they start an accessor rather than name a method
+fn typescript_member_name(token: &Token) -> bool {
+    token.name().is_some() || matches!(token, Token::StringLiteral(_) | Token::Number(_) | Token::Bracket('[') | Token::Other('#'))
+}
+
+// Whether a { after these tokens starts an object type, as in { a: number } | null, rather than a body
+fn typescript_type_starts(tokens: &[Token]) -> bool {
+    tokens.is_empty()
+        || matches!(tokens.last(), Some(Token::Other('|') | Token::Other('&') | Token::Comma | Token::Colon))
+        || tokens.ends_with(&[Token::Equals, Token::AngleBracket('>')])
+}
+
+// A parameter without its decorators and modifiers, along with what the modifiers of a constructor's say:
+// @Inject() private readonly store: Store -> store: Store, Some(Access::Private), true
+fn typescript_parameter(tokens: &[Token]) -> (Vec<Token>, Option<Access>, bool) {
+    let mut parameter = Vec::new();
+    let mut access = None;
+    let mut readonly = false;
+    let mut tokens = tokens.iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        // a parameter called readonly
+        let modifier = parameter.is_empty() && !matches!(tokens.peek(), Some(Token::Colon | Token::Equals | Token::Other('?')) | None);
+
+        match token {
+            Token::Other('@') => {
+                tokens.next();
+                while tokens.next_if_eq(&&Token::Period).is_some() {
+                    tokens.next();
+                }
+                if tokens.next_if_eq(&&Token::Parenthesis('(')).is_some() {
+                    let mut depth = 0;
+                    for token in tokens.by_ref() {
+                        match token {
+                            Token::Parenthesis('(') => depth += 1,
+                            Token::Parenthesis(')') if depth == 0 => break,
+                            Token::Parenthesis(')') => depth -= 1,
+                            _ => {},
+                        }
+                    }
+                }
+            },
+            Token::Publicity(public) if modifier => access = Some((*public).into()),
+            Token::Identifier(id) if modifier && id == "protected" => access = Some(Access::Protected),
+            Token::Identifier(id) if modifier && id == "readonly" => readonly = true,
+            Token::Identifier(id) if modifier && id == "override" => {},
+            token => parameter.push(token.clone()),
+        }
+    }
+
+    (parameter, access, readonly)
+}
+
+// name?: Type = value -> ("name", [Type], Some([value]))
+fn typescript_binding(tokens: &[Token]) -> Option<(String, Vec<Token>, Option<Vec<Token>>)> {
+    // the default value, rather than the => of a function type
+    let equals = (0..tokens.len()).find(|index| tokens[*index] == Token::Equals && tokens.get(index + 1) != Some(&Token::AngleBracket('>')));
+    let (declaration, value) = match equals {
+        Some(equals) => (&tokens[..equals], Some(tokens[equals + 1..].to_vec())),
+        None => (tokens, None),
+    };
+    let (name, vtype) = match declaration.iter().position(|token| *token == Token::Colon) {
+        Some(colon) => (&declaration[..colon], declaration[colon + 1..].to_vec()),
+        None => (declaration, Vec::new()),
+    };
+
+    let name = name.iter().rev().find_map(|token| match token {
+        Token::StringLiteral(name) => Some(name.as_str()),
+        token => token.name(),
+    })?;
+
+    Some((name.to_string(), vtype, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{methods, resolve};
+
+    #[test]
+    fn regex_literals_dont_close_the_class_body() {
+        let hierarchy = resolve(&[("src/scanner.ts", "export class Scanner {
+    private closing = /\\}/g;
+    private opening = /[/{]/;
+
+    scan(text: string): number {
+        const half = text.length / 2 / 1;
+        return text.split(/\\{/).length;
+    }
+
+    reset(): void { }
+}
+")]);
+
+        assert_eq!(methods(&hierarchy, "Scanner"), ["scan", "reset"]);
+        assert_eq!(hierarchy.nodes.len(), 1);
+    }
+}
@@ -0,0 +1,299 @@
+use std::path::{Component, Path};
+
+use crate::scanner::language;
+use crate::structs::{visibility, Function, Hierarchy, ObjectKind, Variable};
+
+// Renders part of a hierarchy as a PlantUML class diagram, with a package per namespace or package the objects
+// are declared in, and otherwise per source file named after its path from the directory all the files share:
+//
+// @startuml
+// package models.base {
+//     abstract class "Base" as n0 {
+//         +name : str
+//         +greet(other : Base) : str
+//     }
+//     class "Child" as n1
+// }
+// n0 <|-- n1
+// n2 <.. n1 : include
+// n3 *-- n0                           a go struct embedding a type
+// @enduml
+pub struct PlantUml<'a> {
+    hierarchy: &'a Hierarchy,
+    hide_private: bool,
+    // directory every object's file is in, left off package names
+    common_directory: Vec<String>,
+}
+
+impl<'a> PlantUml<'a> {
+    pub fn new(hierarchy: &'a Hierarchy, hide_private: bool) -> PlantUml<'a> {
+        let mut common_directory: Option<Vec<String>> = None;
+        for node in &hierarchy.nodes {
+            let mut directory = path_segments(&node.object.filepath);
+            directory.pop(); // file name
+
+            common_directory = Some(match common_directory {
+                Some(common) => common
+                    .into_iter()
+                    .zip(directory)
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect(),
+                None => directory,
+            });
+        }
+
+        PlantUml {
+            hierarchy,
+            hide_private,
+            common_directory: common_directory.unwrap_or_default(),
+        }
+    }
+
+    pub fn render(&self, nodes: &[usize]) -> String {
+        let nodes: Vec<usize> = nodes
+            .iter()
+            .copied()
+            .filter(|index| !self.hide_private || self.hierarchy.nodes[*index].object.public)
+            .collect();
+
+        let mut uml = String::from("@startuml\n");
+        uml.push_str("hide empty members\n");
+
+        // packages in the order their objects first appear
+        let packages: Vec<String> = nodes.iter().map(|index| self.package(*index)).collect();
+        let mut names: Vec<&String> = Vec::new();
+        for package in &packages {
+            if !names.contains(&package) {
+                names.push(package);
+            }
+        }
+
+        for name in names {
+            uml.push_str(&format!("\npackage {} {{\n", name));
+            for (index, _) in nodes.iter().zip(&packages).filter(|(_, package)| *package == name) {
+                uml.push_str(&self.class(*index));
+            }
+            uml.push_str("}\n");
+        }
+
+        let mut external: Vec<&str> = Vec::new();
+        let mut relationships = String::new();
+        for index in &nodes {
+            let node = &self.hierarchy.nodes[*index];
+            for parent in node.parents.iter().filter(|parent| nodes.contains(parent)) {
+                let identifier = &self.hierarchy.nodes[*parent].object.identifier;
+                match self.hierarchy.mixin(*index, identifier) {
+                    Some(mixin) => relationships.push_str(&format!("n{} <.. n{} : {}\n", parent, index, mixin)),
+                    None if self.hierarchy.embeds(*index, identifier) => relationships.push_str(&format!("n{} *-- n{}\n", index, parent)),
+                    None => {
+                        let arrow = if self.hierarchy.implements(*index, *parent) { "<|.." } else { "<|--" };
+                        relationships.push_str(&format!("n{} {} n{}\n", parent, arrow, index));
+                    },
+                }
+            }
+
+            for parent in &node.unresolved_parents {
+                let position = match external.iter().position(|name| name == parent) {
+                    Some(position) => position,
+                    None => {
+                        external.push(parent);
+                        external.len() - 1
+                    },
+                };
+                match self.hierarchy.mixin(*index, parent) {
+                    Some(mixin) => relationships.push_str(&format!("x{} <.. n{} : {}\n", position, index, mixin)),
+                    None if self.hierarchy.embeds(*index, parent) => relationships.push_str(&format!("n{} *-- x{}\n", index, position)),
+                    None => {
+                        let arrow = if self.hierarchy.implements_unresolved(*index, parent) { "<|.." } else { "<|--" };
+                        relationships.push_str(&format!("x{} {} n{}\n", position, arrow, index));
+                    },
+                }
+            }
+        }
+
+        if !external.is_empty() {
+            uml.push('\n');
+        }
+        for (position, name) in external.iter().enumerate() {
+            uml.push_str(&format!("class \"{}\" as x{}\n", name, position));
+        }
+
+        if !relationships.is_empty() {
+            uml.push('\n');
+            uml.push_str(&relationships);
+        }
+        uml.push_str("@enduml\n");
+
+        uml
+    }
+
+    fn class(&self, index: usize) -> String {
+        let object = &self.hierarchy.nodes[index].object;
+        let declaration = match object.kind {
+            ObjectKind::Interface | ObjectKind::Trait => "interface",
+            ObjectKind::Enum => "enum",
+            _ if object.is_abstract => "abstract class",
+            _ => "class",
+        };
+
+        let mut name = object.identifier.clone();
+        if !object.generics.is_empty() {
+            name.push_str(&format!("<{}>", object.generics.join(", ")));
+        }
+
+        let mut class = format!("    {} \"{}\" as n{}", declaration, name, index);
+        match object.kind {
+            ObjectKind::Struct => class.push_str(" <<struct>>"),
+            ObjectKind::Trait => class.push_str(" <<trait>>"),
+            ObjectKind::Module => class.push_str(" <<module>>"),
+            ObjectKind::Impl => class.push_str(" <<impl>>"),
+            ObjectKind::Dataclass => class.push_str(" <<dataclass>>"),
+            ObjectKind::NamedTuple => class.push_str(" <<NamedTuple>>"),
+            ObjectKind::TypedDict => class.push_str(" <<TypedDict>>"),
+            ObjectKind::Data => class.push_str(" <<data>>"),
+            _ => {},
+        }
+
+        let mut members = Vec::new();
+        for variable in object.variables.iter().filter(|variable| !self.hide_private || variable.access.is_public()) {
+            members.push(field(variable));
+        }
+        for function in object.functions.iter().filter(|function| !self.hide_private || function.access.is_public()) {
+            members.push(method(function));
+        }
+
+        if members.is_empty() {
+            class.push('\n');
+            return class
+        }
+
+        class.push_str(" {\n");
+        for member in members {
+            class.push_str(&format!("        {}\n", member));
+        }
+        class.push_str("    }\n");
+
+        class
+    }
+
+    // The namespace or package the object at index is declared in, App\Models -> App.Models, otherwise its
+    // file, /repo/src/models/base.py -> models.base when every file is under /repo/src. Rust's modules are
+    // within the file, so they follow its path
+    fn package(&self, index: usize) -> String {
+        let object = &self.hierarchy.nodes[index].object;
+        let mut segments = Vec::new();
+        if object.namespace.is_empty() || language(Path::new(&object.filepath)) == Some("rust") {
+            segments = path_segments(&object.filepath);
+            if let Some(file) = segments.last_mut()
+                && let Some(stem) = Path::new(file.as_str()).file_stem() {
+                *file = stem.to_string_lossy().to_string();
+            }
+            segments.drain(..self.common_directory.len().min(segments.len()));
+        }
+        segments.extend(object.namespace.split(['.', ':', '\\']).filter(|segment| !segment.is_empty()).map(String::from));
+
+        let segments: Vec<String> = segments
+            .into_iter()
+            .map(|segment| segment.replace(|c: char| !c.is_alphanumeric() && c != '_', "_"))
+            .collect();
+
+        segments.join(".")
+    }
+}
+
+fn path_segments(filepath: &str) -> Vec<String> {
+    Path::new(filepath)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+// +name : str
+fn field(variable: &Variable) -> String {
+    match variable.type_string() {
+        Some(vtype) if !vtype.is_empty() => format!("{}{} : {}", visibility(variable.access), variable.identifier, vtype),
+        _ => format!("{}{}", visibility(variable.access), variable.identifier),
+    }
+}
+
+// +greet(other : Base) : str, {static} +create() : Base
+fn method(function: &Function) -> String {
+    let arguments: Vec<String> = function.arguments()
+        .iter()
+        .map(|parameter| match &parameter.ptype {
+            Some(ptype) => format!("{} : {}", parameter.identifier, ptype),
+            None => parameter.identifier.clone(),
+        })
+        .collect();
+
+    let mut method = format!("{}{}({})", visibility(function.access), function.identifier, arguments.join(", "));
+    if !function.return_type.is_empty() {
+        method.push_str(&format!(" : {}", function.return_type));
+    }
+    if function.is_static || function.is_class_method {
+        method.insert_str(0, "{static} ");
+    } else if function.is_abstract {
+        method.insert_str(0, "{abstract} ");
+    }
+
+    method
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlantUml;
+    use crate::testing::resolve;
+
+    const SHAPE: &str = "package com.example.shapes;
+
+public class Shape extends Base implements Serializable {
+    private int sides;
+    protected String name;
+    int corners;
+
+    public double area() { return 0; }
+}
+";
+
+    #[test]
+    fn members_are_drawn_with_their_access() {
+        let hierarchy = resolve(&[("com/example/shapes/Shape.java", SHAPE)]);
+        let uml = PlantUml::new(&hierarchy, false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        for member in ["-sides : int", "#name : String", "~corners : int", "+area() : double"] {
+            assert!(uml.contains(member), "{}", uml);
+        }
+    }
+
+    #[test]
+    fn packages_are_named_after_the_declared_package() {
+        let hierarchy = resolve(&[("src/com/example/shapes/Shape.java", SHAPE)]);
+        let uml = PlantUml::new(&hierarchy, false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        assert!(uml.contains("package com.example.shapes {\n"), "{}", uml);
+    }
+
+    #[test]
+    fn external_interfaces_are_drawn_as_realisations() {
+        let hierarchy = resolve(&[("com/example/shapes/Shape.java", SHAPE)]);
+        let uml = PlantUml::new(&hierarchy, false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        assert!(uml.contains("class \"Base\" as x0\n"), "{}", uml);
+        assert!(uml.contains("x0 <|-- n0\n"), "{}", uml);
+        assert!(uml.contains("x1 <|.. n0\n"), "{}", uml);
+    }
+
+    #[test]
+    fn embedded_go_structs_are_composition() {
+        let hierarchy = resolve(&[("shapes/square.go", "package shapes\n\ntype Base struct{}\n\ntype Square struct {\n    Base\n}\n")]);
+        let base = hierarchy.find("Base").unwrap();
+        let square = hierarchy.find("Square").unwrap();
+        let uml = PlantUml::new(&hierarchy, false).render(&hierarchy.descendants(&hierarchy.roots(), None));
+
+        assert!(uml.contains(&format!("n{} *-- n{}\n", square, base)), "{}", uml);
+    }
+}
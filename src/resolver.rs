@@ -0,0 +1,1081 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::modules::{CrateMap, EsModuleMap, ModuleMap};
+use crate::structs::{Diagnostic, Function, Hierarchy, HierarchyNode, Implementation, Import, Object, ObjectKind, ParsedModel, Severity};
+
+// How many use declarations and re-exports are followed before giving up on a rust path
+const MAX_RUST_DEPTH: usize = 16;
+// and how many re-exports on an ES import
+const MAX_ES_DEPTH: usize = 16;
+// Ruby's core classes and modules, which a class of the same name reopens
+const RUBY_CORE: [&str; 20] = [
+    "Array", "BasicObject", "Comparable", "Enumerable", "Exception", "FalseClass", "Float", "Hash", "Integer", "Kernel",
+    "Module", "NilClass", "Numeric", "Object", "Proc", "Range", "String", "Struct", "Symbol", "TrueClass",
+];
+
+pub struct Resolver {
+    objects: Vec<Object>,
+    // where each of an object's parents was written, rust impl blocks bring in parents from other files
+    scopes: Vec<Vec<Scope>>,
+    imports: Vec<Import>,
+    python_modules: ModuleMap,
+    rust_modules: CrateMap,
+    // crate::parser::Parser -> index into objects
+    rust_items: HashMap<RustPath, usize>,
+    // module -> indices into imports of its use declarations
+    rust_imports: HashMap<RustPath, Vec<usize>>,
+    // modules declared inline with mod name { }
+    rust_namespaces: HashSet<RustPath>,
+    es_modules: EsModuleMap,
+    // filepath -> language, for the languages whose members can be defined outside of their class
+    file_types: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Scope {
+    filepath: String,
+    namespace: String,
+}
+
+impl Scope {
+    fn of(object: &Object) -> Scope {
+        Scope {
+            filepath: object.filepath.clone(),
+            namespace: object.namespace.clone(),
+        }
+    }
+}
+
+// A path within a crate, which is known by its root file. Paths into crates we haven't read have no root
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RustPath {
+    krate: String,
+    segments: Vec<String>,
+}
+
+impl RustPath {
+    fn join(&self, segments: &[String]) -> RustPath {
+        let mut path = self.clone();
+        path.segments.extend(segments.iter().cloned());
+        path
+    }
+}
+
+// What a parent name was matched to
+enum Resolution {
+    Found(usize),
+    Ambiguous(Vec<usize>),
+    NotFound,
+}
+
+impl Resolver {
+    pub fn new(model: &ParsedModel) -> Resolver {
+        let files = |language: &str| -> Vec<&str> {
+            model.files
+                .iter()
+                .filter(|(_, file_type)| file_type == language)
+                .map(|(filepath, _)| filepath.as_str())
+                .collect()
+        };
+
+        let mut resolver = Resolver {
+            objects: Vec::new(),
+            scopes: Vec::new(),
+            imports: model.imports.clone(),
+            python_modules: ModuleMap::python(&files("python")),
+            rust_modules: CrateMap::rust(&files("rust"), &model.modules),
+            rust_items: HashMap::new(),
+            rust_imports: HashMap::new(),
+            rust_namespaces: HashSet::new(),
+            es_modules: EsModuleMap::new(&[files("typescript"), files("javascript")].concat()),
+            file_types: model.files.iter().cloned().collect(),
+        };
+
+        for object in model.all_objects() {
+            resolver.scopes.push(vec![Scope::of(object); object.parents.len()]);
+            resolver.objects.push(object.clone());
+        }
+
+        resolver.index_rust();
+        resolver.merge_impls();
+        resolver.merge_partials();
+        resolver.satisfy_interfaces();
+        resolver.index_rust();
+
+        resolver
+    }
+
+    pub fn resolve(&self) -> Hierarchy {
+        let mut declarations: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            if !self.is_external(index) {
+                declarations.entry(object.identifier.as_str()).or_default().push(index);
+            }
+        }
+
+        let mut nodes: Vec<HierarchyNode> = self.objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| HierarchyNode {
+                object: object.clone(),
+                parents: Vec::new(),
+                children: Vec::new(),
+                unresolved_parents: Vec::new(),
+                ambiguous_parents: Vec::new(),
+                external: self.is_external(index),
+            })
+            .collect();
+        let mut diagnostics = Vec::new();
+
+        for (index, object) in self.objects.iter().enumerate() {
+            if nodes[index].external {
+                continue;
+            }
+            for (parent, scope) in object.parents.iter().zip(&self.scopes[index]) {
+                let resolution = if self.rust_module(scope).is_some() {
+                    self.find_rust_parent(index, parent, scope)
+                } else if self.es_modules.contains(&scope.filepath) {
+                    self.find_es_parent(index, parent, &declarations)
+                } else if self.file_types.get(&scope.filepath).is_some_and(|language| language == "php") {
+                    self.find_php_parent(index, parent, scope)
+                } else {
+                    self.find_parent(index, parent, &declarations)
+                };
+
+                match resolution {
+                    Resolution::Found(parent_index) => {
+                        let found = &self.objects[parent_index];
+                        let node = &mut nodes[index].object;
+                        match node.keywords.iter_mut().find(|(keyword, name)| keyword == "use" && name == parent) {
+                            // a php trait is used rather than implemented, under the name it was imported as
+                            Some((_, name)) => *name = found.identifier.clone(),
+                            // what the parent turned out to be settles whether it's an interface, which c# can't always say
+                            None => {
+                                let is_interface = found.kind.is_interface();
+                                let interfaces = &mut node.interfaces;
+                                match interfaces.iter().position(|interface| interface == parent) {
+                                    Some(position) if !is_interface => {
+                                        interfaces.remove(position);
+                                    },
+                                    None if is_interface => interfaces.push(parent.clone()),
+                                    _ => {},
+                                }
+                            },
+                        }
+
+                        // the same parent under two names, eg Named and shapes.Named in go, is one edge
+                        if !nodes[index].parents.contains(&parent_index) {
+                            nodes[index].parents.push(parent_index);
+                            nodes[parent_index].children.push(index);
+                        }
+                    },
+                    Resolution::Ambiguous(candidates) => {
+                        let files: Vec<&str> = candidates
+                            .iter()
+                            .map(|candidate| self.objects[*candidate].filepath.as_str())
+                            .collect();
+                        diagnostics.push(Diagnostic {
+                            filepath: object.filepath.clone(),
+                            line: object.span.start_line,
+                            column: object.span.start_column,
+                            length: 1,
+                            severity: Severity::Warning,
+                            message: format!("`{}` could be the parent of `{}` declared in any of {}", parent, object.identifier, files.join(", ")),
+                        });
+
+                        nodes[index].unresolved_parents.push(parent.clone());
+                        nodes[index].ambiguous_parents.push(parent.clone());
+                    },
+                    Resolution::NotFound => nodes[index].unresolved_parents.push(parent.clone()),
+                }
+            }
+        }
+
+        Hierarchy {
+            nodes,
+            diagnostics,
+        }
+    }
+
+    // Impls and extensions left after merge_impls are for types that weren't read, as are top level ruby
+    // classes named after one of ruby's own
+    fn is_external(&self, index: usize) -> bool {
+        let object = &self.objects[index];
+        let ruby = self.file_types.get(&object.filepath).is_some_and(|language| language == "ruby");
+
+        object.kind == ObjectKind::Impl || (ruby && object.namespace.is_empty() && RUBY_CORE.contains(&object.identifier.as_str()))
+    }
+
+    // Matches a parent name to a declared object. Imports decide where they can, otherwise an object
+    // from the same file is preferred, then one declared anywhere. Names declared in several other
+    // files are ambiguous rather than guessed, and those in another language are never matched.
+    fn find_parent(&self, index: usize, parent: &str, declarations: &HashMap<&str, Vec<usize>>) -> Resolution {
+        let object = &self.objects[index];
+        let language = self.file_types.get(&object.filepath);
+        let candidates: Vec<usize> = match declarations.get(self.imported_name(&object.filepath, parent)) {
+            Some(candidates) => candidates
+                .iter()
+                .copied()
+                .filter(|candidate| *candidate != index && self.file_types.get(&self.objects[*candidate].filepath) == language)
+                .collect(),
+            None => return Resolution::NotFound,
+        };
+
+        let same_file = candidates
+            .iter()
+            .copied()
+            .find(|candidate| self.objects[*candidate].filepath == object.filepath);
+        // a name declared in the file itself shadows anything imported
+        if let Some(candidate) = same_file
+            && !parent.contains('.') {
+            return Resolution::Found(candidate)
+        }
+
+        if let Some(files) = self.imported_files(&object.filepath, parent) {
+            let imported: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|candidate| files.contains(&self.objects[*candidate].filepath.as_str()))
+                .collect();
+
+            match imported.as_slice() {
+                [candidate] => return Resolution::Found(*candidate),
+                [] => {},
+                _ => return Resolution::Ambiguous(imported),
+            }
+        }
+
+        match (same_file, candidates.as_slice()) {
+            (Some(candidate), _) => Resolution::Found(candidate),
+            (None, [candidate]) => Resolution::Found(*candidate),
+            (None, []) => Resolution::NotFound,
+            (None, _) => Resolution::Ambiguous(candidates),
+        }
+    }
+
+    // Rust parents are found by their path, through use declarations. Those that lead out of the
+    // crates read, eg std::fmt::Display, aren't matched to objects by name
+    fn find_rust_parent(&self, index: usize, parent: &str, scope: &Scope) -> Resolution {
+        match self.rust_lookup(scope, parent) {
+            Some(found) if found != index => Resolution::Found(found),
+            _ => Resolution::NotFound,
+        }
+    }
+
+    // ES parents are found through the imports of the file they're used in, following re-exports such as
+    // export * from './base' in an index.ts. Names that aren't imported are found as python's are, eg
+    // classes declared in the same file or globals from another script
+    fn find_es_parent(&self, index: usize, parent: &str, declarations: &HashMap<&str, Vec<usize>>) -> Resolution {
+        let object = &self.objects[index];
+        let end = parent.find('<').unwrap_or(parent.len());
+        let segments: Vec<&str> = parent[..end].trim().split('.').collect();
+
+        let import = self.imports
+            .iter()
+            .find(|import| import.filepath == object.filepath && import.binding() == segments[0]);
+        match import {
+            Some(import) => match self.es_import(import, &segments[1..], 0) {
+                Some(found) if found != index => Resolution::Found(found),
+                _ => Resolution::NotFound,
+            },
+            None => self.find_parent(index, parent, declarations),
+        }
+    }
+
+    // PHP parents are qualified as php does it, \App\Models\Base is absolute, Base after use App\Models\Base or
+    // Model after use App\Models\Base as Model is what's imported, otherwise the name is in the file's own
+    // namespace. Class names aren't case sensitive
+    fn find_php_parent(&self, index: usize, parent: &str, scope: &Scope) -> Resolution {
+        let qualified = match parent.strip_prefix('\\') {
+            Some(absolute) => absolute.to_string(),
+            None => {
+                let (first, rest) = match parent.split_once('\\') {
+                    Some((first, rest)) => (first, Some(rest)),
+                    None => (parent, None),
+                };
+                let import = self.imports
+                    .iter()
+                    .find(|import| import.filepath == scope.filepath && import.namespace == scope.namespace && import.binding().eq_ignore_ascii_case(first));
+
+                let base = match import {
+                    Some(import) => [import.module.as_str(), import.name.as_deref().unwrap_or_default()].join("\\"),
+                    None if scope.namespace.is_empty() => first.to_string(),
+                    None => format!("{}\\{}", scope.namespace, first),
+                };
+                match rest {
+                    Some(rest) => format!("{}\\{}", base, rest),
+                    None => base,
+                }
+            },
+        };
+        let (namespace, name) = qualified.trim_start_matches('\\').rsplit_once('\\').unwrap_or(("", &qualified));
+
+        let candidates: Vec<usize> = self.objects
+            .iter()
+            .enumerate()
+            .filter(|(candidate, object)| {
+                *candidate != index
+                    && object.identifier.eq_ignore_ascii_case(name)
+                    && object.namespace.eq_ignore_ascii_case(namespace)
+                    && self.file_types.get(&object.filepath).is_some_and(|language| language == "php")
+            })
+            .map(|(candidate, _)| candidate)
+            .collect();
+
+        match candidates.as_slice() {
+            [candidate] => Resolution::Found(*candidate),
+            [] => Resolution::NotFound,
+            _ => Resolution::Ambiguous(candidates),
+        }
+    }
+
+    // The object an import brings in, or for import * as shapes, the one named by the rest of the path
+    fn es_import(&self, import: &Import, rest: &[&str], depth: usize) -> Option<usize> {
+        // export default Base exports one of the file's own names
+        let file = if import.module.is_empty() {
+            import.filepath.as_str()
+        } else {
+            self.es_modules.file(&import.filepath, &import.module)?
+        };
+
+        match import.name.as_deref()? {
+            "*" => self.es_export(file, rest, depth + 1),
+            name => self.es_export(file, &[&[name], rest].concat(), depth + 1),
+        }
+    }
+
+    // The object a file exports by a path, declared in it or re-exported from another file, eg Base or
+    // shapes.Base after export * as shapes from './shapes'
+    fn es_export(&self, file: &str, path: &[&str], depth: usize) -> Option<usize> {
+        let (name, rest) = path.split_first()?;
+        if depth > MAX_ES_DEPTH {
+            return None
+        }
+        if rest.is_empty()
+            && let Some(index) = self.objects.iter().position(|object| object.filepath == file && object.identifier == *name) {
+            return Some(index)
+        }
+
+        let imports = self.imports.iter().filter(|import| import.filepath == file);
+        // export { Base } from './base'
+        if let Some(import) = imports.clone().find(|import| import.binding() == *name) {
+            return self.es_import(import, rest, depth + 1)
+        }
+        // export * from './base'
+        imports
+            .filter(|import| import.name.as_deref() == Some("*") && import.alias.is_none())
+            .find_map(|import| self.es_export(self.es_modules.file(&import.filepath, &import.module)?, path, depth + 1))
+    }
+
+    // Where rust items, use declarations and inline modules are
+    fn index_rust(&mut self) {
+        let mut items = HashMap::new();
+        let mut namespaces = HashSet::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            let module = match self.rust_module(&Scope::of(object)) {
+                Some(module) => module,
+                None => continue,
+            };
+
+            // mod a { mod b { } } declares a and a::b
+            let depth = object.namespace.split("::").filter(|segment| !segment.is_empty()).count();
+            for parent in 0..depth {
+                namespaces.insert(RustPath {
+                    krate: module.krate.clone(),
+                    segments: module.segments[..module.segments.len() - parent].to_vec(),
+                });
+            }
+
+            if object.kind != ObjectKind::Impl {
+                items.entry(module.join(std::slice::from_ref(&object.identifier))).or_insert(index);
+            }
+        }
+
+        let mut imports: HashMap<RustPath, Vec<usize>> = HashMap::new();
+        for (index, import) in self.imports.iter().enumerate() {
+            let scope = Scope {
+                filepath: import.filepath.clone(),
+                namespace: import.namespace.clone(),
+            };
+            if let Some(module) = self.rust_module(&scope) {
+                imports.entry(module).or_default().push(index);
+            }
+        }
+
+        self.rust_items = items;
+        self.rust_imports = imports;
+        self.rust_namespaces = namespaces;
+    }
+
+    // Moves the methods and traits of rust impl blocks, c++ members defined outside of their class, kotlin
+    // and swift extensions, and go methods onto the type they're for. Impls for types from elsewhere, eg impl Display for Vec<T>, are left as external objects
+    fn merge_impls(&mut self) {
+        let mut merged = HashSet::new();
+
+        for index in 0..self.objects.len() {
+            if self.objects[index].kind != ObjectKind::Impl {
+                continue;
+            }
+            let language = self.file_types.get(&self.objects[index].filepath).map(|language| language.as_str());
+            let cpp = language == Some("cpp");
+            let target = match language {
+                Some("cpp" | "kotlin" | "swift" | "go") => self.defined_class(index),
+                _ => self.rust_lookup(&Scope::of(&self.objects[index]), &self.objects[index].identifier),
+            };
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+            if cpp {
+                self.drop_declared(index, target);
+            }
+
+            self.merge_piece(index, target);
+            merged.insert(index);
+        }
+
+        self.remove_objects(&merged);
+    }
+
+    // The class the c++ definitions, kotlin extensions, swift extension or go methods at index are for, Shape
+    // for double Shape::area() { }, fun Shape.scale(), extension Shape: Drawable { } or func (s Shape) Area(). A c++ class is looked for in the namespaces around its definitions from the
+    // innermost out, then a class is looked for in the same file, the header beside it, shape.h for shape.cpp,
+    // and the same directory, a kotlin or go package. Otherwise it's the only class of that name
+    fn defined_class(&self, index: usize) -> Option<usize> {
+        let piece = &self.objects[index];
+        let language = self.file_types.get(&piece.filepath);
+        let segments: Vec<&str> = piece.identifier.split("::").collect();
+        let (name, qualifier) = segments.split_last()?;
+        let candidates: Vec<usize> = (0..self.objects.len())
+            .filter(|candidate| {
+                let object = &self.objects[*candidate];
+                object.kind != ObjectKind::Impl && object.identifier == *name && self.file_types.get(&object.filepath) == language
+            })
+            .collect();
+
+        if language.is_some_and(|language| language == "cpp") {
+            let mut namespace: Vec<&str> = piece.namespace.split("::").filter(|segment| !segment.is_empty()).collect();
+            loop {
+                let qualified = [namespace.as_slice(), qualifier].concat().join("::");
+                if let Some(candidate) = candidates.iter().find(|candidate| self.objects[**candidate].namespace == qualified) {
+                    return Some(*candidate)
+                }
+                if namespace.pop().is_none() {
+                    break
+                }
+            }
+        }
+
+        let stem = |filepath: &str| Path::new(filepath).with_extension("");
+        let directory = |filepath: &str| Path::new(filepath).parent().map(|parent| parent.to_path_buf());
+        let nearby: [&dyn Fn(&Object) -> bool; 3] = [
+            &|object| object.filepath == piece.filepath,
+            &|object| stem(&object.filepath) == stem(&piece.filepath),
+            &|object| directory(&object.filepath) == directory(&piece.filepath),
+        ];
+        for near in nearby {
+            let found: Vec<usize> = candidates.iter().copied().filter(|candidate| near(&self.objects[*candidate])).collect();
+            if let [target] = found.as_slice() {
+                return Some(*target)
+            }
+        }
+
+        match candidates.as_slice() {
+            [target] => Some(*target),
+            _ => None,
+        }
+    }
+
+    // C++ members defined outside of their class are declared in it too, the definition of a static variable
+    // gives the declaration its value. Ruby methods and fields redeclared in a reopened class are dropped the same way
+    fn drop_declared(&mut self, index: usize, target: usize) {
+        let declared: HashSet<String> = self.objects[target].functions.iter().map(|function| function.identifier.clone()).collect();
+        self.objects[index].functions.retain(|function| !declared.contains(&function.identifier));
+
+        for variable in std::mem::take(&mut self.objects[index].variables) {
+            match self.objects[target].variables.iter_mut().find(|existing| existing.identifier == variable.identifier) {
+                Some(existing) => {
+                    if existing.value.is_none() {
+                        existing.value = variable.value;
+                    }
+                },
+                None => self.objects[index].variables.push(variable),
+            }
+        }
+    }
+
+    // Go types satisfy an interface by having its methods rather than by naming it, so each interface whose
+    // methods a type has, its own or those of what it embeds, is made one of its parents. Interfaces that embed
+    // one from outside of what was read, eg io.Closer, can't be checked. Pointer and value receivers aren't told apart
+    fn satisfy_interfaces(&mut self) {
+        let types: Vec<usize> = (0..self.objects.len())
+            .filter(|index| self.file_types.get(&self.objects[*index].filepath).is_some_and(|language| language == "go"))
+            .collect();
+        let method_sets: Vec<(HashSet<String>, bool)> = types.iter().map(|index| self.method_set(*index, &mut HashSet::new())).collect();
+
+        for (position, index) in types.iter().enumerate() {
+            if self.objects[*index].kind.is_interface() {
+                continue;
+            }
+
+            for (other, interface) in types.iter().enumerate() {
+                let (methods, complete) = &method_sets[other];
+                if !self.objects[*interface].kind.is_interface() || !complete || methods.is_empty() || !methods.is_subset(&method_sets[position].0) {
+                    continue;
+                }
+
+                let name = self.objects[*interface].identifier.clone();
+                let scope = Scope::of(&self.objects[*index]);
+                let object = &mut self.objects[*index];
+                if !object.parents.contains(&name) {
+                    object.parents.push(name.clone());
+                    object.interfaces.push(name);
+                    self.scopes[*index].push(scope);
+                }
+            }
+        }
+    }
+
+    // The signatures of the methods of the go type at index, along with those of the types it embeds, and
+    // whether all of those types were found
+    fn method_set(&self, index: usize, visited: &mut HashSet<usize>) -> (HashSet<String>, bool) {
+        let object = &self.objects[index];
+        let mut methods: HashSet<String> = object.functions.iter().map(go_signature).collect();
+        let mut complete = true;
+        if !visited.insert(index) {
+            return (methods, complete)
+        }
+
+        for (keyword, embedded) in &object.keywords {
+            if keyword != "embedded" {
+                continue;
+            }
+            // a type from the same package, otherwise the only one of that name
+            let name = base_name(embedded);
+            let candidates: Vec<usize> = (0..self.objects.len())
+                .filter(|candidate| {
+                    let other = &self.objects[*candidate];
+                    other.identifier == name && other.kind != ObjectKind::Impl && self.file_types.get(&other.filepath) == self.file_types.get(&object.filepath)
+                })
+                .collect();
+            let directory = |filepath: &str| Path::new(filepath).parent().map(|parent| parent.to_path_buf());
+            let nearby: Vec<usize> = candidates.iter().copied().filter(|candidate| directory(&self.objects[*candidate].filepath) == directory(&object.filepath)).collect();
+
+            match (nearby.as_slice(), candidates.as_slice()) {
+                ([target], _) | ([], [target]) => {
+                    let (embedded_methods, embedded_complete) = self.method_set(*target, visited);
+                    methods.extend(embedded_methods);
+                    complete = complete && embedded_complete;
+                },
+                _ => complete = false,
+            }
+        }
+
+        (methods, complete)
+    }
+
+    // Merges the pieces of c# partial classes, and ruby classes and modules reopened after they're declared,
+    // which share a language, a namespace and a name. A reopened ruby class can redefine methods it already has
+    fn merge_partials(&mut self) {
+        let mut first: HashMap<(String, String, String), usize> = HashMap::new();
+        let mut merged = HashSet::new();
+
+        for index in 0..self.objects.len() {
+            let language = self.file_types.get(&self.objects[index].filepath).cloned().unwrap_or_default();
+            let ruby = language == "ruby";
+            if !self.objects[index].partial && !ruby {
+                continue;
+            }
+
+            let key = (language, self.objects[index].namespace.clone(), self.objects[index].identifier.clone());
+            match first.get(&key) {
+                Some(target) => {
+                    if ruby {
+                        self.drop_declared(index, *target);
+                    }
+                    self.merge_piece(index, *target);
+                    merged.insert(index);
+                },
+                None => {
+                    first.insert(key, index);
+                },
+            }
+        }
+
+        self.remove_objects(&merged);
+    }
+
+    // Moves what the piece at index declares onto target, its header is kept in target's implementations
+    fn merge_piece(&mut self, index: usize, target: usize) {
+        let piece = self.objects[index].clone();
+        let scopes = self.scopes[index].clone();
+        let object = &mut self.objects[target];
+
+        object.implementations.push(Implementation {
+            trait_name: if piece.kind == ObjectKind::Impl { piece.parents.first().cloned() } else { None },
+            attributes: piece.attributes.clone(),
+            generics: piece.generics,
+            where_clauses: piece.where_clauses.clone(),
+            filepath: piece.filepath,
+            span: piece.span,
+        });
+
+        // each piece of a partial class says something about the whole of it, unlike an impl's #[cfg(test)]
+        if piece.kind != ObjectKind::Impl {
+            object.is_abstract = object.is_abstract || piece.is_abstract;
+            object.public = object.public || piece.public;
+            for attribute in piece.attributes {
+                if !object.attributes.contains(&attribute) {
+                    object.attributes.push(attribute);
+                }
+            }
+            for where_clause in piece.where_clauses {
+                if !object.where_clauses.contains(&where_clause) {
+                    object.where_clauses.push(where_clause);
+                }
+            }
+        }
+
+        for (parent, scope) in piece.parents.into_iter().zip(scopes) {
+            if !object.parents.contains(&parent) {
+                object.parents.push(parent);
+                self.scopes[target].push(scope);
+            }
+        }
+        for interface in piece.interfaces {
+            if !object.interfaces.contains(&interface) {
+                object.interfaces.push(interface);
+            }
+        }
+        for keyword in piece.keywords {
+            if !object.keywords.contains(&keyword) {
+                object.keywords.push(keyword);
+            }
+        }
+
+        object.variables.extend(piece.variables);
+        object.functions.extend(piece.functions);
+        for nested in piece.objects {
+            // nested partial classes are declared again in each piece
+            if !object.objects.iter().any(|existing| existing.identifier == nested.identifier) {
+                object.objects.push(nested);
+            }
+        }
+        object.implementations.extend(piece.implementations);
+    }
+
+    fn remove_objects(&mut self, removed: &HashSet<usize>) {
+        let mut index = 0;
+        self.objects.retain(|_| {
+            index += 1;
+            !removed.contains(&(index - 1))
+        });
+        let mut index = 0;
+        self.scopes.retain(|_| {
+            index += 1;
+            !removed.contains(&(index - 1))
+        });
+    }
+
+    // The crate and module a rust scope is in, eg crate::parser::rust for src/parser/rust.rs
+    fn rust_module(&self, scope: &Scope) -> Option<RustPath> {
+        let (krate, path) = self.rust_modules.module(&scope.filepath)?;
+        let mut segments = path.to_vec();
+        segments.extend(scope.namespace.split("::").filter(|segment| !segment.is_empty()).map(|segment| segment.to_string()));
+
+        Some(RustPath {
+            krate: krate.to_string(),
+            segments,
+        })
+    }
+
+    // The rust object a path written in scope refers to, eg fmt::Display<T> or super::Parser
+    fn rust_lookup(&self, scope: &Scope, path: &str) -> Option<usize> {
+        let module = self.rust_module(scope)?;
+        let end = path.find('<').unwrap_or(path.len());
+        let segments: Vec<String> = path[..end]
+            .split("::")
+            .map(|segment| segment.trim())
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| segment.to_string())
+            .collect();
+
+        let path = self.rust_path(&module, &segments, 0)?;
+        self.rust_item(&path, 0)
+    }
+
+    // Makes a path written in module absolute, going through the module's use declarations
+    fn rust_path(&self, module: &RustPath, segments: &[String], depth: usize) -> Option<RustPath> {
+        let (first, rest) = segments.split_first()?;
+        if depth > MAX_RUST_DEPTH {
+            return None
+        }
+
+        let path = match first.as_str() {
+            "crate" => RustPath {
+                krate: module.krate.clone(),
+                segments: vec![first.clone()],
+            }.join(rest),
+            "self" => module.join(rest),
+            "super" => {
+                let mut parent = module.clone();
+                if parent.segments.len() > 1 {
+                    parent.segments.pop();
+                }
+                match rest.first().map(|segment| segment.as_str()) {
+                    Some("super") => return self.rust_path(&parent, rest, depth + 1),
+                    _ => parent.join(rest),
+                }
+            },
+
+            _ => {
+                let local = module.join(std::slice::from_ref(first));
+                if let Some(import) = self.rust_import(module, first) {
+                    self.rust_path(module, &import_path(import), depth + 1)?.join(rest)
+                } else if self.rust_items.contains_key(&local) || self.is_rust_module(&local) {
+                    module.join(segments)
+                } else if let Some(path) = self.rust_glob(module, segments, depth) {
+                    path
+                } else if let Some(root) = self.rust_modules.library(first)
+                    && !rest.is_empty() {
+                    // a binary using its own library by name
+                    RustPath {
+                        krate: root.to_string(),
+                        segments: vec![String::from("crate")],
+                    }.join(rest)
+                } else {
+                    // another crate, or the prelude
+                    RustPath {
+                        krate: String::new(),
+                        segments: segments.to_vec(),
+                    }
+                }
+            },
+        };
+
+        Some(path)
+    }
+
+    // The object at an absolute path, following re-exports such as pub use parser::Parser in lib.rs
+    fn rust_item(&self, path: &RustPath, depth: usize) -> Option<usize> {
+        if let Some(index) = self.rust_items.get(path) {
+            return Some(*index)
+        }
+        if depth > MAX_RUST_DEPTH || path.krate.is_empty() {
+            return None
+        }
+
+        let (name, segments) = path.segments.split_last()?;
+        let module = RustPath {
+            krate: path.krate.clone(),
+            segments: segments.to_vec(),
+        };
+
+        match self.rust_import(&module, name) {
+            Some(import) => {
+                let target = self.rust_path(&module, &import_path(import), depth + 1)?;
+                self.rust_item(&target, depth + 1)
+            },
+            None => {
+                let target = self.rust_glob(&module, std::slice::from_ref(name), depth + 1)?;
+                self.rust_item(&target, depth + 1)
+            },
+        }
+    }
+
+    // A path through one of the module's use a::* declarations to an object or module
+    fn rust_glob(&self, module: &RustPath, segments: &[String], depth: usize) -> Option<RustPath> {
+        let imports = self.rust_imports.get(module)?;
+
+        imports
+            .iter()
+            .map(|index| &self.imports[*index])
+            .filter(|import| import.name.as_deref() == Some("*"))
+            .find_map(|import| {
+                let path = self.rust_path(module, &import_path(import), depth + 1)?.join(segments);
+                let found = self.rust_items.contains_key(&path) || self.is_rust_module(&path) || self.rust_item(&path, depth + 1).is_some();
+                found.then_some(path)
+            })
+    }
+
+    // The use declaration in module that brings in name
+    fn rust_import(&self, module: &RustPath, name: &str) -> Option<&Import> {
+        self.rust_imports
+            .get(module)?
+            .iter()
+            .map(|index| &self.imports[*index])
+            .find(|import| {
+                import.name.as_deref() != Some("*")
+                    && import.binding() == name
+                    && import_path(import) != [name] // use serde;
+            })
+    }
+
+    fn is_rust_module(&self, path: &RustPath) -> bool {
+        self.rust_modules.is_module(&path.krate, &path.segments) || self.rust_namespaces.contains(path)
+    }
+
+    // The name a parent was declared with, Base for B after from .base import Base as B
+    fn imported_name<'b>(&'b self, filepath: &str, parent: &'b str) -> &'b str {
+        let name = base_name(parent);
+        if name != parent.trim() {
+            return name
+        }
+
+        self.imports
+            .iter()
+            .find(|import| import.filepath == filepath && import.alias.as_deref() == Some(name))
+            .and_then(|import| import.name.as_deref())
+            .unwrap_or(name)
+    }
+
+    // The files a python parent name comes from going by the imports of the file it's used in,
+    // eg B after from .base import Base as B, or mb.Base after import models.base as mb
+    fn imported_files(&self, filepath: &str, parent: &str) -> Option<Vec<&str>> {
+        let end = parent.find(['[', '(']).unwrap_or(parent.len());
+        let segments: Vec<&str> = parent[..end].trim().split('.').collect();
+        let first = segments[0];
+        // models.base.Base -> base
+        let qualifier = segments.get(1..segments.len().saturating_sub(1)).unwrap_or_default();
+
+        let imports: Vec<&Import> = self.imports.iter().filter(|import| import.filepath == filepath).collect();
+        let mut modules = Vec::new();
+
+        match imports.iter().find(|import| import.binding() == first) {
+            // Base after from models.base import Base
+            Some(import) if segments.len() == 1 => match &import.name {
+                Some(_) => modules.push(import.module.clone()),
+                None => return None,
+            },
+
+            Some(import) => {
+                let mut module = match (&import.name, &import.alias) {
+                    // from models import base -> base.Base, from . import base
+                    (Some(name), _) if import.module.ends_with('.') => format!("{}{}", import.module, name),
+                    (Some(name), _) => format!("{}.{}", import.module, name),
+                    // import models.base as mb -> mb.Base
+                    (None, Some(_)) => import.module.clone(),
+                    // import models.base -> models.base.Base
+                    (None, None) => first.to_string(),
+                };
+                for segment in qualifier {
+                    module = format!("{}.{}", module, segment);
+                }
+                modules.push(module);
+            },
+
+            // from models.base import *
+            None if segments.len() == 1 => {
+                for import in imports.iter().filter(|import| import.name.as_deref() == Some("*")) {
+                    modules.push(import.module.clone());
+                }
+            },
+
+            // a fully qualified name, models.base.Base
+            None => modules.push(segments[..segments.len() - 1].join(".")),
+        }
+
+        let mut files = Vec::new();
+        for module in modules {
+            if let Some(module) = self.python_modules.absolute(filepath, &module) {
+                files.extend(self.python_modules.files(&module));
+            }
+        }
+
+        if files.is_empty() {
+            None
+        } else {
+            Some(files)
+        }
+    }
+}
+
+// use crate::structs::Object -> ["crate", "structs", "Object"], use a::* -> ["a"]
+fn import_path(import: &Import) -> Vec<String> {
+    let mut path: Vec<String> = import.module
+        .split("::")
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect();
+    if let Some(name) = &import.name
+        && name != "*" {
+        path.push(name.clone());
+    }
+
+    path
+}
+
+// Read(p []byte) (int, error) -> Read([]byte)(int, error), what a go method has to match to satisfy an interface
+fn go_signature(function: &Function) -> String {
+    let parameters: Vec<&str> = function.parameters.iter().filter_map(|parameter| parameter.ptype.as_deref()).collect();
+    format!("{}({}){}", function.identifier, parameters.join(", "), function.return_type)
+}
+
+// models.base.Base -> Base, Generic[T] -> Generic, crate::structs::Object -> Object, App\Models\Base -> Base
+pub fn base_name(parent: &str) -> &str {
+    let end = parent.find(['[', '<', '(']).unwrap_or(parent.len());
+    let path = parent[..end].trim();
+
+    path.rsplit(['.', ':', '\\']).next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Severity;
+    use crate::testing::{methods, node, resolve};
+
+    #[test]
+    fn partial_classes_are_merged() {
+        let hierarchy = resolve(&[
+            ("Shapes/Shape.cs", "namespace Shapes {\n    public partial class Shape : Base {\n        public void Draw() { }\n    }\n}\n"),
+            ("Shapes/Shape.Area.cs", "namespace Shapes {\n    public partial class Shape {\n        public double Area() { return 0; }\n    }\n}\n"),
+        ]);
+
+        assert_eq!(hierarchy.nodes.len(), 1);
+        assert_eq!(methods(&hierarchy, "Shape"), ["Draw", "Area"]);
+        assert_eq!(node(&hierarchy, "Shape").unresolved_parents, ["Base"]);
+    }
+
+    #[test]
+    fn partial_classes_are_only_merged_within_a_language() {
+        let hierarchy = resolve(&[
+            ("Shape.cs", "public partial class Shape {\n    public void Draw() { }\n}\n"),
+            ("shape.rb", "class Shape\n  def area\n  end\nend\n"),
+        ]);
+
+        assert_eq!(hierarchy.nodes.len(), 2);
+        assert_eq!(hierarchy.nodes[0].object.functions.len(), 1);
+        assert_eq!(hierarchy.nodes[1].object.functions.len(), 1);
+    }
+
+    #[test]
+    fn parents_are_found_in_the_same_file_then_through_imports_then_anywhere() {
+        let hierarchy = resolve(&[
+            ("shapes/a.py", "class Base:\n    pass\n\nclass Local(Base):\n    pass\n"),
+            ("shapes/b.py", "class Base:\n    pass\n"),
+            ("shapes/imported.py", "from b import Base\n\nclass Imported(Base):\n    pass\n"),
+            ("shapes/guessed.py", "class Guessed(Base):\n    pass\n\nclass Unique(Single):\n    pass\n"),
+            ("shapes/single.py", "class Single:\n    pass\n"),
+        ]);
+        let at = |filepath: &str| hierarchy.nodes.iter().position(|node| node.object.filepath == filepath).unwrap();
+        let guessed = node(&hierarchy, "Guessed");
+
+        assert_eq!(node(&hierarchy, "Local").parents, [at("shapes/a.py")]);
+        assert_eq!(node(&hierarchy, "Imported").parents, [at("shapes/b.py")]);
+        assert_eq!(node(&hierarchy, "Unique").parents, [at("shapes/single.py")]);
+        // declared in two other files, neither of them imported
+        assert!(guessed.parents.is_empty());
+        assert_eq!(guessed.ambiguous_parents, ["Base"]);
+        assert_eq!(hierarchy.diagnostics.len(), 1);
+        assert_eq!(hierarchy.diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn python_parents_are_found_through_relative_aliased_and_dotted_imports() {
+        let hierarchy = resolve(&[
+            ("models/__init__.py", ""),
+            ("models/base.py", "class Base:\n    pass\n"),
+            ("other/base.py", "class Base:\n    pass\n"),
+            ("models/child.py", "from .base import Base as B\n\nclass Child(B):\n    pass\n"),
+            ("app.py", "import models.base\n\nclass Dotted(models.base.Base):\n    pass\n"),
+            ("script.py", "from base import Base\n\nclass Script(Base):\n    pass\n"),
+        ]);
+        let at = |filepath: &str| hierarchy.nodes.iter().position(|node| node.object.filepath == filepath).unwrap();
+
+        assert_eq!(node(&hierarchy, "Child").parents, [at("models/base.py")]);
+        assert_eq!(node(&hierarchy, "Dotted").parents, [at("models/base.py")]);
+        // other isn't a package, so base is other/base.py by name rather than models/base.py by the end of its path
+        assert_eq!(node(&hierarchy, "Script").parents, [at("other/base.py")]);
+        assert!(hierarchy.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn a_parent_named_twice_is_one_edge() {
+        let hierarchy = resolve(&[
+            ("src/named.ts", "export interface Named {}\n"),
+            ("src/square.ts", "import { Named } from './named';\nimport * as s from './named';\n\nexport class Square implements Named, s.Named {}\n"),
+            ("shapes/named.go", "package shapes\n\ntype Titled interface {\n    Title() string\n}\n"),
+            ("shapes/labelled.go", "package shapes\n\nimport s \"example.com/shapes\"\n\ntype Labelled interface {\n    Titled\n    s.Titled\n}\n"),
+        ]);
+        let named = hierarchy.find("Named").unwrap();
+        let titled = hierarchy.find("Titled").unwrap();
+
+        assert_eq!(node(&hierarchy, "Square").parents, [named]);
+        assert_eq!(node(&hierarchy, "Named").children, [hierarchy.find("Square").unwrap()]);
+        assert_eq!(node(&hierarchy, "Labelled").parents, [titled]);
+    }
+
+    #[test]
+    fn parents_are_only_found_within_a_language() {
+        let hierarchy = resolve(&[
+            ("shapes.py", "class Shape:\n    pass\n\nclass Circle(Shape):\n    pass\n"),
+            ("Shape.java", "public class Shape {\n}\n"),
+            ("Square.java", "public class Square extends Shape {\n}\n"),
+            ("square.rb", "class Square < Shape\nend\n"),
+        ]);
+        let at = |filepath: &str| hierarchy.nodes.iter().position(|node| node.object.filepath == filepath).unwrap();
+
+        assert_eq!(node(&hierarchy, "Circle").parents, [at("shapes.py")]);
+        assert_eq!(hierarchy.nodes[at("Square.java")].parents, [at("Shape.java")]);
+        assert!(hierarchy.nodes[at("square.rb")].parents.is_empty());
+        assert_eq!(hierarchy.nodes[at("square.rb")].unresolved_parents, ["Shape"]);
+    }
+
+    #[test]
+    fn impl_blocks_are_merged_into_their_type() {
+        let hierarchy = resolve(&[
+            ("src/shapes.rs", "pub trait Shape {\n    fn area(&self) -> f64;\n}\n\npub struct Circle {\n    radius: f64,\n}\n"),
+            ("src/circle.rs", "use crate::shapes::{Circle, Shape};\n\nimpl Circle {\n    pub fn new(radius: f64) -> Self {\n        Circle { radius }\n    }\n}\n\nimpl Shape for Circle {\n    fn area(&self) -> f64 {\n        0.0\n    }\n}\n"),
+        ]);
+        let circle = node(&hierarchy, "Circle");
+
+        assert_eq!(hierarchy.nodes.len(), 2);
+        assert_eq!(methods(&hierarchy, "Circle"), ["new", "area"]);
+        assert_eq!(circle.object.implementations.len(), 2);
+        assert_eq!(circle.parents, [hierarchy.find("Shape").unwrap()]);
+    }
+
+    #[test]
+    fn rust_parents_are_found_through_crate_super_and_self_paths() {
+        let hierarchy = resolve(&[
+            ("src/lib.rs", "mod shapes;\n\npub trait Named {}\n"),
+            ("src/shapes.rs", "mod circle;\n\npub trait Shape {}\n\npub trait Round {}\n"),
+            ("src/shapes/circle.rs", "pub struct Circle {}\n\npub trait Drawable {}\n\nimpl crate::Named for Circle {}\nimpl super::Shape for Circle {}\nimpl self::Drawable for Circle {}\nimpl crate::shapes::Round for Circle {}\n"),
+        ]);
+        let parents: Vec<&str> = node(&hierarchy, "Circle").parents.iter().map(|parent| hierarchy.nodes[*parent].object.identifier.as_str()).collect();
+
+        assert_eq!(parents, ["Named", "Shape", "Drawable", "Round"]);
+    }
+
+    #[test]
+    fn extensions_of_types_that_were_not_read_are_external() {
+        let hierarchy = resolve(&[
+            ("Sources/Shout.swift", "protocol Loud {}\n\nextension String: Loud {\n    func shout() -> String { uppercased() }\n}\n"),
+            ("lib/shout.rb", "class Array\n  def second = self[1]\nend\n\nclass Stack < Array\nend\n"),
+        ]);
+        let roots: Vec<&str> = hierarchy.roots().iter().map(|root| hierarchy.nodes[*root].object.identifier.as_str()).collect();
+
+        assert!(node(&hierarchy, "String").external);
+        assert!(node(&hierarchy, "Array").external);
+        assert!(node(&hierarchy, "Loud").children.is_empty());
+        assert_eq!(node(&hierarchy, "Stack").unresolved_parents, ["Array"]);
+        assert_eq!(roots, ["Loud", "Stack"]);
+    }
+
+    #[test]
+    fn go_types_satisfy_the_interfaces_whose_methods_they_have() {
+        let hierarchy = resolve(&[
+            ("shapes/shape.go", "package shapes\n\ntype Shape interface {\n    Area() float64\n}\n\ntype Solid interface {\n    Shape\n    Volume() float64\n}\n"),
+            ("shapes/base.go", "package shapes\n\ntype Base struct{}\n\nfunc (b *Base) Area() float64 { return 0 }\n"),
+            ("shapes/cube.go", "package shapes\n\ntype Cube struct {\n    Base\n}\n\nfunc (c Cube) Volume() float64 { return 0 }\n"),
+        ]);
+        let shape = hierarchy.find("Shape").unwrap();
+        let solid = hierarchy.find("Solid").unwrap();
+
+        assert_eq!(node(&hierarchy, "Base").parents, [shape]);
+        assert_eq!(node(&hierarchy, "Cube").parents, [hierarchy.find("Base").unwrap(), shape, solid]);
+        // embedding an interface makes it a parent, interfaces don't satisfy each other
+        assert_eq!(node(&hierarchy, "Solid").parents, [shape]);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+
+use crate::structs::{Diagnostic, FileData, Severity};
+
+// Builds FileData from a directory tree, skipping anything .gitignore / .ignore files leave out.
+// Include and exclude globs are matched against paths relative to the root, eg "src/**/*.py"
+pub struct Scanner {
+    root: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Scanner {
+    pub fn new(root: &Path, include: &[String], exclude: &[String]) -> Scanner {
+        Scanner {
+            root: root.to_path_buf(),
+            include: include.to_vec(),
+            exclude: exclude.to_vec(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn scan(&mut self) -> (Vec<FileData>, Vec<Diagnostic>) {
+        let mut all_files = Vec::new();
+
+        let include = self.glob_set(&self.include.clone());
+        let exclude = self.glob_set(&self.exclude.clone());
+        if !self.root.is_dir() {
+            let root = self.root.display().to_string();
+            self.error(&root, String::from("not a directory"));
+        }
+        if !self.diagnostics.is_empty() {
+            return (all_files, std::mem::take(&mut self.diagnostics))
+        }
+
+        let walker = WalkBuilder::new(&self.root)
+            .require_git(false) // .gitignore applies outside of git repos too
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    let root = self.root.display().to_string();
+                    self.error(&root, error.to_string());
+                    continue;
+                },
+            };
+
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+
+            let file_type = match language(path) {
+                Some(file_type) => file_type,
+                None => continue,
+            };
+
+            let relative = path.strip_prefix(&self.root).unwrap_or(path);
+            if !self.include.is_empty() && !include.is_match(relative) {
+                continue;
+            }
+            if exclude.is_match(relative) {
+                continue;
+            }
+
+            let filepath = path.display().to_string();
+            let source = match fs::read_to_string(path) {
+                Ok(source) => source,
+                Err(error) => {
+                    self.error(&filepath, format!("could not read file: {}, skipping it", error));
+                    continue;
+                },
+            };
+
+            let lines: HashMap<i64, String> = source
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index as i64 + 1, line.to_string()))
+                .collect();
+
+            all_files.push(FileData {
+                file_no: all_files.len() as u32 + 1,
+                file_type: file_type.to_string(),
+                filepath,
+                lines,
+            });
+        }
+
+        (all_files, std::mem::take(&mut self.diagnostics))
+    }
+
+    fn glob_set(&mut self, globs: &[String]) -> GlobSet {
+        let mut builder = GlobSetBuilder::new();
+        for glob in globs {
+            match Glob::new(glob) {
+                Ok(glob) => {
+                    builder.add(glob);
+                },
+                Err(error) => self.error("<arguments>", format!("invalid glob `{}`: {}", glob, error.kind())),
+            }
+        }
+
+        builder.build().unwrap_or_else(|_| GlobSet::empty())
+    }
+
+    fn error(&mut self, filepath: &str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            filepath: filepath.to_string(),
+            line: 0,
+            column: 0,
+            length: 0,
+            severity: Severity::Error,
+            message,
+        });
+    }
+}
+
+// The fileType for a path, going by its extension
+pub fn language(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+    let language = match extension.as_str() {
+        "py" | "pyi" => "python",
+        "rs" => "rust",
+        "java" => "java",
+        "cs" => "csharp",
+        "ts" | "tsx" | "mts" | "cts" => "typescript",
+        "js" | "jsx" | "mjs" | "cjs" => "javascript",
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" | "h" => "cpp",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "go" => "go",
+        "rb" => "ruby",
+        "php" => "php",
+        _ => return None,
+    };
+
+    Some(language)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::Scanner;
+
+    // A directory of empty source files under the system's temporary directory
+    fn directory(name: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("classtree-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        root
+    }
+
+    fn scan(root: &Path, include: &[&str], exclude: &[&str]) -> Vec<String> {
+        let include: Vec<String> = include.iter().map(|glob| glob.to_string()).collect();
+        let exclude: Vec<String> = exclude.iter().map(|glob| glob.to_string()).collect();
+        let (files, diagnostics) = Scanner::new(root, &include, &exclude).scan();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+        files
+            .iter()
+            .map(|file| Path::new(&file.filepath).strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn globs_are_matched_against_paths_relative_to_the_root() {
+        let root = directory("globs", &["src/a.py", "src/b.rs", "src/tests/test_a.py", "README.md"]);
+
+        assert_eq!(scan(&root, &[], &[]), ["src/a.py", "src/b.rs", "src/tests/test_a.py"]);
+        assert_eq!(scan(&root, &["src/**/*.py"], &[]), ["src/a.py", "src/tests/test_a.py"]);
+        assert_eq!(scan(&root, &["src/**/*.py"], &["**/tests/**"]), ["src/a.py"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn files_gitignore_leaves_out_are_skipped() {
+        let root = directory("gitignore", &["models.py", "build/models.py", "generated.py"]);
+        fs::write(root.join(".gitignore"), "build/\ngenerated.py\n").unwrap();
+
+        assert_eq!(scan(&root, &[], &[]), ["models.py"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn invalid_globs_are_reported() {
+        let root = directory("invalid", &["models.py"]);
+        let (files, diagnostics) = Scanner::new(&root, &[String::from("src/[")], &[]).scan();
+
+        assert!(files.is_empty());
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.starts_with("invalid glob `src/[`"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,24 +1,80 @@
-use crate::HashMap;
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use crate::resolver::base_name;
 
 #[derive(Clone)]
 pub struct FileData {
-    pub file_no: i16,
+    pub file_no: u32,
     pub file_type: String,
     pub filepath: String,
     pub lines: HashMap<i64, String>
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TokenisedFileData {
-    pub file_no: i16,
+    pub file_no: u32,
     pub file_type: String,
     pub filepath: String,
     pub old_lines: HashMap<i64, String>,
-    pub lines: HashMap<i64, Vec<Token>>
+    pub lines: HashMap<i64, Vec<Token>>,
+    // positions[line][i] is where lines[line][i] was found
+    pub positions: HashMap<i64, Vec<TokenPosition>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
 
+// offset is in bytes from the start of the line, column (1-based) and length are in characters
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TokenPosition {
+    pub offset: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+// Lines and columns are 1-based, end_column is one past the last character
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct Span {
+    pub file_no: u32,
+    pub start_line: i64,
+    pub start_column: usize,
+    pub end_line: i64,
+    pub end_column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+// Line and column are 1-based, a column of 0 points at the whole line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub filepath: String,
+    pub line: i64,
+    pub column: usize,
+    pub length: usize,
+    pub severity: Severity,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum ASTNode {
     Object(Object),
     Variable(Variable),
@@ -28,37 +84,432 @@ pub enum ASTNode {
     Eof,
 }
 
+// Everything a parse produces. Nested objects live in their enclosing Object.objects
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParsedModel {
+    pub objects: Vec<Object>,
+    pub functions: Vec<Function>,
+    pub variables: Vec<Variable>,
+    pub imports: Vec<Import>,
+    // rust's mod name; declarations, which say where the files of a crate sit in its module tree
+    pub modules: Vec<ModuleDeclaration>,
+    // (filepath, file_type) of every file read, including ones without objects such as __init__.py
+    pub files: Vec<(String, String)>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ParsedModel {
+    // Every object including nested ones, outer objects first
+    pub fn all_objects(&self) -> Vec<&Object> {
+        let mut objects = Vec::new();
+        for object in &self.objects {
+            object.collect_objects(&mut objects);
+        }
+
+        objects
+    }
+}
+
+// import models.base as mb -> { module: "models.base", name: None, alias: Some("mb") }
+// from ..base import Base -> { module: "..base", name: Some("Base"), alias: None }
+// use crate::structs::{self, Token as T} in rust -> { module: "crate", name: Some("structs") }, { module: "crate::structs", name: Some("Token"), alias: Some("T") }
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Import {
+    // as written, relative imports keep their leading dots. Empty for typescript's exports of the
+    // file's own names, export default Base is Base imported as default
+    pub module: String,
+    // what was imported from the module, * for everything
+    pub name: Option<String>,
+    pub alias: Option<String>,
+    // see Object.namespace
+    pub namespace: String,
+    pub filepath: String,
+    pub span: Span,
+}
+
+// mod name; in rust, with the file given by a #[path = "..."] attribute if there is one
 #[derive(Debug, Clone, PartialEq)]
+pub struct ModuleDeclaration {
+    pub name: String,
+    pub path: Option<String>,
+    pub namespace: String,
+    pub filepath: String,
+}
+
+impl Import {
+    // The name the import makes available, import a.b binds a
+    pub fn binding(&self) -> &str {
+        match (&self.alias, &self.name) {
+            (Some(alias), _) => alias,
+            (None, Some(name)) => name,
+            (None, None) => self.module.split('.').next().unwrap_or(&self.module),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableKind {
+    // per instance, self.x in python or a struct field
+    Field,
+    // shared by the class, x = 0 in a python class body
+    ClassAttribute,
+    // read through a method, @property in python
+    Property,
+    // a case of an enum. A rust variant's payload is its type, eg (String, i32) or { x: i32 },
+    // the arguments of a java constant are its value
+    Variant,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Variable {
     pub identifier: String,
+    pub kind: VariableKind,
+    // a property without a setter
+    pub readonly: bool,
+    #[serde(serialize_with = "serialize_tokens")]
     pub value: Option<Vec<Token>>,
+    #[serde(rename = "type", serialize_with = "serialize_tokens")]
     pub vtype: Option<Vec<Token>>,
     pub parent: String,
+    #[serde(flatten)]
+    pub access: Access,
+    pub filepath: String,
+    pub span: Span,
+}
+
+impl Variable {
+    pub fn type_string(&self) -> Option<String> {
+        self.vtype.as_ref().map(|vtype| tokens_to_string(vtype))
+    }
 }
 
 
-#[derive(Debug, Clone, PartialEq)]
+// Where a member can be used from. Languages with only public and private members use the ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Public,
+    // java's, c++'s and php's protected, and ruby's
+    Protected,
+    // java members without a modifier, c# and kotlin's internal, an unexported go name
+    Package,
+    Private,
+}
+
+impl Access {
+    pub fn is_public(&self) -> bool {
+        *self == Access::Public
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Access::Public => "public",
+            Access::Protected => "protected",
+            Access::Package => "package",
+            Access::Private => "private",
+        }
+    }
+}
+
+impl From<bool> for Access {
+    fn from(public: bool) -> Access {
+        if public { Access::Public } else { Access::Private }
+    }
+}
+
+// "public": false, "access": "protected", public as it was before access was added
+impl Serialize for Access {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("public", &self.is_public())?;
+        map.serialize_entry("access", self.name())?;
+        map.end()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Parameter {
     pub identifier: String,
+    #[serde(rename = "type")]
     pub ptype: Option<String>,
 
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    Class,
+    Struct,
+    Enum,
+    Interface,
+    Trait,
+    Module,
+    Impl,
+    // python classes whose annotated class attributes are fields
+    Dataclass,
+    NamedTuple,
+    TypedDict,
+    // kotlin's data class
+    Data,
+}
+
+impl ObjectKind {
+    // Annotated class attributes of these are per instance fields
+    pub fn has_declared_fields(&self) -> bool {
+        matches!(self, ObjectKind::Dataclass | ObjectKind::NamedTuple | ObjectKind::TypedDict)
+    }
+
+    // The kind of object a Token::ObjectDeclaration keyword starts
+    pub fn from_keyword(keyword: &str) -> ObjectKind {
+        match keyword.to_ascii_lowercase().as_str() {
+            "struct" | "union" | "defstruct" | "table" | "type" => ObjectKind::Struct,
+            "enum" => ObjectKind::Enum,
+            "interface" | "protocol" => ObjectKind::Interface,
+            "trait" => ObjectKind::Trait,
+            "module" | "defmodule" => ObjectKind::Module,
+            "impl" => ObjectKind::Impl,
+            _ => ObjectKind::Class, // class, object, record
+        }
+    }
+
+    // Interfaces and traits are implemented rather than extended
+    pub fn is_interface(&self) -> bool {
+        matches!(self, ObjectKind::Interface | ObjectKind::Trait)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Object {
     pub identifier: String,
-    pub block: Vec<ASTNode>,
+    pub kind: ObjectKind,
+    #[serde(rename = "abstract")]
+    pub is_abstract: bool,
     pub public: bool,
-    pub variables: HashMap<String, ASTNode>,
+    pub decorators: Vec<String>,
+    // rust attributes, eg #[derive(Debug, Clone)] -> ["derive(Debug, Clone)"]
+    pub attributes: Vec<String>,
+    // type parameters, eg Generic[K, V] -> ["K", "V"]
+    pub generics: Vec<String>,
+    // bounds on them, from the parameters and where clause, eg <T: Display> -> ["T: Display"]
+    pub where_clauses: Vec<String>,
+    // python's class Child(Base, metaclass=Meta)
+    pub metaclass: Option<String>,
+    // other name=value pairs in the base list, eg total=False, or how c++ inherits a base that isn't public, eg (private, Named),
+    // or how a parent is brought in when it isn't inherited, a go struct's (embedded, Base), a ruby (include, Comparable) or a php (use, Loggable)
+    pub keywords: Vec<(String, String)>,
+    #[serde(rename = "fields")]
+    pub variables: Vec<Variable>,
+    #[serde(rename = "methods")]
+    pub functions: Vec<Function>,
+    // nested objects have their own entry in the document, only their names are written here
+    #[serde(rename = "nested", serialize_with = "serialize_identifiers")]
+    pub objects: Vec<Object>,
+
+    // as written in the source, Hierarchy resolves them
+    #[serde(rename = "bases")]
+    pub parents: Vec<String>,
+    // the parents that are interfaces or traits rather than base classes
+    pub interfaces: Vec<String>,
+    // c#'s partial class, declared in pieces that are merged into one
+    pub partial: bool,
+    // the rust impl blocks, other pieces of a c# partial class, c++ out-of-line definitions, kotlin and swift
+    // extensions, and reopenings of a ruby class merged into this object
+    pub implementations: Vec<Implementation>,
+    // where the object is declared within its file, eg tests for mod tests { } in rust, or the package of a java,
+    // kotlin or go file
+    pub namespace: String,
+    pub filepath: String,
+    pub span: Span,
+}
+
+// The header of an impl block, impl<T: Display> Trait for Type<T>, or of one piece of a partial class
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Implementation {
+    #[serde(rename = "trait")]
+    pub trait_name: Option<String>,
+    pub attributes: Vec<String>,
+    pub generics: Vec<String>,
+    pub where_clauses: Vec<String>,
+    pub filepath: String,
+    pub span: Span,
+}
 
-    pub parent: Option<Box<Object>>,
+impl Object {
+    fn collect_objects<'a>(&'a self, objects: &mut Vec<&'a Object>) {
+        objects.push(self);
+        for object in &self.objects {
+            object.collect_objects(objects);
+        }
+    }
+
+    // A field assigned in several places is kept once, taking its type from whichever assignment has one.
+    // A property's setter makes it writable
+    pub fn add_variable(&mut self, variable: Variable) {
+        match self.variables.iter_mut().find(|existing| existing.identifier == variable.identifier) {
+            Some(existing) => {
+                if existing.vtype.is_none() {
+                    existing.vtype = variable.vtype;
+                }
+                existing.readonly = existing.readonly && variable.readonly;
+            },
+            None => self.variables.push(variable),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+pub struct Hierarchy {
+    pub nodes: Vec<HierarchyNode>,
+    // parents that couldn't be told apart
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Indices in parents/children point into Hierarchy.nodes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HierarchyNode {
+    #[serde(flatten)]
+    pub object: Object,
+    pub parents: Vec<usize>,
+    pub children: Vec<usize>,
+    pub unresolved_parents: Vec<String>,
+    // the unresolved parents that matched several objects
+    pub ambiguous_parents: Vec<String>,
+    // an extension of a type that wasn't read, eg swift's extension String or ruby's class Array, kept in the
+    // model but not linked to its parents or drawn
+    pub external: bool,
+}
+
+impl Hierarchy {
+    // Objects without a resolved parent
+    pub fn roots(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|index| self.nodes[*index].parents.is_empty() && !self.nodes[*index].external)
+            .collect()
+    }
+
+    // A class implements an interface or trait, interfaces extend each other
+    pub fn implements(&self, index: usize, parent: usize) -> bool {
+        self.nodes[parent].object.kind.is_interface() && !self.nodes[index].object.kind.is_interface()
+    }
+
+    // The same for a parent that wasn't resolved, going by what its declaration said it was, eg a java class's
+    // implements Serializable
+    pub fn implements_unresolved(&self, index: usize, parent: &str) -> bool {
+        let object = &self.nodes[index].object;
+        object.interfaces.iter().any(|interface| interface == parent) && !object.kind.is_interface()
+    }
+
+    // How a ruby module or php trait is mixed into the object at index, include, extend, prepend or use, None
+    // for other parents
+    pub fn mixin(&self, index: usize, parent: &str) -> Option<&str> {
+        self.nodes[index].object.keywords
+            .iter()
+            .find(|(keyword, module)| matches!(keyword.as_str(), "include" | "extend" | "prepend" | "use") && base_name(module) == base_name(parent))
+            .map(|(keyword, _)| keyword.as_str())
+    }
+
+    // A go struct embedding the parent, which it's made up of rather than a kind of
+    pub fn embeds(&self, index: usize, parent: &str) -> bool {
+        let object = &self.nodes[index].object;
+        !object.kind.is_interface() && object.keywords.iter().any(|(keyword, embedded)| keyword == "embedded" && base_name(embedded) == base_name(parent))
+    }
+
+    pub fn find(&self, identifier: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.object.identifier == identifier)
+    }
+
+    // starts and everything below them, at most max_depth levels down, in breadth first order
+    pub fn descendants(&self, starts: &[usize], max_depth: Option<usize>) -> Vec<usize> {
+        let mut found: Vec<usize> = Vec::new();
+        let mut level: Vec<usize> = starts.to_vec();
+        let mut depth = 0;
+
+        while !level.is_empty() {
+            let mut next = Vec::new();
+            for index in level {
+                if found.contains(&index) {
+                    continue;
+                }
+                found.push(index);
+
+                if max_depth.is_none_or(|max_depth| depth < max_depth) {
+                    next.extend(self.nodes[index].children.iter().copied());
+                }
+            }
+
+            level = next;
+            depth += 1;
+        }
+
+        found
+    }
+
+    // The plain text format, each object on its own line indented below its parents, with the parents that
+    // weren't found in brackets
+    pub fn display(&self, starts: &[usize], max_depth: Option<usize>) -> String {
+        let mut text = String::new();
+        for start in starts {
+            self.display_node(*start, 0, max_depth, &mut Vec::new(), &mut text);
+        }
+
+        text
+    }
+
+    fn display_node(&self, index: usize, depth: usize, max_depth: Option<usize>, path: &mut Vec<usize>, text: &mut String) {
+        let node = &self.nodes[index];
+        text.push_str(&format!("{}{}", "    ".repeat(depth), node.object.identifier));
+        if !node.unresolved_parents.is_empty() {
+            text.push_str(&format!(" ({})", node.unresolved_parents.join(", ")));
+        }
+        text.push('\n');
+
+        // inheritance cycles
+        if path.contains(&index) || max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            return;
+        }
+
+        path.push(index);
+        for child in &node.children {
+            self.display_node(*child, depth + 1, max_depth, path, text);
+        }
+        path.pop();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Function {
     pub identifier: String,
     pub parameters: Vec<Parameter>,
     pub return_type: String,
+    #[serde(flatten)]
+    pub access: Access,
+    #[serde(rename = "abstract")]
+    pub is_abstract: bool,
+    #[serde(rename = "static")]
+    pub is_static: bool,
+    #[serde(rename = "class_method")]
+    pub is_class_method: bool,
+    // names without arguments, eg @functools.lru_cache(maxsize=2) -> functools.lru_cache
+    pub decorators: Vec<String>,
+    pub attributes: Vec<String>,
+    // exceptions a java method declares, throws IOException
+    pub throws: Vec<String>,
+    pub filepath: String,
+    pub span: Span,
+}
+
+impl Function {
+    // Parameters without the receiver, eg (self, name) -> [name], (cls, name) -> [name] for class methods
+    pub fn arguments(&self) -> Vec<&Parameter> {
+        self.parameters
+            .iter()
+            .enumerate()
+            .filter(|(index, parameter)| {
+                parameter.identifier != "self" && !(*index == 0 && self.is_class_method && parameter.identifier == "cls")
+            })
+            .map(|(_, parameter)| parameter)
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -74,7 +525,7 @@ pub enum Type {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
-    ObjectDeclaration,
+    ObjectDeclaration(String),
     Trait,
     VariableDeclaration,
     SelfToken,
@@ -83,7 +534,7 @@ pub enum Token {
     Identifier(String),
     StringLiteral(String),
     Number(String),
-    Type(Type),
+    Type(Type, String),
     Comment(String),
     Indent(i16),
     BlockOpen(char),
@@ -104,38 +555,222 @@ pub enum Token {
     Eof,
 }
 
-impl Token {
-    pub fn to_string(self) -> String {
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Token::ObjectDeclaration => String::from("Object"),
-            Token::VariableDeclaration => format!("var"),
-            Token::SelfToken => format!("self"),
-            Token::FunctionDeclaration => format!("Function"),
-            Token::Publicity(bool) => {
-                if bool {
-                    String::from("pub")
+            Token::ObjectDeclaration(keyword) => write!(f, "{}", keyword),
+            Token::Trait => write!(f, "trait"),
+            Token::VariableDeclaration => write!(f, "var"),
+            Token::SelfToken => write!(f, "self"),
+            Token::FunctionDeclaration => write!(f, "Function"),
+            Token::Publicity(public) => {
+                if *public {
+                    write!(f, "pub")
                 } else {
-                    String::from("private")
+                    write!(f, "private")
                 }
             },
-            Token::Identifier(s) => s,
-            Token::StringLiteral(s) => s,
-            Token::Number(i) => i,
-            Token::Comment(s) => s,
-            Token::Indent(_i) => String::from("Indent"),
-            Token::BlockOpen(c) => format!("{}", c),
-            Token::BlockClose => String::from("}"),
-            Token::Period => String::from("."),
-            Token::Comma => String::from(","),
-            Token::Colon => String::from(":"),
-            Token::Semicolon => String::from(";"),
-            Token::Arrow => String::from("->"),
-            Token::Parenthesis(p) => format!("{}", p),
-            Token::Equals => String::from("="),
-            Token::Other(o) => String::from(o),
-            Token::Eof => String::from("Eof"),
-
-            _ => format!("{:?}", self)
+            Token::Identifier(s) => write!(f, "{}", s),
+            Token::StringLiteral(s) => write!(f, "{}", s),
+            Token::Number(i) => write!(f, "{}", i),
+            Token::Type(_, s) => write!(f, "{}", s),
+            Token::Comment(s) => write!(f, "{}", s),
+            Token::Indent(_i) => write!(f, "Indent"),
+            Token::BlockOpen(c) => write!(f, "{}", c),
+            Token::BlockClose => write!(f, "}}"),
+            Token::Bracket(b) => write!(f, "{}", b),
+            Token::Period => write!(f, "."),
+            Token::Comma => write!(f, ","),
+            Token::Colon => write!(f, ":"),
+            Token::Connect => write!(f, "::"),
+            Token::Semicolon => write!(f, ";"),
+            Token::Arrow => write!(f, "->"),
+            Token::Parenthesis(p) => write!(f, "{}", p),
+            Token::AngleBracket(a) => write!(f, "{}", a),
+            Token::Equals => write!(f, "="),
+            Token::Other(o) => write!(f, "{}", o),
+            Token::None => write!(f, "None"),
+            Token::Eof => write!(f, "Eof"),
         }
     }
 }
+
+impl Token {
+    // Words that can name something, keywords of other languages included, eg a rust struct called Module
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Token::Identifier(id) | Token::ObjectDeclaration(id) | Token::Type(_, id) => Some(id),
+            _ => None,
+        }
+    }
+
+    fn is_word(&self) -> bool {
+        matches!(self,
+            Token::ObjectDeclaration(_)
+            | Token::Trait
+            | Token::VariableDeclaration
+            | Token::SelfToken
+            | Token::FunctionDeclaration
+            | Token::Publicity(_)
+            | Token::Identifier(_)
+            | Token::StringLiteral(_)
+            | Token::Number(_)
+            | Token::Type(..)
+        )
+    }
+}
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+// What `--format json` prints. Bump SCHEMA_VERSION whenever a field is renamed, removed or changes
+// meaning, adding fields doesn't need a new version.
+//
+// {
+//   "schema_version": 1,
+//   "objects": [{                          every object, nested ones included
+//     "identifier": "Child",
+//     "kind": "class",                     class | struct | enum | interface | trait | module | impl
+//                                          | dataclass | named_tuple | typed_dict | data
+//     "abstract": false,
+//     "public": true,
+//     "decorators": ["dataclass"],         python and typescript decorators and java annotations, without arguments
+//     "attributes": ["derive(Debug)"],     rust and c# attributes
+//     "generics": ["T"],
+//     "where_clauses": ["T: Display"],
+//     "metaclass": "ABCMeta",              or null
+//     "keywords": [["total", "False"]],    other name=value pairs in the base list, c++'s private, protected and virtual bases,
+//                                          go's embedded types and ruby's include, extend and prepend
+//     "fields": [{
+//       "identifier", "kind",              field | class_attribute | property | variant
+//       "readonly", "value", "type", "parent", "public",
+//       "access",                          public | protected | package | private
+//       "filepath", "span"
+//     }],
+//     "methods": [{
+//       "identifier", "parameters": [{"identifier", "type"}], "return_type", "public", "access",
+//       "abstract", "static", "class_method", "decorators", "attributes", "throws", "filepath", "span"
+//     }],
+//     "nested": ["Inner"],                 names of objects declared inside this one
+//     "bases": ["models.Base"],            parents as written in the source, derived rust traits included
+//     "interfaces": ["Sized"],             the bases that are interfaces or traits rather than classes
+//     "partial": false,                    a c# partial class
+//     "implementations": [{                rust impls, c# partial pieces, c++ definitions, extensions and reopened ruby classes merged in
+//       "trait", "attributes", "generics", "where_clauses", "filepath", "span"
+//     }],
+//     "namespace": "tests",                modules declared within the file around the object, eg rust's mod tests { }, or the package
+//     "filepath": "models/child.py",
+//     "span": {"file_no", "start_line", "start_column", "end_line", "end_column"},
+//     "parents": [0],                      indices into "objects" of the bases that were found
+//     "children": [2, 3],
+//     "unresolved_parents": ["object"],    bases that weren't found
+//     "ambiguous_parents": [],             unresolved bases that matched several objects
+//     "external": false                    an extension of a type that wasn't read, which has no parents or children
+//   }],
+//   "functions": [...],                    module level functions, as in "methods"
+//   "variables": [...],                    module level variables, as in "fields"
+//   "imports": [{"module", "name", "alias", "namespace", "filepath", "span"}],
+//   "diagnostics": [{"filepath", "line", "column", "length", "severity", "message"}]
+// }
+//
+// Types, values and return types are source text, null or "" when they aren't written down.
+// Lines and columns are 1-based, a line or column of 0 means the whole file or line.
+#[derive(Debug, Serialize)]
+pub struct ModelDocument<'a> {
+    pub schema_version: u32,
+    pub objects: &'a [HierarchyNode],
+    pub functions: &'a [Function],
+    pub variables: &'a [Variable],
+    pub imports: &'a [Import],
+    pub diagnostics: &'a [Diagnostic],
+}
+
+impl<'a> ModelDocument<'a> {
+    pub fn new(model: &'a ParsedModel, hierarchy: &'a Hierarchy, diagnostics: &'a [Diagnostic]) -> ModelDocument<'a> {
+        ModelDocument {
+            schema_version: SCHEMA_VERSION,
+            objects: &hierarchy.nodes,
+            functions: &model.functions,
+            variables: &model.variables,
+            imports: &model.imports,
+            diagnostics,
+        }
+    }
+}
+
+fn serialize_tokens<S: Serializer>(tokens: &Option<Vec<Token>>, serializer: S) -> Result<S::Ok, S::Error> {
+    match tokens {
+        Some(tokens) => serializer.serialize_some(&tokens_to_string(tokens)),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_identifiers<S: Serializer>(objects: &[Object], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(objects.iter().map(|object| &object.identifier))
+}
+
+// UML visibility marker
+pub fn visibility(access: Access) -> char {
+    match access {
+        Access::Public => '+',
+        Access::Protected => '#',
+        Access::Package => '~',
+        Access::Private => '-',
+    }
+}
+
+// Joins tokens back into source-like text, eg [Identifier(Dict), Bracket('['), ...] -> "Dict[str, int]"
+pub fn tokens_to_string(tokens: &[Token]) -> String {
+    let mut string = String::new();
+    let mut previous_word = false;
+
+    for token in tokens {
+        let word = token.is_word();
+        if previous_word && word {
+            string.push(' ');
+        }
+
+        string.push_str(&token.to_string());
+        if *token == Token::Comma {
+            string.push(' ');
+        }
+
+        previous_word = word;
+    }
+
+    string
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ModelDocument, SCHEMA_VERSION};
+    use crate::testing::{parse, resolve};
+    use crate::Resolver;
+
+    #[test]
+    fn json_documents_follow_the_schema() {
+        let model = parse(&[("models/shapes.py", "class Base:\n    pass\n\nclass Square(Base):\n    side: int = 1\n\n    def area(self) -> int:\n        return self.side ** 2\n")]);
+        let hierarchy = Resolver::new(&model).resolve();
+        let document = serde_json::to_value(ModelDocument::new(&model, &hierarchy, &[])).unwrap();
+
+        assert_eq!(document["schema_version"], SCHEMA_VERSION);
+        let square = &document["objects"][1];
+        assert_eq!(square["identifier"], "Square");
+        assert_eq!(square["kind"], "class");
+        assert_eq!(square["bases"], serde_json::json!(["Base"]));
+        assert_eq!(square["parents"], serde_json::json!([0]));
+        assert_eq!(square["fields"][0]["identifier"], "side");
+        assert_eq!(square["fields"][0]["type"], "int");
+        assert_eq!(square["fields"][0]["access"], "public");
+        assert_eq!(square["methods"][0]["return_type"], "int");
+        assert_eq!(square["span"]["start_line"], 4);
+        assert_eq!(document["objects"][0]["children"], serde_json::json!([1]));
+    }
+
+    #[test]
+    fn text_is_indented_below_each_parent() {
+        let hierarchy = resolve(&[("shapes.py", "class Shape(Base):\n    pass\n\nclass Square(Shape):\n    pass\n\nclass Cube(Square):\n    pass\n")]);
+
+        assert_eq!(hierarchy.display(&hierarchy.roots(), None), "Shape (Base)\n    Square\n        Cube\n");
+        assert_eq!(hierarchy.display(&hierarchy.roots(), Some(1)), "Shape (Base)\n    Square\n");
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::scanner::language;
+use crate::structs::{FileData, Hierarchy, HierarchyNode, ParsedModel};
+use crate::{Lexer, Parser, Resolver};
+
+// Sources run through the lexer and parser as a scan would read them, (filepath, source) with the language
+// going by the extension
+pub fn parse(sources: &[(&str, &str)]) -> ParsedModel {
+    let files = sources
+        .iter()
+        .enumerate()
+        .map(|(index, (filepath, source))| FileData {
+            file_no: index as u32 + 1,
+            file_type: language(Path::new(filepath)).unwrap_or_default().to_string(),
+            filepath: filepath.to_string(),
+            lines: source
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index as i64 + 1, line.to_string()))
+                .collect::<HashMap<i64, String>>(),
+        })
+        .collect();
+
+    Parser::new(Lexer::new(files).lex()).parse()
+}
+
+pub fn resolve(sources: &[(&str, &str)]) -> Hierarchy {
+    Resolver::new(&parse(sources)).resolve()
+}
+
+pub fn node<'a>(hierarchy: &'a Hierarchy, identifier: &str) -> &'a HierarchyNode {
+    match hierarchy.find(identifier) {
+        Some(index) => &hierarchy.nodes[index],
+        None => panic!("no object named `{}`", identifier),
+    }
+}
+
+pub fn methods(hierarchy: &Hierarchy, identifier: &str) -> Vec<String> {
+    node(hierarchy, identifier)
+        .object
+        .functions
+        .iter()
+        .map(|function| function.identifier.clone())
+        .collect()
+}
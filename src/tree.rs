@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::structs::{Hierarchy, ObjectKind};
+
+const ABSTRACT_COLOUR: &str = "\x1b[33m"; // yellow
+const INTERFACE_COLOUR: &str = "\x1b[36m"; // cyan
+const RESET: &str = "\x1b[0m";
+
+// Renders a hierarchy like the unix tree command. An object with several parents is listed
+// under each of them, its children only the first time:
+//
+// Base (object)
+// ├── Child [also under Mixin]
+// │   └── GrandChild
+// └── Other
+// Mixin
+// └── Child [see under Base]
+pub struct Tree<'a> {
+    hierarchy: &'a Hierarchy,
+    colour: bool,
+}
+
+impl<'a> Tree<'a> {
+    pub fn new(hierarchy: &'a Hierarchy, colour: bool) -> Tree<'a> {
+        Tree {
+            hierarchy,
+            colour,
+        }
+    }
+
+    pub fn render(&self, starts: &[usize], max_depth: Option<usize>) -> String {
+        let mut tree = String::new();
+        // object -> the parent it was listed with its children under
+        let mut expanded = HashMap::new();
+
+        for start in starts {
+            tree.push_str(&self.name(*start));
+            tree.push_str(&self.notes(*start, None, &expanded));
+            tree.push('\n');
+
+            expanded.insert(*start, None);
+            self.render_children(*start, "", max_depth, &mut vec![*start], &mut expanded, &mut tree);
+        }
+
+        tree
+    }
+
+    // path is the chain of objects from the start down to index
+    fn render_children(
+        &self,
+        index: usize,
+        prefix: &str,
+        max_depth: Option<usize>,
+        path: &mut Vec<usize>,
+        expanded: &mut HashMap<usize, Option<usize>>,
+        tree: &mut String,
+    ) {
+        if max_depth.is_some_and(|max_depth| path.len() > max_depth) {
+            return;
+        }
+
+        let children = &self.hierarchy.nodes[index].children;
+        for (position, child) in children.iter().enumerate() {
+            let last = position == children.len() - 1;
+            let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+
+            tree.push_str(&format!("{}{}{}", prefix, branch, self.name(*child)));
+            tree.push_str(&self.notes(*child, Some(index), expanded));
+
+            // inheritance cycles
+            if path.contains(child) {
+                tree.push_str(" [cycle]\n");
+                continue;
+            }
+            tree.push('\n');
+
+            if expanded.contains_key(child) {
+                continue;
+            }
+            expanded.insert(*child, Some(index));
+
+            path.push(*child);
+            self.render_children(*child, &format!("{}{}", prefix, indent), max_depth, path, expanded, tree);
+            path.pop();
+        }
+    }
+
+    fn name(&self, index: usize) -> String {
+        let object = &self.hierarchy.nodes[index].object;
+
+        let colour = if object.kind == ObjectKind::Interface || object.kind == ObjectKind::Trait {
+            Some(INTERFACE_COLOUR)
+        } else if object.is_abstract {
+            Some(ABSTRACT_COLOUR)
+        } else {
+            None
+        };
+
+        match colour {
+            Some(colour) if self.colour => format!("{}{}{}", colour, object.identifier, RESET),
+            _ => object.identifier.clone(),
+        }
+    }
+
+    // Unresolved parents, and where else an object with several parents is listed
+    fn notes(&self, index: usize, parent: Option<usize>, expanded: &HashMap<usize, Option<usize>>) -> String {
+        let node = &self.hierarchy.nodes[index];
+        let mut notes = String::new();
+
+        if !node.unresolved_parents.is_empty() {
+            notes.push_str(&format!(" ({})", node.unresolved_parents.join(", ")));
+        }
+
+        if node.parents.len() > 1 {
+            if let Some(Some(first)) = expanded.get(&index) {
+                notes.push_str(&format!(" [see under {}]", self.hierarchy.nodes[*first].object.identifier));
+            } else {
+                let others: Vec<&str> = node.parents
+                    .iter()
+                    .filter(|other| Some(**other) != parent)
+                    .map(|other| self.hierarchy.nodes[*other].object.identifier.as_str())
+                    .collect();
+                notes.push_str(&format!(" [also under {}]", others.join(", ")));
+            }
+        }
+
+        notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tree;
+    use crate::testing::resolve;
+
+    const SHAPES: &str = "class Shape(object):\n    pass\n\nclass Mixin:\n    pass\n\nclass Square(Shape, Mixin):\n    pass\n\nclass Cube(Square):\n    pass\n\nclass Circle(Shape):\n    pass\n";
+
+    #[test]
+    fn objects_with_several_parents_are_expanded_once() {
+        let hierarchy = resolve(&[("shapes.py", SHAPES)]);
+        let tree = Tree::new(&hierarchy, false).render(&hierarchy.roots(), None);
+
+        assert_eq!(tree, "Shape (object)\n├── Square [also under Mixin]\n│   └── Cube\n└── Circle\nMixin\n└── Square [see under Shape]\n");
+    }
+
+    #[test]
+    fn a_root_is_shown_down_to_the_depth() {
+        let hierarchy = resolve(&[("shapes.py", SHAPES)]);
+        let shape = hierarchy.find("Shape").unwrap();
+        let tree = Tree::new(&hierarchy, false).render(&[shape], Some(1));
+
+        assert_eq!(tree, "Shape (object)\n├── Square [also under Mixin]\n└── Circle\n");
+    }
+
+    #[test]
+    fn interfaces_and_abstract_classes_are_coloured() {
+        let hierarchy = resolve(&[("shapes.py", "from abc import ABC\n\nclass Shape(ABC):\n    pass\n")]);
+        let tree = Tree::new(&hierarchy, true).render(&hierarchy.roots(), None);
+
+        assert_eq!(tree, "\x1b[33mShape\x1b[0m (ABC)\n");
+    }
+
+    #[test]
+    fn inheritance_cycles_are_cut_short() {
+        let hierarchy = resolve(&[("shapes.py", "class A(B):\n    pass\n\nclass B(A):\n    pass\n")]);
+        let tree = Tree::new(&hierarchy, false).render(&[hierarchy.find("A").unwrap()], None);
+
+        assert_eq!(tree, "A\n└── B\n    └── A [cycle]\n");
+    }
+}
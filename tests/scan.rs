@@ -0,0 +1,21 @@
+use std::path::Path;
+
+use classtree::structs::Severity;
+use classtree::{Lexer, Parser, Resolver, Scanner};
+
+// The crate's own source, which uses most of what the rust front-end reads
+#[test]
+fn the_crate_reads_its_own_source() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let (files, mut diagnostics) = Scanner::new(&root, &[String::from("**/*.rs")], &[]).scan();
+    let mut model = Parser::new(Lexer::new(files).lex()).parse();
+    let mut hierarchy = Resolver::new(&model).resolve();
+    diagnostics.append(&mut model.diagnostics);
+    diagnostics.append(&mut hierarchy.diagnostics);
+
+    let errors: Vec<_> = diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).collect();
+    assert!(errors.is_empty(), "{:?}", errors);
+    for name in ["Object", "Hierarchy", "Parser", "Lexer"] {
+        assert!(hierarchy.find(name).is_some(), "no object named `{}`", name);
+    }
+}